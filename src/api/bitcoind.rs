@@ -0,0 +1,496 @@
+use crate::api::{AtomicalsApi, ScripthashListing};
+use crate::errors::{Error, Result};
+use crate::types::atomicals::AtomicalInfo;
+use crate::wallet::Utxo;
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use bitcoin::psbt::Psbt;
+use bitcoin::{Amount, OutPoint, Script, ScriptBuf, Transaction, TxOut, Txid};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::rc::Rc;
+use std::str::FromStr;
+
+macro_rules! log {
+    ($($t:tt)*) => (log::info!($($t)*))
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    #[serde(default)]
+    result: Value,
+    #[serde(default)]
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct UnspentEntry {
+    txid: String,
+    vout: u32,
+    #[serde(rename = "scriptPubKey")]
+    script_pubkey: String,
+    amount: f64,
+    /// listunspent 返回确认数
+    #[serde(default)]
+    confirmations: Option<u32>,
+    /// scantxoutset 返回所在高度
+    #[serde(default)]
+    height: Option<u32>,
+}
+
+impl UnspentEntry {
+    fn into_utxo(self, height: Option<u32>) -> Result<Utxo> {
+        let txid = Txid::from_str(&self.txid)
+            .map_err(|e| Error::TransactionError(format!("Invalid txid: {}", e)))?;
+        let script_pubkey = ScriptBuf::from_hex(&self.script_pubkey)
+            .map_err(|e| Error::ScriptError(format!("Invalid scriptPubKey: {}", e)))?;
+        let value = Amount::from_btc(self.amount)
+            .map_err(|e| Error::InvalidAmount(format!("Invalid amount {}: {}", self.amount, e)))?;
+        Ok(Utxo { outpoint: OutPoint::new(txid, self.vout), txout: TxOut { value, script_pubkey }, height })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ScanResult {
+    #[serde(default)]
+    unspents: Vec<UnspentEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MempoolAccept {
+    allowed: bool,
+    #[serde(rename = "reject-reason", default)]
+    reject_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProcessedPsbt {
+    psbt: String,
+    complete: bool,
+}
+
+/// Bitcoin Core JSON-RPC 客户端
+#[derive(Debug, Clone)]
+pub struct BitcoindRpc {
+    url: String,
+    auth: Option<(String, String)>,
+    client: reqwest::Client,
+}
+
+impl BitcoindRpc {
+    /// `url` 形如 `http://127.0.0.1:18443`，使用钱包 RPC 时带上 `/wallet/<name>`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            auth: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// `rpcuser` / `rpcpassword` 认证
+    pub fn with_auth(mut self, user: impl Into<String>, password: impl Into<String>) -> Self {
+        self.auth = Some((user.into(), password.into()));
+        self
+    }
+
+    /// 读取节点数据目录下的 `.cookie` 文件认证
+    pub fn with_cookie_file(self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let cookie = std::fs::read_to_string(path)
+            .map_err(|e| Error::WalletError(format!("Failed to read cookie file {}: {}", path.display(), e)))?;
+        let (user, password) = cookie.trim().split_once(':')
+            .ok_or_else(|| Error::WalletError(format!("Invalid cookie file {}", path.display())))?;
+        Ok(self.with_auth(user, password))
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        log!("bitcoind request: {} {}", method, params);
+        let mut request = self.client.post(&self.url)
+            .json(&json!({ "jsonrpc": "1.0", "id": "atomicals-rs", "method": method, "params": params }));
+        if let Some((user, password)) = &self.auth {
+            request = request.basic_auth(user, Some(password));
+        }
+
+        let response = request.send().await
+            .map_err(|e| Error::NetworkError(format!("{} request failed: {}", method, e)))?;
+        // 节点对 RPC 错误返回 500 并带上错误信息，认证失败时没有响应体
+        let status = response.status();
+        let body = response.text().await
+            .map_err(|e| Error::NetworkError(format!("Failed to read {} response: {}", method, e)))?;
+        let response: RpcResponse = serde_json::from_str(&body)
+            .map_err(|_| Error::NetworkError(format!("{} failed with HTTP {}: {}", method, status, body)))?;
+        if let Some(error) = response.error {
+            return Err(Error::NetworkError(format!("{} failed ({}): {}", method, error.code, error.message)));
+        }
+        serde_json::from_value(response.result)
+            .map_err(|e| Error::DeserializationError(format!("Unexpected {} response: {}", method, e)))
+    }
+
+    pub async fn get_block_count(&self) -> Result<u32> {
+        self.call("getblockcount", json!([])).await
+    }
+
+    /// 节点钱包中属于该脚本的 UTXO，包括未确认的
+    pub async fn list_unspent(&self, script_pubkey: &Script) -> Result<Vec<Utxo>> {
+        let tip = self.get_block_count().await?;
+        let entries: Vec<UnspentEntry> = self.call("listunspent", json!([0, 9_999_999])).await?;
+        let script_hex = script_pubkey.to_hex_string();
+        entries.into_iter()
+            .filter(|entry| entry.script_pubkey == script_hex)
+            .map(|entry| {
+                // getblockcount 之后出块时确认数可能超过 tip + 1，此时无法推算高度，按未知处理
+                let height = entry.confirmations.filter(|&confirmations| confirmations > 0)
+                    .and_then(|confirmations| (tip + 1).checked_sub(confirmations));
+                entry.into_utxo(height)
+            })
+            .collect()
+    }
+
+    /// 扫描 UTXO 集合，不需要节点钱包，但只包含已确认的输出
+    pub async fn scan_utxos(&self, script_pubkey: &Script) -> Result<Vec<Utxo>> {
+        let descriptor = format!("raw({})", script_pubkey.to_hex_string());
+        let result: ScanResult = self.call("scantxoutset", json!(["start", [descriptor]])).await?;
+        result.unspents.into_iter()
+            .map(|entry| {
+                let height = entry.height;
+                entry.into_utxo(height)
+            })
+            .collect()
+    }
+
+    /// `estimatesmartfee` 的结果换算为 sat/vB，节点数据不足时返回 None
+    pub async fn estimate_smart_fee(&self, conf_target: u16) -> Result<Option<f64>> {
+        let result: Value = self.call("estimatesmartfee", json!([conf_target])).await?;
+        Ok(result["feerate"].as_f64().map(|btc_per_kvb| btc_per_kvb * 100_000.0))
+    }
+
+    /// 先用 `testmempoolaccept` 检查，拒绝原因作为错误返回
+    pub async fn test_mempool_accept(&self, tx: &Transaction) -> Result<()> {
+        let hex = bitcoin::consensus::encode::serialize_hex(tx);
+        let results: Vec<MempoolAccept> = self.call("testmempoolaccept", json!([[hex]])).await?;
+        match results.first() {
+            Some(result) if result.allowed => Ok(()),
+            Some(result) => Err(Error::TransactionError(format!(
+                "Transaction {} rejected: {}", tx.txid(), result.reject_reason.as_deref().unwrap_or("unknown reason")
+            ))),
+            None => Err(Error::TransactionError("Empty testmempoolaccept response".into())),
+        }
+    }
+
+    pub async fn send_raw_transaction(&self, tx: &Transaction) -> Result<String> {
+        let hex = bitcoin::consensus::encode::serialize_hex(tx);
+        self.call("sendrawtransaction", json!([hex])).await
+    }
+
+    /// 由节点钱包签名并完成 PSBT 中它持有密钥的输入
+    pub async fn wallet_process_psbt(&self, psbt: &Psbt) -> Result<(Psbt, bool)> {
+        let encoded = BASE64.encode(psbt.serialize());
+        let result: ProcessedPsbt = self.call("walletprocesspsbt", json!([encoded, true, "DEFAULT", true])).await?;
+        let bytes = BASE64.decode(result.psbt.as_bytes())
+            .map_err(|e| Error::PsbtError(format!("Failed to decode PSBT: {}", e)))?;
+        let psbt = Psbt::deserialize(&bytes)
+            .map_err(|e| Error::PsbtError(format!("Failed to parse PSBT: {}", e)))?;
+        Ok((psbt, result.complete))
+    }
+}
+
+/// UTXO 的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UtxoSource {
+    /// `scantxoutset`，适用于任意脚本，只包含已确认输出
+    ScanTxOutSet,
+    /// `listunspent`，需要地址已导入节点钱包（可以是观察钱包），包含未确认输出
+    Wallet,
+}
+
+/// 使用 Bitcoin Core 节点的 `AtomicalsApi`
+///
+/// UTXO、费率和广播走节点 RPC，atomical 查询交给 `with_atomicals` 指定的索引器。
+/// UTXO 列表会去掉索引器记录为带有 atomicals 的输出；没有索引器时 `list_utxos` 返回错误，
+/// 除非通过 `allow_unfiltered_utxos` 明确接受未过滤的列表，避免把 atomicals 当作手续费花掉。
+#[derive(Clone)]
+pub struct BitcoindApi {
+    rpc: BitcoindRpc,
+    source: UtxoSource,
+    atomicals: Option<Rc<dyn AtomicalsApi>>,
+    allow_unfiltered: bool,
+}
+
+impl BitcoindApi {
+    pub fn new(rpc: BitcoindRpc) -> Self {
+        Self { rpc, source: UtxoSource::ScanTxOutSet, atomicals: None, allow_unfiltered: false }
+    }
+
+    pub fn with_utxo_source(mut self, source: UtxoSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn with_atomicals(mut self, api: impl AtomicalsApi + 'static) -> Self {
+        self.atomicals = Some(Rc::new(api));
+        self
+    }
+
+    /// 没有索引器时返回节点的全部 UTXO，只适合确定不持有 atomicals 的地址
+    pub fn allow_unfiltered_utxos(mut self) -> Self {
+        self.allow_unfiltered = true;
+        self
+    }
+
+    pub fn rpc(&self) -> &BitcoindRpc {
+        &self.rpc
+    }
+
+    fn atomicals(&self) -> Result<&dyn AtomicalsApi> {
+        self.atomicals.as_deref()
+            .ok_or_else(|| Error::NetworkError("No Atomicals indexer configured for bitcoind backend".into()))
+    }
+}
+
+#[async_trait(?Send)]
+impl AtomicalsApi for BitcoindApi {
+    async fn list_scripthash(&self, scripthash: &str) -> Result<ScripthashListing> {
+        self.atomicals()?.list_scripthash(scripthash).await
+    }
+
+    async fn get_state(&self, atomical_id: &str) -> Result<AtomicalInfo> {
+        self.atomicals()?.get_state(atomical_id).await
+    }
+
+    async fn get_location(&self, atomical_id: &str) -> Result<AtomicalInfo> {
+        self.atomicals()?.get_location(atomical_id).await
+    }
+
+    async fn get_by_realm(&self, realm: &str) -> Result<Value> {
+        self.atomicals()?.get_by_realm(realm).await
+    }
+
+    async fn get_by_ticker(&self, ticker: &str) -> Result<Value> {
+        self.atomicals()?.get_by_ticker(ticker).await
+    }
+
+    async fn get_ft_info(&self, atomical_id: &str) -> Result<Value> {
+        self.atomicals()?.get_ft_info(atomical_id).await
+    }
+
+    async fn broadcast(&self, tx: &Transaction) -> Result<String> {
+        self.rpc.test_mempool_accept(tx).await?;
+        self.rpc.send_raw_transaction(tx).await
+    }
+
+    async fn get_global(&self) -> Result<Value> {
+        self.atomicals()?.get_global().await
+    }
+
+    async fn fee_rate(&self) -> Result<f64> {
+        // regtest 等没有足够数据时节点不返回 feerate
+        let fee_rate = self.rpc.estimate_smart_fee(1).await?.unwrap_or(0.0);
+        let fee_rate = if fee_rate > 0.0 { fee_rate } else { 1.0 };
+        log!("Got fee rate: {} sat/vB", fee_rate);
+        Ok(fee_rate)
+    }
+
+    async fn list_utxos(&self, script_pubkey: &Script) -> Result<Vec<Utxo>> {
+        if self.atomicals.is_none() && !self.allow_unfiltered {
+            return Err(Error::NetworkError(
+                "No Atomicals indexer configured for bitcoind backend, refusing to list unfiltered UTXOs".into(),
+            ));
+        }
+        let utxos = match self.source {
+            UtxoSource::ScanTxOutSet => self.rpc.scan_utxos(script_pubkey).await?,
+            UtxoSource::Wallet => self.rpc.list_unspent(script_pubkey).await?,
+        };
+        match &self.atomicals {
+            Some(api) => api.exclude_atomicals(script_pubkey, utxos).await,
+            None => Ok(utxos),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{test_server, MockAtomicalsApi};
+    use bitcoin::{absolute::LockTime, transaction::Version, Sequence, TxIn, Witness};
+
+    const SCRIPT: &str = "5120d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d";
+    const TXID: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+
+    // 代替节点的 JSON-RPC 服务，要求 user:pass 认证
+    async fn serve(handler: impl Fn(&str, &Value) -> std::result::Result<Value, Value> + Send + 'static) -> String {
+        test_server::serve(move |request| {
+            if !request.headers.contains("authorization: basic dxnlcjpwyxnz") {
+                return (401, String::new());
+            }
+            let request: Value = serde_json::from_slice(&request.body).unwrap();
+            match handler(request["method"].as_str().unwrap(), &request["params"]) {
+                Ok(result) => (200, json!({ "result": result, "error": null, "id": request["id"] }).to_string()),
+                Err(error) => (500, json!({ "result": null, "error": error, "id": request["id"] }).to_string()),
+            }
+        }).await
+    }
+
+    fn node(method: &str, params: &Value) -> std::result::Result<Value, Value> {
+        match method {
+            "getblockcount" => Ok(json!(110)),
+            "listunspent" => Ok(json!([
+                { "txid": TXID, "vout": 0, "scriptPubKey": SCRIPT, "amount": 0.00012345, "confirmations": 0 },
+                { "txid": TXID, "vout": 1, "scriptPubKey": SCRIPT, "amount": 0.5, "confirmations": 10 },
+                { "txid": TXID, "vout": 2, "scriptPubKey": "0014751e76e8199196d454941c45d1b3a323f1433bd6", "amount": 1.0, "confirmations": 10 },
+            ])),
+            "scantxoutset" => {
+                assert_eq!(params, &json!(["start", [format!("raw({})", SCRIPT)]]));
+                Ok(json!({ "success": true, "height": 110, "unspents": [
+                    { "txid": TXID, "vout": 1, "scriptPubKey": SCRIPT, "amount": 0.5, "height": 101 },
+                ]}))
+            }
+            "estimatesmartfee" => Ok(json!({ "feerate": 0.00012, "blocks": 2 })),
+            "testmempoolaccept" => Ok(json!([{ "txid": TXID, "allowed": false, "reject-reason": "min relay fee not met" }])),
+            _ => Err(json!({ "code": -32601, "message": "Method not found" })),
+        }
+    }
+
+    fn rpc(url: &str) -> BitcoindRpc {
+        BitcoindRpc::new(url).with_auth("user", "pass")
+    }
+
+    fn script() -> ScriptBuf {
+        ScriptBuf::from_hex(SCRIPT).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_list_utxos_from_node() {
+        let url = serve(node).await;
+
+        let utxos = BitcoindApi::new(rpc(&url)).with_utxo_source(UtxoSource::Wallet).allow_unfiltered_utxos()
+            .list_utxos(&script()).await.unwrap();
+        assert_eq!(utxos.len(), 2);
+        assert_eq!(utxos[0].txout.value, Amount::from_sat(12_345));
+        assert_eq!(utxos[0].height, None);
+        assert_eq!(utxos[1].height, Some(101));
+
+        let utxos = BitcoindApi::new(rpc(&url)).allow_unfiltered_utxos().list_utxos(&script()).await.unwrap();
+        assert_eq!(utxos.len(), 1);
+        assert_eq!(utxos[0].txout.value, Amount::from_sat(50_000_000));
+        assert_eq!(utxos[0].height, Some(101));
+
+        // 读取区块高度和列出 UTXO 之间出了新块
+        let url = serve(|method, params| match method {
+            "getblockcount" => Ok(json!(5)),
+            "listunspent" => Ok(json!([
+                { "txid": TXID, "vout": 1, "scriptPubKey": SCRIPT, "amount": 0.5, "confirmations": 7 },
+            ])),
+            _ => node(method, params),
+        }).await;
+        let utxos = BitcoindApi::new(rpc(&url)).with_utxo_source(UtxoSource::Wallet).allow_unfiltered_utxos()
+            .list_utxos(&script()).await.unwrap();
+        assert_eq!(utxos[0].height, None);
+    }
+
+    #[tokio::test]
+    async fn test_list_utxos_excludes_atomicals() {
+        // 索引器记录节点返回的其中一个 UTXO 上有 Realm
+        let atomicals = MockAtomicalsApi::new();
+        let realm_id = atomicals.add_realm("mock", &script(), Amount::from_sat(1_000));
+        let realm_txid = realm_id.split('i').next().unwrap().to_string();
+        let url = serve(move |method, params| match method {
+            "scantxoutset" => Ok(json!({ "success": true, "height": 110, "unspents": [
+                { "txid": TXID, "vout": 1, "scriptPubKey": SCRIPT, "amount": 0.5, "height": 101 },
+                { "txid": realm_txid, "vout": 0, "scriptPubKey": SCRIPT, "amount": 0.00001, "height": 101 },
+            ]})),
+            _ => node(method, params),
+        }).await;
+
+        // 没有索引器时默认拒绝列出 UTXO
+        assert!(matches!(BitcoindApi::new(rpc(&url)).list_utxos(&script()).await, Err(Error::NetworkError(_))));
+        let utxos = BitcoindApi::new(rpc(&url)).allow_unfiltered_utxos().list_utxos(&script()).await.unwrap();
+        assert_eq!(utxos.len(), 2);
+
+        let api = BitcoindApi::new(rpc(&url)).with_atomicals(atomicals);
+        let utxos = api.list_utxos(&script()).await.unwrap();
+        assert_eq!(utxos.len(), 1);
+        assert_eq!(utxos[0].outpoint.txid.to_string(), TXID);
+
+        // atomical 查询交给索引器
+        assert_eq!(api.get_state(&realm_id).await.unwrap().atomical_id, realm_id);
+        assert!(BitcoindApi::new(rpc(&url)).get_state(&realm_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fee_rate_and_broadcast() {
+        let url = serve(node).await;
+        let api = BitcoindApi::new(rpc(&url));
+        assert!((api.fee_rate().await.unwrap() - 12.0).abs() < 1e-9);
+
+        let tx = Transaction {
+            version: Version(2),
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn { previous_output: OutPoint::null(), script_sig: ScriptBuf::new(), sequence: Sequence::MAX, witness: Witness::new() }],
+            output: vec![TxOut { value: Amount::from_sat(1_000), script_pubkey: script() }],
+        };
+        match api.broadcast(&tx).await {
+            Err(Error::TransactionError(message)) => assert!(message.contains("min relay fee not met")),
+            other => panic!("unexpected broadcast result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_accepted_broadcast_and_wallet_psbt() {
+        let url = serve(|method, params| match method {
+            "testmempoolaccept" => Ok(json!([{ "txid": TXID, "allowed": true }])),
+            "sendrawtransaction" => Ok(json!(TXID)),
+            // 节点钱包不持有任何密钥，原样返回
+            "walletprocesspsbt" => Ok(json!({ "psbt": params[0], "complete": false })),
+            _ => node(method, params),
+        }).await;
+        let api = BitcoindApi::new(rpc(&url));
+
+        let tx = Transaction {
+            version: Version(2),
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn { previous_output: OutPoint::new(Txid::from_str(TXID).unwrap(), 0), script_sig: ScriptBuf::new(), sequence: Sequence::MAX, witness: Witness::new() }],
+            output: vec![TxOut { value: Amount::from_sat(1_000), script_pubkey: script() }],
+        };
+        assert_eq!(api.broadcast(&tx).await.unwrap(), TXID);
+
+        let psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        let (processed, complete) = api.rpc().wallet_process_psbt(&psbt).await.unwrap();
+        assert_eq!(processed, psbt);
+        assert!(!complete);
+    }
+
+    #[tokio::test]
+    async fn test_rpc_errors() {
+        let url = serve(node).await;
+        match rpc(&url).call::<Value>("getbestblockhash", json!([])).await {
+            Err(Error::NetworkError(message)) => assert!(message.contains("Method not found")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match BitcoindRpc::new(&url).with_auth("user", "wrong").get_block_count().await {
+            Err(Error::NetworkError(message)) => assert!(message.contains("401")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cookie_file() {
+        let path = std::env::temp_dir().join(format!("atomicals-rs-cookie-{}", std::process::id()));
+        std::fs::write(&path, "__cookie__:secret\n").unwrap();
+        let rpc = BitcoindRpc::new("http://127.0.0.1:18443").with_cookie_file(&path).unwrap();
+        assert_eq!(rpc.auth, Some(("__cookie__".to_string(), "secret".to_string())));
+        std::fs::remove_file(&path).unwrap();
+        assert!(BitcoindRpc::new("http://127.0.0.1:18443").with_cookie_file(&path).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use crate::api::{AtomicalsApi, EsploraClient, ScripthashListing};
+use crate::errors::{Error, Result};
+use crate::types::atomicals::AtomicalInfo;
+use crate::types::AtomicalsNetwork;
+use async_trait::async_trait;
+use bitcoin::Transaction;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+#[cfg(target_arch = "wasm32")]
+macro_rules! log {
+    ($($t:tt)*) => (web_sys::console::log_1(&format!($($t)*).into()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+macro_rules! log {
+    ($($t:tt)*) => (log::info!($($t)*))
+}
+
+/// 通过 HTTP 代理（`POST {base_url}/{method}`，请求体 `{"params": [...]}`）访问 Atomicals ElectrumX
+///
+/// 代理返回 `{"success": bool, "response": ...}`。配置了 mempool.space / Esplora 地址时
+/// 使用其 `/fee-estimates`，否则使用 `blockchain.estimatefee`。
+#[derive(Debug, Clone)]
+pub struct ElectrumApi {
+    base_url: String,
+    fees: Option<EsploraClient>,
+    client: reqwest::Client,
+}
+
+impl ElectrumApi {
+    /// `base_url` 形如 `https://ep.wizz.cash/proxy`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            fees: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// mempool.space / Esplora 费率接口，形如 `https://mempool.space/api`
+    pub fn with_mempool_url(mut self, mempool_url: impl Into<String>) -> Self {
+        self.fees = Some(EsploraClient::new(mempool_url));
+        self
+    }
+
+    /// 网络对应的公共服务；没有公共 ElectrumX 代理的网络返回 None
+    pub fn for_network(network: impl Into<AtomicalsNetwork>) -> Option<Self> {
+        let network = network.into();
+        let api = Self::new(network.electrumx_url()?);
+        Some(match network.mempool_url() {
+            Some(mempool_url) => api.with_mempool_url(mempool_url),
+            None => api,
+        })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let url = format!("{}/{}", self.base_url, method);
+        log!("ElectrumX request: {} {}", url, params);
+
+        let response = self.client.post(&url)
+            .json(&json!({ "params": params }))
+            .send().await
+            .map_err(|e| Error::NetworkError(format!("{} request failed: {}", method, e)))?;
+        let body: Value = response.json().await
+            .map_err(|e| Error::DeserializationError(format!("Failed to parse {} response: {}", method, e)))?;
+        decode_response(method, body)
+    }
+
+    async fn call_as<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let response = self.call(method, params).await?;
+        serde_json::from_value(response)
+            .map_err(|e| Error::DeserializationError(format!("Unexpected {} response: {}", method, e)))
+    }
+
+    async fn atomical(&self, method: &str, atomical_id: &str) -> Result<AtomicalInfo> {
+        let response = self.call(method, json!([atomical_id])).await
+            .map_err(|e| Error::AtomicalNotFound(format!("Atomical {}: {}", atomical_id, e)))?;
+        let mut info: AtomicalInfo = serde_json::from_value(result_field(response, "result"))
+            .map_err(|e| Error::DeserializationError(format!("Unexpected {} response: {}", method, e)))?;
+
+        // 确保 location 字段格式为 txid:vout
+        for location in info.location_info.iter_mut() {
+            if !location.location.contains(':') {
+                location.location = format!("{}:{}", location.txid, location.index);
+            }
+        }
+        Ok(info)
+    }
+}
+
+// 取出代理响应中的 `response` 字段
+fn decode_response(method: &str, body: Value) -> Result<Value> {
+    let success = body.get("success").and_then(Value::as_bool).unwrap_or(false);
+    let response = body.get("response").cloned().unwrap_or(Value::Null);
+    if !success {
+        return Err(Error::NetworkError(format!("{} rejected: {}", method, response)));
+    }
+    Ok(response)
+}
+
+// 多数查询把结果放在 `result`（或 `global`）字段中
+fn result_field(mut response: Value, field: &str) -> Value {
+    match response.get_mut(field) {
+        Some(value) => value.take(),
+        None => response,
+    }
+}
+
+#[async_trait(?Send)]
+impl AtomicalsApi for ElectrumApi {
+    async fn list_scripthash(&self, scripthash: &str) -> Result<ScripthashListing> {
+        self.call_as("blockchain.atomicals.listscripthash", json!([scripthash, true])).await
+    }
+
+    async fn get_state(&self, atomical_id: &str) -> Result<AtomicalInfo> {
+        self.atomical("blockchain.atomicals.get_state", atomical_id).await
+    }
+
+    async fn get_location(&self, atomical_id: &str) -> Result<AtomicalInfo> {
+        self.atomical("blockchain.atomicals.get_location", atomical_id).await
+    }
+
+    async fn get_by_realm(&self, realm: &str) -> Result<Value> {
+        let response = self.call("blockchain.atomicals.get_by_realm", json!([realm])).await?;
+        Ok(result_field(response, "result"))
+    }
+
+    async fn get_by_ticker(&self, ticker: &str) -> Result<Value> {
+        let response = self.call("blockchain.atomicals.get_by_ticker", json!([ticker])).await?;
+        Ok(result_field(response, "result"))
+    }
+
+    async fn get_ft_info(&self, atomical_id: &str) -> Result<Value> {
+        let response = self.call("blockchain.atomicals.get_ft_info", json!([atomical_id])).await?;
+        Ok(result_field(response, "result"))
+    }
+
+    async fn broadcast(&self, tx: &Transaction) -> Result<String> {
+        let hex = bitcoin::consensus::encode::serialize_hex(tx);
+        match self.call("blockchain.transaction.broadcast", json!([hex])).await? {
+            Value::String(txid) => Ok(txid),
+            response => Err(Error::NetworkError(format!("Unexpected broadcast response: {}", response))),
+        }
+    }
+
+    async fn get_global(&self) -> Result<Value> {
+        let response = self.call("blockchain.atomicals.get_global", json!([])).await?;
+        Ok(result_field(response, "global"))
+    }
+
+    async fn fee_rate(&self) -> Result<f64> {
+        let fee_rate = match &self.fees {
+            // 下一个区块确认
+            Some(fees) => fees.fee_rate(1).await?.unwrap_or(0.0),
+            // BTC/kvB 换算为 sat/vB，节点无法估算时返回 -1
+            None => {
+                let btc_per_kvb: f64 = self.call_as("blockchain.estimatefee", json!([1])).await?;
+                btc_per_kvb * 100_000.0
+            }
+        };
+
+        let fee_rate = if fee_rate > 0.0 { fee_rate } else { 1.0 };
+        log!("Got fee rate: {} sat/vB", fee_rate);
+        Ok(fee_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::Network;
+
+    #[test]
+    fn test_decode_response() {
+        let body = json!({"success": true, "response": {"result": {"atomical_id": "abc"}}});
+        let response = decode_response("blockchain.atomicals.get_state", body).unwrap();
+        assert_eq!(result_field(response, "result"), json!({"atomical_id": "abc"}));
+
+        let body = json!({"success": false, "response": "not found"});
+        assert!(matches!(decode_response("blockchain.atomicals.get_state", body), Err(Error::NetworkError(_))));
+        assert!(decode_response("blockchain.atomicals.get_state", json!({})).is_err());
+    }
+
+    #[test]
+    fn test_result_field_falls_back_to_response() {
+        assert_eq!(result_field(json!({"global": {"height": 1}}), "global"), json!({"height": 1}));
+        assert_eq!(result_field(json!({"height": 1}), "global"), json!({"height": 1}));
+    }
+
+    #[test]
+    fn test_base_url_is_configurable() {
+        assert_eq!(ElectrumApi::new("http://localhost:8080/proxy/").base_url(), "http://localhost:8080/proxy");
+        assert_eq!(ElectrumApi::for_network(Network::Bitcoin).unwrap().base_url(), "https://ep.wizz.cash/proxy");
+        assert_eq!(ElectrumApi::for_network(Network::Testnet).unwrap().base_url(), "https://eptestnet4.wizz.cash/proxy");
+        assert!(ElectrumApi::for_network(AtomicalsNetwork::Signet).is_none());
+        assert!(ElectrumApi::for_network(Network::Regtest).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fee_rate_from_fee_estimates() {
+        let url = crate::api::test_server::serve(|request| match request.path.as_str() {
+            "/fee-estimates" => (200, json!({ "1": 4.5, "2": 3.5, "144": 1.0 }).to_string()),
+            _ => (404, String::new()),
+        }).await;
+        // 配置了费率接口时不请求 ElectrumX
+        assert_eq!(ElectrumApi::new("http://127.0.0.1:9").with_mempool_url(&url).fee_rate().await.unwrap(), 4.5);
+    }
+}
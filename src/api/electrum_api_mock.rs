@@ -0,0 +1,633 @@
+use crate::api::{scripthash, AtomicalsApi, ScripthashListing, ScripthashUtxo};
+use crate::errors::{Error, Result};
+use crate::types::atomicals::{
+    AtomicalInfo, AtomicalState, LatestState, LocationInfo, SubrealmRule, SubrealmsState,
+    ATOMICALS_PROTOCOL_ENVELOPE,
+};
+use crate::utils::sleep_ms;
+use async_trait::async_trait;
+use bitcoin::blockdata::script::Instruction;
+use bitcoin::hashes::Hash;
+use bitcoin::key::XOnlyPublicKey;
+use bitcoin::opcodes::all::OP_CHECKSIG;
+use bitcoin::secp256k1::{Message, Secp256k1};
+use bitcoin::sighash::{Prevouts, SighashCache, TapSighash};
+use bitcoin::taproot::{self, ControlBlock, TapLeafHash};
+use bitcoin::{
+    absolute::LockTime, transaction::Version, Amount, OutPoint, Script, ScriptBuf, Sequence,
+    Transaction, TxIn, TxOut, Txid, Witness,
+};
+use serde_json::{json, Map, Value};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+/// reveal 交易中解析出的 atomicals 信封
+#[derive(Debug, Clone)]
+pub struct MockEnvelope {
+    /// 操作类型，如 `nft`、`dmt`
+    pub op: String,
+    /// CBOR payload 解码后的 JSON，无法解码时为 Null
+    pub payload: Value,
+    /// 被花费的 commit 输出，新 atomical 的 ID 由它得出
+    pub commit: OutPoint,
+    pub reveal_txid: Txid,
+}
+
+#[derive(Debug, Clone)]
+struct MockUtxo {
+    txout: TxOut,
+    /// 0 表示仍在内存池中
+    height: u32,
+    atomicals: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct MockAtomical {
+    atomical_type: String,
+    subtype: Option<String>,
+    /// FT 分散在多个输出上，只跟踪 NFT 的位置
+    location: Option<OutPoint>,
+    state: Option<AtomicalState>,
+}
+
+#[derive(Debug)]
+struct MockChain {
+    height: u32,
+    fee_rate: f64,
+    utxos: BTreeMap<OutPoint, MockUtxo>,
+    transactions: HashMap<Txid, Transaction>,
+    mempool: Vec<Txid>,
+    atomicals: BTreeMap<String, MockAtomical>,
+    realms: HashMap<String, String>,
+    tickers: HashMap<String, String>,
+    ft_info: HashMap<String, Value>,
+    envelopes: Vec<MockEnvelope>,
+    funded: u32,
+    latency_ms: i32,
+}
+
+/// 内存中的 Atomicals 索引器和区块链，用于不联网的端到端测试
+///
+/// 克隆出的实例共享同一条链，测试可以把一份交给钱包，另一份用来布置状态和检查结果。
+/// 广播时检查输入存在且未花费、金额不超支，并验证 taproot 签名（key path 和
+/// `<pubkey> OP_CHECKSIG ...` 形式的 script path），通过后交易进入内存池，
+/// 输入上的 atomicals 移到同序号的输出（没有则移到第一个输出）。
+/// reveal 交易中的 `nft` 信封按 `request_realm` / `request_subrealm` 登记新的 Realm，
+/// `dmt` 信封把对应 ticker 的 FT 放到第一个输出上。
+#[derive(Debug, Clone)]
+pub struct MockAtomicalsApi {
+    chain: Rc<RefCell<MockChain>>,
+}
+
+impl Default for MockAtomicalsApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockAtomicalsApi {
+    pub fn new() -> Self {
+        Self {
+            chain: Rc::new(RefCell::new(MockChain {
+                height: 1,
+                fee_rate: 1.0,
+                utxos: BTreeMap::new(),
+                transactions: HashMap::new(),
+                mempool: Vec::new(),
+                atomicals: BTreeMap::new(),
+                realms: HashMap::new(),
+                tickers: HashMap::new(),
+                ft_info: HashMap::new(),
+                envelopes: Vec::new(),
+                funded: 0,
+                latency_ms: 0,
+            })),
+        }
+    }
+
+    pub fn set_fee_rate(&self, fee_rate: f64) {
+        self.chain.borrow_mut().fee_rate = fee_rate;
+    }
+
+    /// UTXO 查询、费率和广播在返回前等待的时间，用于模拟网络往返让并发操作交错执行
+    pub fn set_latency_ms(&self, latency_ms: i32) {
+        self.chain.borrow_mut().latency_ms = latency_ms;
+    }
+
+    async fn delay(&self) {
+        let latency_ms = self.chain.borrow().latency_ms;
+        if latency_ms > 0 {
+            sleep_ms(latency_ms).await;
+        }
+    }
+
+    pub fn height(&self) -> u32 {
+        self.chain.borrow().height
+    }
+
+    /// 向脚本发送一笔已确认的资金，返回其位置
+    pub fn fund(&self, script_pubkey: &Script, value: Amount) -> OutPoint {
+        self.chain.borrow_mut().fund(script_pubkey, value)
+    }
+
+    /// 在脚本上放置一个已确认的 Realm，返回其 atomical ID
+    pub fn add_realm(&self, name: &str, script_pubkey: &Script, value: Amount) -> String {
+        let mut chain = self.chain.borrow_mut();
+        let location = chain.fund(script_pubkey, value);
+        let atomical_id = atomical_id(&location);
+        chain.register_nft(&atomical_id, "realm", location);
+        chain.realms.insert(name.to_string(), atomical_id.clone());
+        atomical_id
+    }
+
+    /// 设置 Realm 的 subrealm 铸造规则
+    pub fn set_subrealm_rules(&self, atomical_id: &str, rules: Vec<SubrealmRule>) -> Result<()> {
+        let mut chain = self.chain.borrow_mut();
+        let atomical = chain.atomicals.get_mut(atomical_id)
+            .ok_or_else(|| Error::AtomicalNotFound(atomical_id.to_string()))?;
+        atomical.state = Some(AtomicalState {
+            latest: Some(LatestState { subrealms: Some(SubrealmsState { rules }) }),
+        });
+        Ok(())
+    }
+
+    /// 登记一个可铸造的 ticker，`ft_info` 作为 `get_ft_info` 的结果，返回其 atomical ID
+    pub fn add_ticker(&self, ticker: &str, ft_info: Value) -> String {
+        let mut chain = self.chain.borrow_mut();
+        let deploy = OutPoint::new(Txid::hash(ticker.as_bytes()), 0);
+        let atomical_id = atomical_id(&deploy);
+        chain.atomicals.insert(atomical_id.clone(), MockAtomical {
+            atomical_type: "FT".to_string(),
+            subtype: Some("decentralized".to_string()),
+            location: None,
+            state: None,
+        });
+
+        let mut info = match ft_info {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        };
+        info.insert("atomical_id".to_string(), json!(atomical_id));
+        info.insert("$ticker".to_string(), json!(ticker));
+        chain.ft_info.insert(atomical_id.clone(), Value::Object(info));
+        chain.tickers.insert(ticker.to_string(), atomical_id.clone());
+        atomical_id
+    }
+
+    /// 把内存池中的交易打包进新区块，返回打包的 txid
+    pub fn mine_block(&self) -> Vec<Txid> {
+        let mut chain = self.chain.borrow_mut();
+        chain.height += 1;
+        let height = chain.height;
+        let txids = std::mem::take(&mut chain.mempool);
+        for utxo in chain.utxos.values_mut().filter(|utxo| utxo.height == 0) {
+            utxo.height = height;
+        }
+        txids
+    }
+
+    /// 按广播顺序返回内存池中的交易
+    pub fn mempool(&self) -> Vec<Transaction> {
+        let chain = self.chain.borrow();
+        chain.mempool.iter().map(|txid| chain.transactions[txid].clone()).collect()
+    }
+
+    pub fn transaction(&self, txid: &Txid) -> Option<Transaction> {
+        self.chain.borrow().transactions.get(txid).cloned()
+    }
+
+    /// 未花费输出，已花费或不存在时返回 None
+    pub fn utxo(&self, outpoint: &OutPoint) -> Option<TxOut> {
+        self.chain.borrow().utxos.get(outpoint).map(|utxo| utxo.txout.clone())
+    }
+
+    pub fn atomicals_at(&self, outpoint: &OutPoint) -> Vec<String> {
+        self.chain.borrow().utxos.get(outpoint).map(|utxo| utxo.atomicals.clone()).unwrap_or_default()
+    }
+
+    pub fn realm_id(&self, name: &str) -> Option<String> {
+        self.chain.borrow().realms.get(name).cloned()
+    }
+
+    pub fn envelopes(&self) -> Vec<MockEnvelope> {
+        self.chain.borrow().envelopes.clone()
+    }
+}
+
+fn atomical_id(location: &OutPoint) -> String {
+    format!("{}i{}", location.txid, location.vout)
+}
+
+impl MockChain {
+    fn fund(&mut self, script_pubkey: &Script, value: Amount) -> OutPoint {
+        // 每笔资金交易的 coinbase 脚本不同，保证 txid 唯一
+        self.funded += 1;
+        let tx = Transaction {
+            version: Version(2),
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::builder().push_int(self.funded as i64).into_script(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut { value, script_pubkey: script_pubkey.to_owned() }],
+        };
+        let outpoint = OutPoint::new(tx.txid(), 0);
+        self.utxos.insert(outpoint, MockUtxo { txout: tx.output[0].clone(), height: self.height, atomicals: Vec::new() });
+        self.transactions.insert(outpoint.txid, tx);
+        outpoint
+    }
+
+    fn register_nft(&mut self, atomical_id: &str, subtype: &str, location: OutPoint) {
+        if let Some(utxo) = self.utxos.get_mut(&location) {
+            utxo.atomicals.push(atomical_id.to_string());
+        }
+        self.atomicals.insert(atomical_id.to_string(), MockAtomical {
+            atomical_type: "NFT".to_string(),
+            subtype: Some(subtype.to_string()),
+            location: Some(location),
+            state: None,
+        });
+    }
+
+    fn accept(&mut self, tx: &Transaction) -> Result<Txid> {
+        let txid = tx.txid();
+        if self.transactions.contains_key(&txid) {
+            return Err(Error::TransactionError(format!("Transaction {} already known", txid)));
+        }
+
+        let prevouts = tx.input.iter()
+            .map(|input| {
+                self.utxos.get(&input.previous_output)
+                    .map(|utxo| utxo.txout.clone())
+                    .ok_or_else(|| Error::TransactionError(format!("Input {} is missing or spent", input.previous_output)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let input_value: Amount = prevouts.iter().map(|txout| txout.value).sum();
+        let output_value: Amount = tx.output.iter().map(|txout| txout.value).sum();
+        if output_value > input_value {
+            return Err(Error::TransactionError(format!("Outputs {} exceed inputs {}", output_value, input_value)));
+        }
+
+        for index in 0..tx.input.len() {
+            verify_taproot_input(tx, index, &prevouts)?;
+        }
+
+        // 花费输入，atomicals 移到同序号的输出
+        let mut moved: Vec<(u32, String)> = Vec::new();
+        for (index, input) in tx.input.iter().enumerate() {
+            if let Some(spent) = self.utxos.remove(&input.previous_output) {
+                let vout = if index < tx.output.len() { index as u32 } else { 0 };
+                moved.extend(spent.atomicals.into_iter().map(|id| (vout, id)));
+            }
+        }
+        for (vout, txout) in tx.output.iter().enumerate() {
+            self.utxos.insert(OutPoint::new(txid, vout as u32), MockUtxo { txout: txout.clone(), height: 0, atomicals: Vec::new() });
+        }
+        for (vout, atomical_id) in moved {
+            let location = OutPoint::new(txid, vout);
+            if let Some(utxo) = self.utxos.get_mut(&location) {
+                utxo.atomicals.push(atomical_id.clone());
+            }
+            if let Some(atomical) = self.atomicals.get_mut(&atomical_id) {
+                if atomical.location.is_some() {
+                    atomical.location = Some(location);
+                }
+            }
+        }
+
+        for input in &tx.input {
+            if let Some((op, payload)) = script_path_leaf(&input.witness).and_then(parse_envelope) {
+                let payload = serde_cbor::from_slice::<Value>(&payload).unwrap_or(Value::Null);
+                self.apply_envelope(MockEnvelope { op, payload, commit: input.previous_output, reveal_txid: txid });
+            }
+        }
+
+        self.transactions.insert(txid, tx.clone());
+        self.mempool.push(txid);
+        Ok(txid)
+    }
+
+    // 新铸造的 atomical 位于 reveal 的第一个输出
+    fn apply_envelope(&mut self, envelope: MockEnvelope) {
+        let location = OutPoint::new(envelope.reveal_txid, 0);
+        let args = &envelope.payload["args"];
+        match envelope.op.as_str() {
+            "nft" => {
+                let atomical_id = atomical_id(&envelope.commit);
+                if let Some(realm) = args["request_realm"].as_str() {
+                    self.register_nft(&atomical_id, "realm", location);
+                    self.realms.entry(realm.to_string()).or_insert(atomical_id);
+                } else if let Some(subrealm) = args["request_subrealm"].as_str() {
+                    self.register_nft(&atomical_id, "subrealm", location);
+                    let parent = args["parent_realm"].as_str().unwrap_or_default();
+                    let parent_name = self.realms.iter()
+                        .find(|(_, id)| id.as_str() == parent)
+                        .map(|(name, _)| name.clone());
+                    if let Some(parent_name) = parent_name {
+                        self.realms.entry(format!("{}.{}", parent_name, subrealm)).or_insert(atomical_id);
+                    }
+                } else {
+                    self.register_nft(&atomical_id, "nft", location);
+                }
+            }
+            "dmt" => {
+                let ticker_id = args["mint_ticker"].as_str().and_then(|ticker| self.tickers.get(ticker)).cloned();
+                if let (Some(ticker_id), Some(utxo)) = (ticker_id, self.utxos.get_mut(&location)) {
+                    utxo.atomicals.push(ticker_id);
+                }
+            }
+            _ => {}
+        }
+        self.envelopes.push(envelope);
+    }
+
+    fn info(&self, atomical_id: &str) -> Result<AtomicalInfo> {
+        let atomical = self.atomicals.get(atomical_id)
+            .ok_or_else(|| Error::AtomicalNotFound(atomical_id.to_string()))?;
+        let current = atomical.location
+            .and_then(|location| self.utxos.get(&location).map(|utxo| (location, utxo)));
+
+        Ok(AtomicalInfo {
+            atomical_id: atomical_id.to_string(),
+            confirmed: current.is_some_and(|(_, utxo)| utxo.height > 0),
+            location_info: current.into_iter()
+                .map(|(location, utxo)| LocationInfo {
+                    atomicals_at_location: utxo.atomicals.clone(),
+                    index: location.vout,
+                    location: location.to_string(),
+                    script: utxo.txout.script_pubkey.to_hex_string(),
+                    scripthash: scripthash(&utxo.txout.script_pubkey),
+                    tx_num: 0,
+                    txid: location.txid.to_string(),
+                    value: utxo.txout.value.to_sat(),
+                })
+                .collect(),
+            state: atomical.state.clone(),
+            subtype: atomical.subtype.clone(),
+            atomical_type: atomical.atomical_type.clone(),
+        })
+    }
+}
+
+// script path 花费的叶子脚本：见证为 [签名, 脚本, 控制块]
+fn script_path_leaf(witness: &Witness) -> Option<&Script> {
+    match witness.len() {
+        3 => witness.nth(1).map(Script::from_bytes),
+        _ => None,
+    }
+}
+
+// 解析 `OP_IF "atom" <op> <payload...> OP_ENDIF` 信封
+fn parse_envelope(script: &Script) -> Option<(String, Vec<u8>)> {
+    let mut instructions = script.instructions()
+        .skip_while(|instruction| !matches!(instruction, Ok(Instruction::PushBytes(bytes)) if bytes.as_bytes() == ATOMICALS_PROTOCOL_ENVELOPE))
+        .skip(1);
+    let op = match instructions.next()? {
+        Ok(Instruction::PushBytes(bytes)) => String::from_utf8(bytes.as_bytes().to_vec()).ok()?,
+        _ => return None,
+    };
+    let mut payload = Vec::new();
+    for instruction in instructions {
+        match instruction.ok()? {
+            Instruction::PushBytes(bytes) => payload.extend_from_slice(bytes.as_bytes()),
+            Instruction::Op(_) => break,
+        }
+    }
+    Some((op, payload))
+}
+
+fn verify_taproot_input(tx: &Transaction, index: usize, prevouts: &[TxOut]) -> Result<()> {
+    let invalid = |reason: String| Error::TransactionError(format!("Input {} of {}: {}", index, tx.txid(), reason));
+
+    let script_pubkey = &prevouts[index].script_pubkey;
+    if !script_pubkey.is_p2tr() {
+        return Err(invalid("only taproot inputs are supported".into()));
+    }
+    let output_key = XOnlyPublicKey::from_slice(&script_pubkey.as_bytes()[2..])
+        .map_err(|e| invalid(e.to_string()))?;
+
+    let secp = Secp256k1::verification_only();
+    let witness = &tx.input[index].witness;
+    let signature = witness.nth(0)
+        .ok_or_else(|| invalid("empty witness".into()))
+        .and_then(|bytes| taproot::Signature::from_slice(bytes).map_err(|e| invalid(e.to_string())))?;
+    let prevouts = Prevouts::All(prevouts);
+    let mut cache = SighashCache::new(tx);
+
+    let (sighash, pubkey): (TapSighash, XOnlyPublicKey) = match script_path_leaf(witness) {
+        None if witness.len() == 1 => {
+            let sighash = cache.taproot_key_spend_signature_hash(index, &prevouts, signature.hash_ty)
+                .map_err(|e| invalid(e.to_string()))?;
+            (sighash, output_key)
+        }
+        Some(script) => {
+            let control_block = witness.nth(2)
+                .ok_or_else(|| invalid("missing control block".into()))
+                .and_then(|bytes| ControlBlock::decode(bytes).map_err(|e| invalid(e.to_string())))?;
+            if !control_block.verify_taproot_commitment(&secp, output_key, script) {
+                return Err(invalid("control block does not commit to the output key".into()));
+            }
+            let pubkey = checksig_key(script).ok_or_else(|| invalid("unsupported tapscript".into()))?;
+            let leaf_hash = TapLeafHash::from_script(script, control_block.leaf_version);
+            let sighash = cache.taproot_script_spend_signature_hash(index, &prevouts, leaf_hash, signature.hash_ty)
+                .map_err(|e| invalid(e.to_string()))?;
+            (sighash, pubkey)
+        }
+        None => return Err(invalid(format!("unsupported witness with {} elements", witness.len()))),
+    };
+
+    let message = Message::from_digest(sighash.to_byte_array());
+    secp.verify_schnorr(&signature.sig, &message, &pubkey)
+        .map_err(|e| invalid(format!("invalid signature: {}", e)))
+}
+
+// reveal 脚本以 `<x-only pubkey> OP_CHECKSIG` 开头
+fn checksig_key(script: &Script) -> Option<XOnlyPublicKey> {
+    let mut instructions = script.instructions();
+    let pubkey = match instructions.next()?.ok()? {
+        Instruction::PushBytes(bytes) => XOnlyPublicKey::from_slice(bytes.as_bytes()).ok()?,
+        _ => return None,
+    };
+    match instructions.next()?.ok()? {
+        Instruction::Op(op) if op == OP_CHECKSIG => Some(pubkey),
+        _ => None,
+    }
+}
+
+#[async_trait(?Send)]
+impl AtomicalsApi for MockAtomicalsApi {
+    async fn list_scripthash(&self, scripthash_hex: &str) -> Result<ScripthashListing> {
+        self.delay().await;
+        let chain = self.chain.borrow();
+        let mut atomicals = Map::new();
+        let utxos = chain.utxos.iter()
+            .filter(|(_, utxo)| scripthash(&utxo.txout.script_pubkey) == scripthash_hex)
+            .map(|(outpoint, utxo)| {
+                for atomical_id in &utxo.atomicals {
+                    atomicals.insert(atomical_id.clone(), json!({ "atomical_id": atomical_id }));
+                }
+                ScripthashUtxo {
+                    txid: outpoint.txid.to_string(),
+                    vout: outpoint.vout,
+                    value: utxo.txout.value.to_sat(),
+                    height: utxo.height,
+                    atomicals: json!(utxo.atomicals),
+                }
+            })
+            .collect();
+        Ok(ScripthashListing { utxos, atomicals: Value::Object(atomicals) })
+    }
+
+    async fn get_state(&self, atomical_id: &str) -> Result<AtomicalInfo> {
+        self.chain.borrow().info(atomical_id)
+    }
+
+    async fn get_location(&self, atomical_id: &str) -> Result<AtomicalInfo> {
+        self.chain.borrow().info(atomical_id)
+    }
+
+    async fn get_by_realm(&self, realm: &str) -> Result<Value> {
+        Ok(json!({ "atomical_id": self.chain.borrow().realms.get(realm), "candidates": [] }))
+    }
+
+    async fn get_by_ticker(&self, ticker: &str) -> Result<Value> {
+        Ok(json!({ "atomical_id": self.chain.borrow().tickers.get(ticker), "candidates": [] }))
+    }
+
+    async fn get_ft_info(&self, atomical_id: &str) -> Result<Value> {
+        self.chain.borrow().ft_info.get(atomical_id).cloned()
+            .ok_or_else(|| Error::AtomicalNotFound(atomical_id.to_string()))
+    }
+
+    async fn broadcast(&self, tx: &Transaction) -> Result<String> {
+        self.delay().await;
+        self.chain.borrow_mut().accept(tx).map(|txid| txid.to_string())
+    }
+
+    async fn get_global(&self) -> Result<Value> {
+        let chain = self.chain.borrow();
+        Ok(json!({ "height": chain.height, "atomical_count": chain.atomicals.len() }))
+    }
+
+    async fn fee_rate(&self) -> Result<f64> {
+        self.delay().await;
+        Ok(self.chain.borrow().fee_rate)
+    }
+}
+
+/// 主密钥和资金密钥按 atomicals-js 的派生路径分开、都连到模拟链的测试钱包
+#[cfg(test)]
+pub(crate) fn mock_wallet(api: &MockAtomicalsApi, network: bitcoin::Network) -> crate::wallet::DualWallet<crate::wallet::HdWallet, crate::wallet::HdWallet> {
+    use crate::wallet::hd::{bip86_funding_path, bip86_path};
+    use crate::wallet::{DualWallet, HdWallet};
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    let key = |path| HdWallet::from_mnemonic_at_path(MNEMONIC, "", network, path).unwrap().with_api(api.clone());
+    DualWallet::new(key(bip86_path(network, 0, 0).unwrap()), key(bip86_funding_path(network, 0).unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::{HdWallet, WalletProvider};
+    use bitcoin::psbt::Psbt;
+    use bitcoin::Network;
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn spend(from: OutPoint, txout: TxOut, to: &Script, value: u64) -> Psbt {
+        let tx = Transaction {
+            version: Version(2),
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn { previous_output: from, script_sig: ScriptBuf::new(), sequence: Sequence::MAX, witness: Witness::new() }],
+            output: vec![TxOut { value: Amount::from_sat(value), script_pubkey: to.to_owned() }],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        psbt.inputs[0].witness_utxo = Some(txout);
+        psbt
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_checks_inputs_and_signatures() {
+        let api = MockAtomicalsApi::new();
+        let wallet = HdWallet::from_mnemonic(MNEMONIC, "", Network::Regtest).unwrap().with_api(api.clone());
+        let script = wallet.address().script_pubkey();
+        let funding = api.fund(&script, Amount::from_sat(10_000));
+        let txout = api.utxo(&funding).unwrap();
+
+        // 未签名
+        let unsigned = spend(funding, txout.clone(), &script, 9_000).unsigned_tx;
+        assert!(api.broadcast(&unsigned).await.is_err());
+
+        // 签名后修改输出，签名失效
+        let mut tampered = wallet.sign_psbt(spend(funding, txout.clone(), &script, 9_000)).await.unwrap().extract_tx().unwrap();
+        tampered.output[0].value = Amount::from_sat(8_000);
+        assert!(api.broadcast(&tampered).await.is_err());
+
+        let tx = wallet.sign_psbt(spend(funding, txout.clone(), &script, 9_000)).await.unwrap().extract_tx().unwrap();
+        let txid = wallet.broadcast_transaction(tx.clone()).await.unwrap();
+        assert_eq!(txid, tx.txid().to_string());
+        assert!(api.utxo(&funding).is_none());
+        assert_eq!(api.mempool().len(), 1);
+
+        // 双花
+        let double_spend = wallet.sign_psbt(spend(funding, txout, &script, 8_000)).await.unwrap().extract_tx().unwrap();
+        assert!(api.broadcast(&double_spend).await.is_err());
+
+        // 内存池中的输出可以继续花费，打包后确认
+        let change = OutPoint::new(tx.txid(), 0);
+        let utxos = wallet.get_utxos().await.unwrap();
+        assert_eq!(utxos.len(), 1);
+        assert_eq!(utxos[0].height, Some(0));
+        assert_eq!(api.mine_block(), vec![tx.txid()]);
+        assert_eq!(wallet.get_utxos().await.unwrap()[0].height, Some(api.height()));
+        assert_eq!(api.utxo(&change).unwrap().value, Amount::from_sat(9_000));
+    }
+
+    #[tokio::test]
+    async fn test_realm_state_and_transfer() {
+        let api = MockAtomicalsApi::new();
+        let wallet = HdWallet::from_mnemonic(MNEMONIC, "", Network::Regtest).unwrap().with_api(api.clone());
+        let script = wallet.address().script_pubkey();
+        let realm_id = api.add_realm("mock", &script, Amount::from_sat(1_000));
+        api.fund(&script, Amount::from_sat(5_000));
+        api.set_subrealm_rules(&realm_id, vec![SubrealmRule { bitworkc: None, p: ".*".into(), o: json!({}) }]).unwrap();
+        assert!(api.set_subrealm_rules("unknown", Vec::new()).is_err());
+
+        assert_eq!(api.get_by_realm("mock").await.unwrap()["atomical_id"], json!(realm_id));
+        assert_eq!(api.get_by_realm("missing").await.unwrap()["atomical_id"], Value::Null);
+
+        let info = wallet.get_atomical_by_id(&realm_id).await.unwrap();
+        let location = info.get_current_location().unwrap();
+        assert_eq!(location.scripthash, scripthash(&script));
+        assert_eq!(location.atomicals_at_location, vec![realm_id.clone()]);
+        assert_eq!(info.state.clone().unwrap().latest.unwrap().subrealms.unwrap().rules.len(), 1);
+
+        // 带 atomicals 的 UTXO 不用于支付
+        let utxos = wallet.get_utxos().await.unwrap();
+        assert_eq!(utxos.len(), 1);
+        assert_eq!(utxos[0].txout.value, Amount::from_sat(5_000));
+
+        let realm_outpoint: OutPoint = location.location.parse().unwrap();
+        let tx = wallet.sign_psbt(spend(realm_outpoint, api.utxo(&realm_outpoint).unwrap(), &script, 900)).await.unwrap().extract_tx().unwrap();
+        api.broadcast(&tx).await.unwrap();
+        let moved = api.get_location(&realm_id).await.unwrap();
+        assert_eq!(moved.get_current_location().unwrap().location, format!("{}:0", tx.txid()));
+        assert!(!moved.confirmed);
+    }
+
+    #[tokio::test]
+    async fn test_ticker_info() {
+        let api = MockAtomicalsApi::new();
+        let ticker_id = api.add_ticker("mock", json!({ "mint_amount": 1000 }));
+        assert_eq!(api.get_by_ticker("mock").await.unwrap()["atomical_id"], json!(ticker_id));
+        let info = api.get_ft_info(&ticker_id).await.unwrap();
+        assert_eq!(info["mint_amount"], json!(1000));
+        assert_eq!(info["$ticker"], json!("mock"));
+        assert!(api.get_ft_info("unknown").await.is_err());
+    }
+}
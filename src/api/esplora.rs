@@ -0,0 +1,434 @@
+use crate::api::{AtomicalsApi, ScripthashListing};
+use crate::errors::{Error, Result};
+use crate::types::atomicals::AtomicalInfo;
+use crate::types::AtomicalsNetwork;
+use crate::wallet::Utxo;
+use async_trait::async_trait;
+use bitcoin::{Address, Amount, BlockHash, OutPoint, Script, Transaction, TxOut, Txid};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+use std::str::FromStr;
+
+#[cfg(target_arch = "wasm32")]
+macro_rules! log {
+    ($($t:tt)*) => (web_sys::console::log_1(&format!($($t)*).into()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+macro_rules! log {
+    ($($t:tt)*) => (log::info!($($t)*))
+}
+
+/// 交易的确认状态，未确认时只有 `confirmed`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxStatus {
+    pub confirmed: bool,
+    #[serde(default)]
+    pub block_height: Option<u32>,
+    #[serde(default)]
+    pub block_hash: Option<BlockHash>,
+    #[serde(default)]
+    pub block_time: Option<u64>,
+}
+
+/// 输出的花费情况，已花费时给出花费它的交易和输入序号
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Outspend {
+    pub spent: bool,
+    #[serde(default)]
+    pub txid: Option<Txid>,
+    #[serde(default)]
+    pub vin: Option<u32>,
+    #[serde(default)]
+    pub status: Option<TxStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EsploraUtxo {
+    txid: Txid,
+    vout: u32,
+    value: u64,
+    status: TxStatus,
+}
+
+/// Esplora REST 客户端，同样适用于 mempool.space
+#[derive(Debug, Clone)]
+pub struct EsploraClient {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl EsploraClient {
+    /// `base_url` 形如 `https://mempool.space/testnet4/api` 或 `http://127.0.0.1:3002`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// 网络对应的 mempool.space 接口；regtest 没有公共服务，返回 None
+    pub fn for_network(network: impl Into<AtomicalsNetwork>) -> Option<Self> {
+        network.into().mempool_url().map(Self::new)
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    async fn get_text(&self, path: &str) -> Result<String> {
+        let url = format!("{}{}", self.base_url, path);
+        log!("Esplora request: GET {}", url);
+        let response = self.client.get(&url).send().await
+            .map_err(|e| Error::NetworkError(format!("GET {} failed: {}", path, e)))?;
+        let status = response.status();
+        let body = response.text().await
+            .map_err(|e| Error::NetworkError(format!("Failed to read {} response: {}", path, e)))?;
+        if !status.is_success() {
+            return Err(Error::NetworkError(format!("GET {} failed with HTTP {}: {}", path, status, body)));
+        }
+        Ok(body)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let body = self.get_text(path).await?;
+        serde_json::from_str(&body)
+            .map_err(|e| Error::DeserializationError(format!("Unexpected {} response: {}", path, e)))
+    }
+
+    /// 地址上的 UTXO，包括未确认的输出
+    pub async fn address_utxos(&self, address: &Address) -> Result<Vec<Utxo>> {
+        let items: Vec<EsploraUtxo> = self.get_json(&format!("/address/{}/utxo", address)).await?;
+        let script_pubkey = address.script_pubkey();
+        Ok(items.into_iter().map(|item| Utxo {
+            outpoint: OutPoint::new(item.txid, item.vout),
+            txout: TxOut { value: Amount::from_sat(item.value), script_pubkey: script_pubkey.clone() },
+            height: item.status.block_height.filter(|_| item.status.confirmed),
+        }).collect())
+    }
+
+    /// 各确认目标（区块数）对应的费率，单位 sat/vB
+    pub async fn fee_estimates(&self) -> Result<BTreeMap<u16, f64>> {
+        let estimates: HashMap<String, f64> = self.get_json("/fee-estimates").await?;
+        estimates.into_iter()
+            .map(|(target, rate)| {
+                let target = target.parse::<u16>()
+                    .map_err(|e| Error::DeserializationError(format!("Invalid fee estimate target {}: {}", target, e)))?;
+                Ok((target, rate))
+            })
+            .collect()
+    }
+
+    /// 在 `target_blocks` 个区块内确认所需的费率，服务没有数据时返回 None
+    pub async fn fee_rate(&self, target_blocks: u16) -> Result<Option<f64>> {
+        Ok(select_fee_rate(&self.fee_estimates().await?, target_blocks))
+    }
+
+    pub async fn broadcast(&self, tx: &Transaction) -> Result<Txid> {
+        let url = format!("{}/tx", self.base_url);
+        log!("Esplora request: POST {}", url);
+        let response = self.client.post(&url)
+            .body(bitcoin::consensus::encode::serialize_hex(tx))
+            .send().await
+            .map_err(|e| Error::NetworkError(format!("POST /tx failed: {}", e)))?;
+        let status = response.status();
+        let body = response.text().await
+            .map_err(|e| Error::NetworkError(format!("Failed to read /tx response: {}", e)))?;
+        // 节点拒绝时返回 400 和 sendrawtransaction 的错误信息
+        if !status.is_success() {
+            return Err(Error::TransactionError(format!("Broadcast rejected with HTTP {}: {}", status, body)));
+        }
+        Txid::from_str(body.trim())
+            .map_err(|e| Error::DeserializationError(format!("Unexpected /tx response {}: {}", body, e)))
+    }
+
+    pub async fn get_transaction(&self, txid: &Txid) -> Result<Transaction> {
+        let hex = self.get_text(&format!("/tx/{}/hex", txid)).await?;
+        let bytes = hex::decode(hex.trim())
+            .map_err(|e| Error::DeserializationError(format!("Invalid transaction hex for {}: {}", txid, e)))?;
+        bitcoin::consensus::encode::deserialize(&bytes)
+            .map_err(|e| Error::DeserializationError(format!("Invalid transaction {}: {}", txid, e)))
+    }
+
+    pub async fn tx_status(&self, txid: &Txid) -> Result<TxStatus> {
+        self.get_json(&format!("/tx/{}/status", txid)).await
+    }
+
+    pub async fn outspend(&self, outpoint: &OutPoint) -> Result<Outspend> {
+        self.get_json(&format!("/tx/{}/outspend/{}", outpoint.txid, outpoint.vout)).await
+    }
+}
+
+// 取不超过目标的最大区块数对应的费率，目标小于所有区块数时取最快的一档
+fn select_fee_rate(estimates: &BTreeMap<u16, f64>, target_blocks: u16) -> Option<f64> {
+    estimates.range(..=target_blocks).next_back()
+        .or_else(|| estimates.iter().next())
+        .map(|(_, rate)| *rate)
+}
+
+/// 使用 Esplora 的 `AtomicalsApi`
+///
+/// UTXO、费率和广播走 Esplora，atomical 查询交给 `with_atomicals` 指定的索引器。
+/// UTXO 列表会去掉索引器记录为带有 atomicals 的输出；没有索引器时 `list_utxos` 返回错误，
+/// 除非通过 `allow_unfiltered_utxos` 明确接受未过滤的列表，避免把 atomicals 当作手续费花掉。
+#[derive(Clone)]
+pub struct EsploraApi {
+    client: EsploraClient,
+    network: AtomicalsNetwork,
+    fee_target: u16,
+    atomicals: Option<Rc<dyn AtomicalsApi>>,
+    allow_unfiltered: bool,
+}
+
+impl EsploraApi {
+    /// `network` 用于把脚本转换为查询 UTXO 的地址
+    pub fn new(client: EsploraClient, network: impl Into<AtomicalsNetwork>) -> Self {
+        Self { client, network: network.into(), fee_target: 1, atomicals: None, allow_unfiltered: false }
+    }
+
+    /// 网络对应的 mempool.space 接口，regtest 返回 None
+    pub fn for_network(network: impl Into<AtomicalsNetwork>) -> Option<Self> {
+        let network = network.into();
+        Some(Self::new(EsploraClient::for_network(network)?, network))
+    }
+
+    /// 估算费率使用的确认目标区块数，默认 1
+    pub fn with_fee_target(mut self, target_blocks: u16) -> Self {
+        self.fee_target = target_blocks;
+        self
+    }
+
+    pub fn with_atomicals(mut self, api: impl AtomicalsApi + 'static) -> Self {
+        self.atomicals = Some(Rc::new(api));
+        self
+    }
+
+    /// 没有索引器时返回 Esplora 的全部 UTXO，只适合确定不持有 atomicals 的地址
+    pub fn allow_unfiltered_utxos(mut self) -> Self {
+        self.allow_unfiltered = true;
+        self
+    }
+
+    pub fn client(&self) -> &EsploraClient {
+        &self.client
+    }
+
+    fn atomicals(&self) -> Result<&dyn AtomicalsApi> {
+        self.atomicals.as_deref()
+            .ok_or_else(|| Error::NetworkError("No Atomicals indexer configured for Esplora backend".into()))
+    }
+}
+
+#[async_trait(?Send)]
+impl AtomicalsApi for EsploraApi {
+    async fn list_scripthash(&self, scripthash: &str) -> Result<ScripthashListing> {
+        self.atomicals()?.list_scripthash(scripthash).await
+    }
+
+    async fn get_state(&self, atomical_id: &str) -> Result<AtomicalInfo> {
+        self.atomicals()?.get_state(atomical_id).await
+    }
+
+    async fn get_location(&self, atomical_id: &str) -> Result<AtomicalInfo> {
+        self.atomicals()?.get_location(atomical_id).await
+    }
+
+    async fn get_by_realm(&self, realm: &str) -> Result<Value> {
+        self.atomicals()?.get_by_realm(realm).await
+    }
+
+    async fn get_by_ticker(&self, ticker: &str) -> Result<Value> {
+        self.atomicals()?.get_by_ticker(ticker).await
+    }
+
+    async fn get_ft_info(&self, atomical_id: &str) -> Result<Value> {
+        self.atomicals()?.get_ft_info(atomical_id).await
+    }
+
+    async fn broadcast(&self, tx: &Transaction) -> Result<String> {
+        Ok(self.client.broadcast(tx).await?.to_string())
+    }
+
+    async fn get_global(&self) -> Result<Value> {
+        self.atomicals()?.get_global().await
+    }
+
+    async fn fee_rate(&self) -> Result<f64> {
+        let fee_rate = self.client.fee_rate(self.fee_target).await?.unwrap_or(0.0);
+        let fee_rate = if fee_rate > 0.0 { fee_rate } else { 1.0 };
+        log!("Got fee rate: {} sat/vB", fee_rate);
+        Ok(fee_rate)
+    }
+
+    async fn list_utxos(&self, script_pubkey: &Script) -> Result<Vec<Utxo>> {
+        if self.atomicals.is_none() && !self.allow_unfiltered {
+            return Err(Error::NetworkError(
+                "No Atomicals indexer configured for Esplora backend, refusing to list unfiltered UTXOs".into(),
+            ));
+        }
+        let address = Address::from_script(script_pubkey, self.network.bitcoin_network())
+            .map_err(|e| Error::AddressError(format!("Script has no {} address: {}", self.network, e)))?;
+        let utxos = self.client.address_utxos(&address).await?;
+        match &self.atomicals {
+            Some(api) => api.exclude_atomicals(script_pubkey, utxos).await,
+            None => Ok(utxos),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{test_server, MockAtomicalsApi};
+    use bitcoin::{absolute::LockTime, transaction::Version, ScriptBuf, Sequence, TxIn, Witness};
+    use serde_json::json;
+
+    const SCRIPT: &str = "5120d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d";
+    const TXID: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+    const SPENDER: &str = "2222222222222222222222222222222222222222222222222222222222222222";
+
+    fn script() -> ScriptBuf {
+        ScriptBuf::from_hex(SCRIPT).unwrap()
+    }
+
+    fn address() -> Address {
+        Address::from_script(&script(), bitcoin::Network::Testnet).unwrap()
+    }
+
+    fn tx() -> Transaction {
+        Transaction {
+            version: Version(2),
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn { previous_output: OutPoint::new(Txid::from_str(TXID).unwrap(), 0), script_sig: ScriptBuf::new(), sequence: Sequence::MAX, witness: Witness::new() }],
+            output: vec![TxOut { value: Amount::from_sat(1_000), script_pubkey: script() }],
+        }
+    }
+
+    // 代替 Esplora 的 REST 服务
+    async fn serve(extra_utxos: Value) -> String {
+        let utxo_path = format!("/address/{}/utxo", address());
+        let tx_hex = bitcoin::consensus::encode::serialize_hex(&tx());
+        test_server::serve(move |request| {
+            let path = request.path.as_str();
+            match (request.method.as_str(), path) {
+                ("GET", p) if p == utxo_path => {
+                    let mut utxos = json!([
+                        { "txid": TXID, "vout": 0, "value": 12_345, "status": { "confirmed": false } },
+                        { "txid": TXID, "vout": 1, "value": 50_000, "status": { "confirmed": true, "block_height": 101, "block_hash": "000000000000000000000000000000000000000000000000000000000000abcd", "block_time": 1_700_000_000 } },
+                    ]);
+                    utxos.as_array_mut().unwrap().extend(extra_utxos.as_array().cloned().unwrap_or_default());
+                    (200, utxos.to_string())
+                }
+                ("GET", "/fee-estimates") => (200, json!({ "1": 20.5, "3": 12.0, "6": 8.0, "144": 1.5 }).to_string()),
+                ("POST", "/tx") => {
+                    let body = String::from_utf8_lossy(&request.body).to_string();
+                    if body == tx_hex {
+                        (200, tx().txid().to_string())
+                    } else {
+                        (400, "sendrawtransaction RPC error: {\"code\":-25,\"message\":\"bad-txns-inputs-missingorspent\"}".to_string())
+                    }
+                }
+                ("GET", p) if p == format!("/tx/{}/hex", TXID) => (200, tx_hex.clone()),
+                ("GET", p) if p == format!("/tx/{}/status", TXID) => (200, json!({ "confirmed": true, "block_height": 101, "block_hash": "000000000000000000000000000000000000000000000000000000000000abcd", "block_time": 1_700_000_000 }).to_string()),
+                ("GET", p) if p == format!("/tx/{}/outspend/0", TXID) => (200, json!({ "spent": true, "txid": SPENDER, "vin": 0, "status": { "confirmed": false } }).to_string()),
+                ("GET", p) if p == format!("/tx/{}/outspend/1", TXID) => (200, json!({ "spent": false }).to_string()),
+                _ => (404, "Not Found".to_string()),
+            }
+        }).await
+    }
+
+    #[test]
+    fn test_select_fee_rate() {
+        let estimates = BTreeMap::from([(1, 20.5), (3, 12.0), (6, 8.0), (144, 1.5)]);
+        assert_eq!(select_fee_rate(&estimates, 1), Some(20.5));
+        assert_eq!(select_fee_rate(&estimates, 2), Some(20.5));
+        assert_eq!(select_fee_rate(&estimates, 5), Some(12.0));
+        assert_eq!(select_fee_rate(&estimates, 1008), Some(1.5));
+        assert_eq!(select_fee_rate(&BTreeMap::from([(2, 9.0)]), 1), Some(9.0));
+        assert_eq!(select_fee_rate(&BTreeMap::new(), 1), None);
+    }
+
+    #[test]
+    fn test_base_url_is_configurable() {
+        assert_eq!(EsploraClient::new("http://127.0.0.1:3002/").base_url(), "http://127.0.0.1:3002");
+        assert_eq!(EsploraClient::for_network(AtomicalsNetwork::Testnet4).unwrap().base_url(), "https://mempool.space/testnet4/api");
+        assert!(EsploraApi::for_network(AtomicalsNetwork::Regtest).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_utxos_and_fee_rate() {
+        let url = serve(json!([])).await;
+        let api = EsploraApi::new(EsploraClient::new(&url), AtomicalsNetwork::Testnet4).with_fee_target(4);
+        assert!(matches!(api.list_utxos(&script()).await, Err(Error::NetworkError(_))));
+
+        let api = api.allow_unfiltered_utxos();
+        let utxos = api.list_utxos(&script()).await.unwrap();
+        assert_eq!(utxos.len(), 2);
+        assert_eq!(utxos[0].txout.value, Amount::from_sat(12_345));
+        assert_eq!(utxos[0].txout.script_pubkey, script());
+        assert_eq!(utxos[0].height, None);
+        assert_eq!(utxos[1].height, Some(101));
+
+        assert_eq!(api.fee_rate().await.unwrap(), 12.0);
+        assert_eq!(api.client().fee_estimates().await.unwrap().len(), 4);
+
+        // 按配置的网络编码地址，服务上没有主网地址
+        let mainnet = EsploraApi::new(EsploraClient::new(&url), AtomicalsNetwork::Mainnet).allow_unfiltered_utxos();
+        assert!(matches!(mainnet.list_utxos(&script()).await, Err(Error::NetworkError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_list_utxos_excludes_atomicals() {
+        let atomicals = MockAtomicalsApi::new();
+        let realm_id = atomicals.add_realm("mock", &script(), Amount::from_sat(1_000));
+        let realm_txid = realm_id.split('i').next().unwrap().to_string();
+        let url = serve(json!([
+            { "txid": realm_txid, "vout": 0, "value": 1_000, "status": { "confirmed": true, "block_height": 101 } },
+        ])).await;
+
+        let api = EsploraApi::new(EsploraClient::new(&url), AtomicalsNetwork::Testnet4);
+        assert_eq!(api.clone().allow_unfiltered_utxos().list_utxos(&script()).await.unwrap().len(), 3);
+        assert!(api.get_state(&realm_id).await.is_err());
+
+        let api = api.with_atomicals(atomicals);
+        let utxos = api.list_utxos(&script()).await.unwrap();
+        assert_eq!(utxos.len(), 2);
+        assert!(utxos.iter().all(|utxo| utxo.outpoint.txid.to_string() == TXID));
+        assert_eq!(api.get_state(&realm_id).await.unwrap().atomical_id, realm_id);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_and_tx_lookups() {
+        let url = serve(json!([])).await;
+        let client = EsploraClient::new(&url);
+        let api = EsploraApi::new(client.clone(), AtomicalsNetwork::Testnet4);
+
+        assert_eq!(api.broadcast(&tx()).await.unwrap(), tx().txid().to_string());
+        let mut rejected = tx();
+        rejected.output[0].value = Amount::from_sat(2_000);
+        match api.broadcast(&rejected).await {
+            Err(Error::TransactionError(message)) => assert!(message.contains("missingorspent")),
+            other => panic!("unexpected broadcast result: {:?}", other),
+        }
+
+        let txid = Txid::from_str(TXID).unwrap();
+        assert_eq!(client.get_transaction(&txid).await.unwrap(), tx());
+        let status = client.tx_status(&txid).await.unwrap();
+        assert!(status.confirmed);
+        assert_eq!(status.block_height, Some(101));
+
+        let spent = client.outspend(&OutPoint::new(txid, 0)).await.unwrap();
+        assert!(spent.spent);
+        assert_eq!(spent.txid, Some(Txid::from_str(SPENDER).unwrap()));
+        assert_eq!(spent.vin, Some(0));
+        assert!(!client.outspend(&OutPoint::new(txid, 1)).await.unwrap().spent);
+
+        match client.tx_status(&Txid::from_str(SPENDER).unwrap()).await {
+            Err(Error::NetworkError(message)) => assert!(message.contains("404")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,176 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bitcoind;
+pub mod electrum_api;
+pub mod electrum_api_mock;
+pub mod esplora;
+#[cfg(test)]
+mod test_server;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use bitcoind::{BitcoindApi, BitcoindRpc, UtxoSource};
+pub use electrum_api::ElectrumApi;
+pub use electrum_api_mock::MockAtomicalsApi;
+pub use esplora::{EsploraApi, EsploraClient};
+
+use crate::errors::{Error, Result};
+use crate::types::atomicals::AtomicalInfo;
+use crate::wallet::Utxo;
+use async_trait::async_trait;
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::{Amount, OutPoint, Script, Transaction, TxOut, Txid};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// `blockchain.atomicals.listscripthash` 返回的 UTXO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScripthashUtxo {
+    pub txid: String,
+    pub vout: u32,
+    pub value: u64,
+    #[serde(default)]
+    pub height: u32,
+    /// 该 UTXO 上的 atomicals，可能是 ID 数组或以 ID 为键的对象
+    #[serde(default)]
+    pub atomicals: Value,
+}
+
+impl ScripthashUtxo {
+    pub fn has_atomicals(&self) -> bool {
+        match &self.atomicals {
+            Value::Null => false,
+            Value::Array(ids) => !ids.is_empty(),
+            Value::Object(map) => !map.is_empty(),
+            _ => true,
+        }
+    }
+}
+
+/// `blockchain.atomicals.listscripthash` 的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScripthashListing {
+    #[serde(default)]
+    pub utxos: Vec<ScripthashUtxo>,
+    /// 地址持有的 atomicals 汇总
+    #[serde(default)]
+    pub atomicals: Value,
+}
+
+/// Atomicals ElectrumX 接口，对应 atomicals-js 的 `ElectrumApiInterface`
+///
+/// 名称查询、FT 信息和全局信息的结构随索引器版本变化，以 JSON 原样返回 `result` 字段。
+#[async_trait(?Send)]
+pub trait AtomicalsApi {
+    /// `blockchain.atomicals.listscripthash`
+    async fn list_scripthash(&self, scripthash: &str) -> Result<ScripthashListing>;
+
+    /// `blockchain.atomicals.get_state`
+    async fn get_state(&self, atomical_id: &str) -> Result<AtomicalInfo>;
+
+    /// `blockchain.atomicals.get_location`
+    async fn get_location(&self, atomical_id: &str) -> Result<AtomicalInfo>;
+
+    /// `blockchain.atomicals.get_by_realm`
+    async fn get_by_realm(&self, realm: &str) -> Result<Value>;
+
+    /// `blockchain.atomicals.get_by_ticker`
+    async fn get_by_ticker(&self, ticker: &str) -> Result<Value>;
+
+    /// `blockchain.atomicals.get_ft_info`
+    async fn get_ft_info(&self, atomical_id: &str) -> Result<Value>;
+
+    /// `blockchain.transaction.broadcast`，返回 txid
+    async fn broadcast(&self, tx: &Transaction) -> Result<String>;
+
+    /// `blockchain.atomicals.get_global`
+    async fn get_global(&self) -> Result<Value>;
+
+    /// 下一个区块的建议费率（sat/vB）
+    async fn fee_rate(&self) -> Result<f64>;
+
+    /// 脚本上不带 atomicals 的 UTXO，可用于支付手续费
+    async fn list_utxos(&self, script_pubkey: &Script) -> Result<Vec<Utxo>> {
+        let listing = self.list_scripthash(&scripthash(script_pubkey)).await?;
+        listing.utxos.into_iter()
+            .filter(|utxo| !utxo.has_atomicals())
+            .map(|item| {
+                let txid = Txid::from_str(&item.txid)
+                    .map_err(|e| Error::TransactionError(format!("Invalid txid: {}", e)))?;
+                Ok(Utxo {
+                    outpoint: OutPoint::new(txid, item.vout),
+                    txout: TxOut {
+                        value: Amount::from_sat(item.value),
+                        script_pubkey: script_pubkey.to_owned(),
+                    },
+                    height: Some(item.height),
+                })
+            })
+            .collect()
+    }
+
+    /// 去掉索引器记录为带有 atomicals 的 UTXO，用于过滤钱包自己给出的 UTXO 列表
+    ///
+    /// 索引器不知道的 UTXO（例如尚未被索引的未确认输出）会保留。
+    async fn exclude_atomicals(&self, script_pubkey: &Script, utxos: Vec<Utxo>) -> Result<Vec<Utxo>> {
+        let listing = self.list_scripthash(&scripthash(script_pubkey)).await?;
+        let occupied = listing.utxos.iter()
+            .filter(|utxo| utxo.has_atomicals())
+            .map(|utxo| {
+                let txid = Txid::from_str(&utxo.txid)
+                    .map_err(|e| Error::TransactionError(format!("Invalid txid: {}", e)))?;
+                Ok(OutPoint::new(txid, utxo.vout))
+            })
+            .collect::<Result<HashSet<_>>>()?;
+        Ok(utxos.into_iter().filter(|utxo| !occupied.contains(&utxo.outpoint)).collect())
+    }
+}
+
+/// ElectrumX 使用的 scripthash：脚本 SHA256 的字节逆序十六进制
+pub fn scripthash(script_pubkey: &Script) -> String {
+    let hash = sha256::Hash::hash(script_pubkey.as_bytes());
+    hash.to_byte_array().iter().rev().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_scripthash() {
+        // ElectrumX 文档中的 P2PKH 示例
+        let script = bitcoin::ScriptBuf::from_hex("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac").unwrap();
+        assert_eq!(scripthash(&script), "8b01df4e368ea28f8dc0423bcf7a4923e3a12d307c875e47a0cfbf90b5c39161");
+    }
+
+    #[test]
+    fn test_utxo_atomicals_detection() {
+        let utxo = |atomicals| ScripthashUtxo { txid: String::new(), vout: 0, value: 0, height: 0, atomicals };
+        assert!(!utxo(Value::Null).has_atomicals());
+        assert!(!utxo(json!([])).has_atomicals());
+        assert!(!utxo(json!({})).has_atomicals());
+        assert!(utxo(json!(["abc"])).has_atomicals());
+        assert!(utxo(json!({"abc": {}})).has_atomicals());
+    }
+
+    #[tokio::test]
+    async fn test_exclude_atomicals() {
+        let api = MockAtomicalsApi::new();
+        let script = bitcoin::ScriptBuf::from_hex("5120d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d").unwrap();
+        let free = api.fund(&script, Amount::from_sat(10_000));
+        let realm_id = api.add_realm("mock", &script, Amount::from_sat(1_000));
+        let realm = OutPoint::from_str(&realm_id.replace('i', ":")).unwrap();
+        let unindexed = OutPoint::new(Txid::from_str("1111111111111111111111111111111111111111111111111111111111111111").unwrap(), 0);
+
+        let utxos = [free, realm, unindexed].into_iter()
+            .map(|outpoint| Utxo {
+                outpoint,
+                txout: TxOut { value: Amount::from_sat(1_000), script_pubkey: script.clone() },
+                height: None,
+            })
+            .collect();
+        let spendable = api.exclude_atomicals(&script, utxos).await.unwrap();
+        assert_eq!(spendable.iter().map(|utxo| utxo.outpoint).collect::<Vec<_>>(), vec![free, unindexed]);
+    }
+}
@@ -0,0 +1,59 @@
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// 测试服务收到的请求，header 已转为小写
+pub(crate) struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: String,
+    pub body: Vec<u8>,
+}
+
+/// 在本地端口启动 HTTP 服务代替节点或 REST 接口，返回 `http://127.0.0.1:<port>`
+///
+/// 每个连接只处理一个请求，`handler` 返回状态码和响应体。
+pub(crate) async fn serve(handler: impl Fn(&Request) -> (u16, String) + Send + 'static) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 4096];
+            let request = loop {
+                let n = socket.read(&mut chunk).await.unwrap();
+                assert!(n > 0, "connection closed before request completed");
+                buf.extend_from_slice(&chunk[..n]);
+                let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") else { continue };
+                let head = String::from_utf8_lossy(&buf[..end]).to_string();
+                let headers = head.to_lowercase();
+                let length = headers.lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .map_or(0, |value| value.trim().parse::<usize>().unwrap());
+                if buf.len() >= end + 4 + length {
+                    let mut request_line = head.lines().next().unwrap_or_default().split(' ');
+                    break Request {
+                        method: request_line.next().unwrap_or_default().to_string(),
+                        path: request_line.next().unwrap_or_default().to_string(),
+                        headers,
+                        body: buf[end + 4..end + 4 + length].to_vec(),
+                    };
+                }
+            };
+
+            let (status, body) = handler(&request);
+            let reason = match status {
+                200 => "OK",
+                400 => "Bad Request",
+                401 => "Unauthorized",
+                404 => "Not Found",
+                _ => "Internal Server Error",
+            };
+            let response = format!(
+                "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status, reason, body.len(), body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    url
+}
@@ -12,6 +12,8 @@ pub enum Error {
     TransactionError(String),
     PsbtError(String),
     MiningError(String),
+    MiningCancelled(String),
+    MiningTimeout(String),
     WalletError(String),
     SerializationError(String),
     DeserializationError(String),
@@ -48,6 +50,8 @@ impl fmt::Display for Error {
             Error::TransactionError(msg) => write!(f, "Transaction error: {}", msg),
             Error::PsbtError(msg) => write!(f, "PSBT error: {}", msg),
             Error::MiningError(msg) => write!(f, "Mining error: {}", msg),
+            Error::MiningCancelled(msg) => write!(f, "Mining cancelled: {}", msg),
+            Error::MiningTimeout(msg) => write!(f, "Mining timeout: {}", msg),
             Error::WalletError(msg) => write!(f, "Wallet error: {}", msg),
             Error::SerializationError(msg) => write!(f, "Serialization error: {}", msg),
             Error::DeserializationError(msg) => write!(f, "Deserialization error: {}", msg),
@@ -1,4 +1,4 @@
-// pub mod api;
+pub mod api;
 pub mod errors;
 // pub mod interfaces;
 pub mod operations;
@@ -0,0 +1,343 @@
+use crate::errors::{Error, Result};
+use crate::operations::mining::split_range;
+use crate::types::bitwork::Bitwork;
+use bitcoin::{Sequence, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// 某个交易模板在给定 bitwork 下已完整搜索过的 sequence 区间
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct MiningCheckpoint {
+    /// 模板哈希：`input[0].sequence` 置零后的 txid
+    pub template: String,
+    pub bitwork: String,
+    /// 已搜索的闭区间，按起点排序且互不重叠、互不相邻
+    pub searched: Vec<(u32, u32)>,
+}
+
+impl MiningCheckpoint {
+    /// 交易没有输入时返回 None
+    pub fn for_template(tx: &Transaction, bitwork: &Bitwork) -> Option<Self> {
+        Some(Self {
+            template: template_hash(tx)?,
+            bitwork: bitwork.to_string(),
+            searched: Vec::new(),
+        })
+    }
+
+    /// 存储中使用的键，由模板哈希和 bitwork 组成
+    pub fn key(&self) -> String {
+        checkpoint_key(&self.template, &self.bitwork)
+    }
+
+    /// 记录一个已搜索的区间，并与已有区间合并
+    pub fn add_range(&mut self, start: u32, end: u32) {
+        if start > end {
+            return;
+        }
+        self.searched.push((start, end));
+        self.searched.sort_unstable();
+
+        let mut merged: Vec<(u32, u32)> = Vec::with_capacity(self.searched.len());
+        for &(start, end) in &self.searched {
+            match merged.last_mut() {
+                Some(last) if start as u64 <= last.1 as u64 + 1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        self.searched = merged;
+    }
+
+    /// 已搜索的 nonce 数量
+    pub fn searched_count(&self) -> u64 {
+        self.searched.iter().map(|&(start, end)| end as u64 - start as u64 + 1).sum()
+    }
+}
+
+/// 交易模板的哈希，与 `input[0].sequence` 无关
+pub fn template_hash(tx: &Transaction) -> Option<String> {
+    let mut template = tx.clone();
+    template.input.first_mut()?.sequence = Sequence::ZERO;
+    Some(template.txid().to_string())
+}
+
+pub fn checkpoint_key(template: &str, bitwork: &str) -> String {
+    format!("{}-{}", template, bitwork)
+}
+
+/// 检查点存储，读写失败不会中断挖矿
+pub trait CheckpointStore: Send + Sync {
+    fn load(&self, key: &str) -> Result<Option<MiningCheckpoint>>;
+    fn save(&self, checkpoint: &MiningCheckpoint) -> Result<()>;
+    fn remove(&self, key: &str) -> Result<()>;
+}
+
+impl<S: CheckpointStore + ?Sized> CheckpointStore for std::sync::Arc<S> {
+    fn load(&self, key: &str) -> Result<Option<MiningCheckpoint>> {
+        (**self).load(key)
+    }
+
+    fn save(&self, checkpoint: &MiningCheckpoint) -> Result<()> {
+        (**self).save(checkpoint)
+    }
+
+    fn remove(&self, key: &str) -> Result<()> {
+        (**self).remove(key)
+    }
+}
+
+/// 进程内存中的检查点存储
+#[derive(Default)]
+pub struct MemoryCheckpointStore {
+    checkpoints: Mutex<HashMap<String, MiningCheckpoint>>,
+}
+
+impl MemoryCheckpointStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CheckpointStore for MemoryCheckpointStore {
+    fn load(&self, key: &str) -> Result<Option<MiningCheckpoint>> {
+        Ok(self.checkpoints.lock().unwrap().get(key).cloned())
+    }
+
+    fn save(&self, checkpoint: &MiningCheckpoint) -> Result<()> {
+        self.checkpoints.lock().unwrap().insert(checkpoint.key(), checkpoint.clone());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<()> {
+        self.checkpoints.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// 以 JSON 文件保存检查点，每个模板一个文件
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileCheckpointStore {
+    dir: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileCheckpointStore {
+    /// 目录不存在时会被创建
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(Error::IoError)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &str) -> std::path::PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CheckpointStore for FileCheckpointStore {
+    fn load(&self, key: &str) -> Result<Option<MiningCheckpoint>> {
+        match std::fs::read(self.path(key)) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::IoError(e)),
+        }
+    }
+
+    fn save(&self, checkpoint: &MiningCheckpoint) -> Result<()> {
+        // 先写临时文件再重命名，避免进程中断时留下不完整的文件
+        let path = self.path(&checkpoint.key());
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(checkpoint)?).map_err(Error::IoError)?;
+        std::fs::rename(&tmp, &path).map_err(Error::IoError)
+    }
+
+    fn remove(&self, key: &str) -> Result<()> {
+        match std::fs::remove_file(self.path(key)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(Error::IoError(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 以 localStorage 保存检查点，键带有固定前缀
+#[cfg(target_arch = "wasm32")]
+pub struct LocalStorageCheckpointStore {
+    prefix: String,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorageCheckpointStore {
+    pub const DEFAULT_PREFIX: &'static str = "atomicals-mining-checkpoint:";
+
+    pub fn new(prefix: Option<String>) -> Self {
+        Self {
+            prefix: prefix.unwrap_or_else(|| Self::DEFAULT_PREFIX.into()),
+        }
+    }
+
+    fn storage() -> Result<web_sys::Storage> {
+        web_sys::window()
+            .ok_or_else(|| Error::DatabaseError("localStorage requires a window".into()))?
+            .local_storage()
+            .map_err(|e| Error::DatabaseError(format!("Failed to access localStorage: {:?}", e)))?
+            .ok_or_else(|| Error::DatabaseError("localStorage is not available".into()))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl CheckpointStore for LocalStorageCheckpointStore {
+    fn load(&self, key: &str) -> Result<Option<MiningCheckpoint>> {
+        let value = Self::storage()?
+            .get_item(&format!("{}{}", self.prefix, key))
+            .map_err(|e| Error::DatabaseError(format!("Failed to read checkpoint: {:?}", e)))?;
+        match value {
+            Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn save(&self, checkpoint: &MiningCheckpoint) -> Result<()> {
+        Self::storage()?
+            .set_item(&format!("{}{}", self.prefix, checkpoint.key()), &serde_json::to_string(checkpoint)?)
+            .map_err(|e| Error::DatabaseError(format!("Failed to write checkpoint: {:?}", e)))
+    }
+
+    fn remove(&self, key: &str) -> Result<()> {
+        Self::storage()?
+            .remove_item(&format!("{}{}", self.prefix, key))
+            .map_err(|e| Error::DatabaseError(format!("Failed to remove checkpoint: {:?}", e)))
+    }
+}
+
+/// 把 [0, max_sequence] 中尚未搜索的部分分配为最多 `parts` 个连续区间
+///
+/// 未搜索的空隙多于 `parts` 时合并间隔最小的相邻空隙（少量重复搜索），
+/// 少于 `parts` 时按长度继续切分较大的空隙。全部搜索过时返回空列表。
+pub fn plan_ranges(max_sequence: u32, searched: &[(u32, u32)], parts: u32) -> Vec<(u32, u32)> {
+    let parts = parts.max(1) as usize;
+
+    let mut gaps = Vec::new();
+    let mut next = 0u64;
+    for &(start, end) in searched {
+        if start as u64 > max_sequence as u64 {
+            break;
+        }
+        if start as u64 > next {
+            gaps.push((next as u32, start - 1));
+        }
+        next = next.max(end as u64 + 1);
+    }
+    if next <= max_sequence as u64 {
+        gaps.push((next as u32, max_sequence));
+    }
+
+    while gaps.len() > parts {
+        let i = (0..gaps.len() - 1)
+            .min_by_key(|&i| gaps[i + 1].0 - gaps[i].1)
+            .unwrap_or(0);
+        gaps[i].1 = gaps[i + 1].1;
+        gaps.remove(i + 1);
+    }
+
+    // 每次把多出的一份分给当前每份最长的空隙
+    let len = |&(start, end): &(u32, u32)| end as u64 - start as u64 + 1;
+    let mut shares = vec![1u64; gaps.len()];
+    for _ in gaps.len()..parts {
+        let widest = (0..gaps.len())
+            .filter(|&i| len(&gaps[i]) > shares[i])
+            .max_by(|&a, &b| (len(&gaps[a]) * shares[b]).cmp(&(len(&gaps[b]) * shares[a])));
+        match widest {
+            Some(i) => shares[i] += 1,
+            None => break,
+        }
+    }
+
+    gaps.iter()
+        .zip(shares)
+        .flat_map(|(&(start, end), share)| split_range(start, end, share as u32))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::mining::split_sequence_range;
+
+    #[test]
+    fn test_add_range_merges_adjacent() {
+        let mut checkpoint = MiningCheckpoint::default();
+        checkpoint.add_range(10, 19);
+        checkpoint.add_range(0, 4);
+        checkpoint.add_range(5, 9);
+        checkpoint.add_range(30, u32::MAX);
+        checkpoint.add_range(25, 40);
+        assert_eq!(checkpoint.searched, vec![(0, 19), (25, u32::MAX)]);
+        assert_eq!(checkpoint.searched_count(), 20 + (u32::MAX as u64 - 25 + 1));
+    }
+
+    #[test]
+    fn test_plan_without_checkpoint_matches_split() {
+        assert_eq!(plan_ranges(999, &[], 4), split_sequence_range(999, 4));
+        assert_eq!(plan_ranges(u32::MAX, &[], 3), split_sequence_range(u32::MAX, 3));
+    }
+
+    #[test]
+    fn test_plan_skips_searched_ranges() {
+        // 上次 4 个 worker 各搜索了一段前缀
+        let searched = [(0, 9), (250, 299), (500, 749), (750, 760)];
+        assert_eq!(plan_ranges(999, &searched, 4), vec![(10, 128), (129, 249), (300, 499), (761, 999)]);
+
+        // 空隙多于 worker 数时合并间隔最小的相邻空隙
+        let searched = [(0, 9), (20, 21), (100, 199)];
+        assert_eq!(plan_ranges(299, &searched, 2), vec![(10, 99), (200, 299)]);
+
+        assert!(plan_ranges(99, &[(0, 99)], 4).is_empty());
+        assert_eq!(plan_ranges(99, &[(0, 97)], 4), vec![(98, 98), (99, 99)]);
+    }
+
+    #[test]
+    fn test_template_hash_ignores_sequence() {
+        use bitcoin::{absolute::LockTime, transaction::Version, OutPoint, ScriptBuf, TxIn, Witness};
+
+        let mut tx = Transaction {
+            version: Version(2),
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence(42),
+                witness: Witness::new(),
+            }],
+            output: vec![],
+        };
+        let hash = template_hash(&tx).unwrap();
+        tx.input[0].sequence = Sequence(7);
+        assert_eq!(template_hash(&tx).unwrap(), hash);
+
+        tx.input.clear();
+        assert!(template_hash(&tx).is_none());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_file_store_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("atomicals-checkpoints-{}", std::process::id()));
+        let store = FileCheckpointStore::new(&dir).unwrap();
+        let checkpoint = MiningCheckpoint {
+            template: "ab".repeat(32),
+            bitwork: "0000.8".into(),
+            searched: vec![(0, 99)],
+        };
+
+        assert!(store.load(&checkpoint.key()).unwrap().is_none());
+        store.save(&checkpoint).unwrap();
+        assert_eq!(store.load(&checkpoint.key()).unwrap(), Some(checkpoint.clone()));
+        store.remove(&checkpoint.key()).unwrap();
+        store.remove(&checkpoint.key()).unwrap();
+        assert!(store.load(&checkpoint.key()).unwrap().is_none());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
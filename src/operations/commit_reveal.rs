@@ -0,0 +1,490 @@
+use crate::types::{AtomicalsNetwork, AtomicalsTx};
+use crate::errors::{Error, Result};
+use crate::wallet::{WalletProvider, UtxoReservation};
+use crate::operations::mining::{mine_commit_with_reroll, CommitCandidate, MiningOptions};
+use crate::operations::mining_backend::MiningBackend;
+use crate::operations::mining_session::MiningSession;
+use crate::types::bitwork::Bitwork;
+use crate::utils::sleep_ms;
+use crate::utils::coin_selection::{select_coins, CoinSelection, CoinSelectionParams};
+use crate::utils::tx_size::{self, InputSpend, ScriptType};
+use crate::utils::script::{self, append_mint_update_reveal_script, reroll_payload};
+use bitcoin::{
+    Amount, Network, Transaction, TxIn, TxOut, Sequence,
+    psbt::{self, Psbt}, ScriptBuf, Address, taproot::{TaprootBuilder, LeafVersion, TapLeafHash, TapNodeHash, ControlBlock},
+    bip32::KeySource,
+    transaction::Version, key::XOnlyPublicKey, secp256k1::Secp256k1,
+    OutPoint,
+};
+use std::collections::BTreeMap;
+
+#[cfg(target_arch = "wasm32")]
+macro_rules! log {
+    ($($t:tt)*) => (web_sys::console::log_1(&format!($($t)*).into()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+macro_rules! log {
+    ($($t:tt)*) => (log::info!($($t)*))
+}
+
+/// 构建 reveal 脚本和 commit 地址
+pub fn prepare_commit_reveal_config(
+    op_type: &str,
+    child_node_xonly_pubkey: &XOnlyPublicKey,
+    atomicals_payload: &[u8],
+    network: Network
+) -> Result<(ScriptBuf, Address)> {
+    // 构建 Taproot 脚本
+    let script = append_mint_update_reveal_script(op_type, child_node_xonly_pubkey, atomicals_payload)?;
+    log!("Taproot script: {:?}", script);
+
+    // 构建 Taproot 输出并验证脚本是否在路径中
+    let secp = Secp256k1::new();
+    let taproot_info = TaprootBuilder::new()
+        .add_leaf(0, script.clone())?
+        .finalize(&secp, *child_node_xonly_pubkey)?;
+    let merkle_root = taproot_info.merkle_root()
+        .ok_or_else(|| Error::TransactionError("Failed to add script to Taproot path".into()))?;
+
+    let tr_script = ScriptBuf::new_p2tr(&secp, *child_node_xonly_pubkey, Some(merkle_root));
+    let tr_address = Address::from_script(&tr_script, network)?;
+
+    Ok((script, tr_address))
+}
+
+/// 铸造用到的地址和密钥
+///
+/// 资金地址支付 commit 手续费并接收找零，资金公钥作为 reveal 脚本的签名密钥，
+/// atomicals 发往接收地址（显式指定或钱包主地址）。
+pub(crate) struct MintKeys {
+    pub funding_address: Address,
+    pub funding_xonly: XOnlyPublicKey,
+    pub primary_address: Address,
+    pub receive_address: Address,
+}
+
+/// 从钱包读取铸造用到的地址，所有地址都必须属于 `network`
+pub(crate) async fn resolve_mint_keys<W: WalletProvider>(
+    wallet: &W,
+    receive_address: Option<&str>,
+    network: Network,
+) -> Result<MintKeys> {
+    let network = AtomicalsNetwork::from(network);
+    let funding_address = network.parse_address(&wallet.get_funding_address().await?)?;
+    let (funding_xonly, _parity) = wallet.get_funding_public_key().await?.inner.x_only_public_key();
+    let primary_address = network.parse_address(&wallet.get_address().await?)?;
+    let receive_address = match receive_address {
+        Some(address) => network.parse_address(address)?,
+        None => primary_address.clone(),
+    };
+
+    Ok(MintKeys { funding_address, funding_xonly, primary_address, receive_address })
+}
+
+/// reveal 交易中 commit 输出之后的输入，例如 Subrealm 直接铸造时花费的父 Realm
+#[derive(Debug, Clone)]
+pub struct RevealInput {
+    pub outpoint: OutPoint,
+    pub txout: TxOut,
+    /// 签名该输入的 Taproot 内部公钥
+    pub internal_key: XOnlyPublicKey,
+}
+
+type PayloadEncoder<'a> = Box<dyn FnMut(u64, u64) -> script::Result<Vec<u8>> + 'a>;
+
+/// commit-reveal 铸造流程
+///
+/// 由操作类型和 CBOR payload 生成 reveal 脚本，按 reveal 的输入输出计算 commit 输出金额，
+/// 选择并预留资金输入，按 bitwork 要求挖矿，返回待签名的 commit 和 reveal PSBT。
+/// 新的操作类型只需提供 payload 和 reveal 输出。
+pub struct CommitRevealBuilder<'a> {
+    op_type: String,
+    payload: Vec<u8>,
+    reroll: Option<PayloadEncoder<'a>>,
+    funding_address: Address,
+    reveal_key: XOnlyPublicKey,
+    network: Network,
+    reveal_inputs: Vec<RevealInput>,
+    reveal_outputs: Vec<TxOut>,
+    bitworkc: Option<Bitwork>,
+    bitworkr: Option<Bitwork>,
+    session: MiningSession,
+}
+
+impl<'a> CommitRevealBuilder<'a> {
+    /// `funding_address` 支付 commit 并接收找零，`reveal_key` 签名 reveal 脚本
+    pub fn new(
+        op_type: &str,
+        payload: Vec<u8>,
+        funding_address: Address,
+        reveal_key: XOnlyPublicKey,
+        network: Network,
+    ) -> Self {
+        Self {
+            op_type: op_type.to_string(),
+            payload,
+            reroll: None,
+            funding_address,
+            reveal_key,
+            network,
+            reveal_inputs: Vec::new(),
+            reveal_outputs: Vec::new(),
+            bitworkc: None,
+            bitworkr: None,
+            session: MiningSession::new(),
+        }
+    }
+
+    /// commit 挖矿耗尽 sequence 空间时用新的 time/nonce 重新编码 payload；
+    /// 未设置时 payload 不可更换，挖矿失败即返回错误
+    pub fn with_payload_reroll(mut self, encode: impl FnMut(u64, u64) -> script::Result<Vec<u8>> + 'a) -> Self {
+        self.reroll = Some(Box::new(encode));
+        self
+    }
+
+    /// 追加 reveal 输出，第一个输出接收铸造的 atomical
+    pub fn reveal_output(mut self, txout: TxOut) -> Self {
+        self.reveal_outputs.push(txout);
+        self
+    }
+
+    /// 追加 reveal 输入，放在 commit 输出之后
+    pub fn reveal_input(mut self, input: RevealInput) -> Self {
+        self.reveal_inputs.push(input);
+        self
+    }
+
+    pub fn with_bitworks(mut self, bitworkc: Option<Bitwork>, bitworkr: Option<Bitwork>) -> Self {
+        self.bitworkc = bitworkc;
+        self.bitworkr = bitworkr;
+        self
+    }
+
+    /// commit 和 reveal 挖矿使用的会话，调用方通过它取消挖矿或读取进度
+    pub fn with_session(mut self, session: MiningSession) -> Self {
+        self.session = session;
+        self
+    }
+
+    // reveal 交易：commit 输出放在第一位，之后是额外输入
+    fn reveal_tx(&self, commit_outpoint: OutPoint) -> Transaction {
+        Transaction {
+            version: Version(2),
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: std::iter::once(commit_outpoint)
+                .chain(self.reveal_inputs.iter().map(|input| input.outpoint))
+                .map(unsigned_input)
+                .collect(),
+            output: self.reveal_outputs.clone(),
+        }
+    }
+
+    /// 选择资金输入并构建 commit 和 reveal PSBT
+    ///
+    /// 设置了 bitwork 时总是挖矿，未提供 `mining_options` 时使用默认选项。
+    pub async fn build<W: WalletProvider, B: MiningBackend>(
+        mut self,
+        wallet: &W,
+        backend: &B,
+        mining_options: Option<&MiningOptions>,
+    ) -> Result<CommitReveal> {
+        let mining_options = mining_options.cloned().unwrap_or_else(MiningOptions::new);
+        let xonly_pubkey = self.reveal_key;
+        let (mut script, mut script_address) =
+            prepare_commit_reveal_config(&self.op_type, &xonly_pubkey, &self.payload, self.network)?;
+
+        // 获取 UTXO 列表和网络费率
+        let utxos = wallet.get_utxos().await?;
+        let fee_rate = wallet.get_network_fee_rate().await?;
+
+        // 按签名后的重量计算 reveal 手续费。挖矿只改变 sequence，更换 payload 时脚本长度不变，重量不受影响
+        let (_, control_block) = reveal_leaf(&script, xonly_pubkey)?;
+        let reveal_spends: Vec<InputSpend> = std::iter::once(InputSpend::TapScript { script: script.clone(), control_block })
+            .chain(self.reveal_inputs.iter().map(|input| {
+                InputSpend::Key(ScriptType::from_script(&input.txout.script_pubkey).unwrap_or(ScriptType::P2TR))
+            }))
+            .collect();
+        let reveal_weight = tx_size::signed_weight(&self.reveal_tx(OutPoint::null()), &reveal_spends);
+        let reveal_fee = tx_size::fee_for_weight(reveal_weight, fee_rate);
+        log!("Calculated reveal fee: {} sats ({} vB)", reveal_fee.to_sat(), reveal_weight.to_vbytes_ceil());
+
+        // commit 输出之外的输入自己支付对应的 reveal 输出
+        let outputs_value: Amount = self.reveal_outputs.iter().map(|txout| txout.value).sum();
+        let inputs_value: Amount = self.reveal_inputs.iter().map(|input| input.txout.value).sum();
+        let commit_output_value = reveal_fee + outputs_value.checked_sub(inputs_value).unwrap_or(Amount::ZERO);
+
+        // commit 交易除资金输入和找零外只有一个 P2TR 输出
+        let change_script = self.funding_address.script_pubkey();
+        let commit_output = TxOut {
+            value: commit_output_value,
+            script_pubkey: script_address.script_pubkey(),
+        };
+        let selection_params = CoinSelectionParams::new(
+            fee_rate,
+            tx_size::outputs_vsize(std::slice::from_ref(&commit_output)),
+            ScriptType::from_script(&change_script).unwrap_or(ScriptType::P2TR),
+        );
+
+        // 去掉并发操作已预留的输入；选中后立即预留，两步之间没有 await
+        let ledger = wallet.utxo_ledger().unwrap_or_default();
+        let utxos = ledger.spendable(utxos);
+
+        // 选择资金输入，找零低于粉尘线时并入手续费
+        let CoinSelection { selected: selected_utxos, fee: commit_fee, change } =
+            select_coins(&utxos, commit_output_value, &selection_params)?;
+        let reservation = ledger.reserve(&selected_utxos)?;
+        log!("Selected {} funding inputs, commit fee: {} sats", selected_utxos.len(), commit_fee.to_sat());
+
+        let mut commit_outputs = vec![commit_output];
+        if let Some(change_amount) = change {
+            commit_outputs.push(TxOut {
+                value: change_amount,
+                script_pubkey: change_script.clone(),
+            });
+        }
+
+        let mut commit_tx = Transaction {
+            version: Version(2),
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: selected_utxos.iter().map(|utxo| unsigned_input(utxo.outpoint)).collect(),
+            output: commit_outputs,
+        };
+
+        if let Some(bitworkc) = &self.bitworkc {
+            log!("Mining commit transaction...");
+            let initial = CommitCandidate {
+                tx: commit_tx.clone(),
+                script: script.clone(),
+                address: script_address.clone(),
+            };
+            // sequence 空间耗尽时更换 payload nonce，重建 reveal 脚本和 commit 输出
+            let (op_type, network, payload_len) = (self.op_type.clone(), self.network, self.payload.len());
+            let reroll = &mut self.reroll;
+            let mined = mine_commit_with_reroll(backend, initial, bitworkc, &mining_options, &self.session, |prev| {
+                let encode = reroll.as_mut()
+                    .ok_or_else(|| Error::MiningError("Payload has no nonce to re-roll".into()))?;
+                let payload = reroll_payload(payload_len, &mut **encode)?;
+                let (script, address) = prepare_commit_reveal_config(&op_type, &xonly_pubkey, &payload, network)?;
+                let mut tx = prev.tx.clone();
+                tx.output[0].script_pubkey = address.script_pubkey();
+                Ok(CommitCandidate { tx, script, address })
+            }).await?;
+            if !mined.result.success {
+                return Err(Error::MiningError("Failed to mine commit transaction".into()));
+            }
+            if let Some(mined_tx) = mined.result.get_transaction() {
+                log!("Mining successful, transaction sequence: {:?}", mined_tx.input[0].sequence);
+                commit_tx = mined_tx;
+            }
+            script = mined.script;
+            script_address = mined.address;
+        }
+
+        let mut commit_psbt = Psbt::from_unsigned_tx(commit_tx.clone())
+            .map_err(|e| Error::PsbtError(format!("Failed to create commit PSBT: {}", e)))?;
+        for (input, utxo) in commit_psbt.inputs.iter_mut().zip(&selected_utxos) {
+            input.witness_utxo = Some(utxo.txout.clone());
+            if is_key_path_output(&utxo.txout, xonly_pubkey) {
+                input.tap_internal_key = Some(xonly_pubkey);
+                input.tap_key_origins = key_origins(xonly_pubkey);
+            }
+        }
+        log!("Commit output address: {}", script_address);
+
+        let mut reveal_tx = self.reveal_tx(OutPoint::new(commit_tx.txid(), 0));
+
+        if let Some(bitworkr) = &self.bitworkr {
+            log!("Mining reveal transaction...");
+            let mining_result = backend.mine(&reveal_tx, bitworkr, &mining_options, &self.session).await?;
+            if !mining_result.success {
+                return Err(Error::MiningError("Failed to mine reveal transaction".into()));
+            }
+            if let Some(mined_tx) = mining_result.get_transaction() {
+                // 只从挖矿结果中获取 nonce(sequence) 字段
+                reveal_tx.input[0].sequence = mined_tx.input[0].sequence;
+            }
+        }
+
+        let mut reveal_psbt = Psbt::from_unsigned_tx(reveal_tx)
+            .map_err(|e| Error::PsbtError(format!("Failed to create reveal PSBT: {}", e)))?;
+        reveal_psbt.inputs[0] = script_path_input(&script, xonly_pubkey, commit_tx.output[0].clone())?;
+        for (psbt_input, input) in reveal_psbt.inputs[1..].iter_mut().zip(&self.reveal_inputs) {
+            psbt_input.witness_utxo = Some(input.txout.clone());
+            if is_key_path_output(&input.txout, input.internal_key) {
+                psbt_input.tap_internal_key = Some(input.internal_key);
+            }
+        }
+        log!("Created reveal PSBT with Taproot script");
+
+        Ok(CommitReveal { commit_psbt, reveal_psbt, reservation, change_script })
+    }
+}
+
+/// 待签名的 commit 和 reveal PSBT
+pub struct CommitReveal {
+    pub commit_psbt: Psbt,
+    pub reveal_psbt: Psbt,
+    /// commit 资金输入的预留，未广播就丢弃时释放
+    pub reservation: UtxoReservation,
+    /// commit 找零脚本，广播后找零记入账本
+    pub change_script: ScriptBuf,
+}
+
+impl CommitReveal {
+    /// 签名并依次广播 commit 和 reveal 交易
+    pub async fn sign_and_broadcast<W: WalletProvider>(self, wallet: &W) -> Result<AtomicalsTx> {
+        log!("Signing transactions...");
+        let signed_commit = wallet.sign_psbt(self.commit_psbt).await?;
+        let signed_reveal = wallet.sign_psbt(self.reveal_psbt).await?;
+
+        let commit_tx = signed_commit.extract_tx()
+            .map_err(|e| Error::TransactionError(format!("Failed to extract commit tx: {}", e)))?;
+        let reveal_tx = signed_reveal.extract_tx()
+            .map_err(|e| Error::TransactionError(format!("Failed to extract reveal tx: {}", e)))?;
+
+        let commit_txid = wallet.broadcast_transaction(commit_tx.clone()).await?;
+        log!("Commit transaction broadcast successfully: {}", commit_txid);
+        // 输入已花费，找零可供后续操作在索引器更新前使用
+        self.reservation.complete(&commit_tx, &self.change_script);
+
+        // 等待 commit 交易传播
+        sleep_ms(2000).await;
+
+        let reveal_txid = wallet.broadcast_transaction(reveal_tx.clone()).await?;
+        log!("Reveal transaction broadcast successfully: {}", reveal_txid);
+
+        Ok(AtomicalsTx::new_with_commit_reveal(
+            commit_tx,
+            reveal_tx,
+            Some(commit_txid),
+            Some(reveal_txid),
+        ))
+    }
+}
+
+fn unsigned_input(previous_output: OutPoint) -> TxIn {
+    TxIn {
+        previous_output,
+        script_sig: ScriptBuf::new(),
+        sequence: Sequence::ZERO,
+        witness: Default::default(),
+    }
+}
+
+// 只有该密钥的 P2TR 输出才设置 taproot 字段，其他类型的输入交给钱包按自己的方式签名
+fn is_key_path_output(txout: &TxOut, xonly_pubkey: XOnlyPublicKey) -> bool {
+    txout.script_pubkey == ScriptBuf::new_p2tr(&Secp256k1::verification_only(), xonly_pubkey, None)
+}
+
+fn key_origins(xonly_pubkey: XOnlyPublicKey) -> BTreeMap<XOnlyPublicKey, (Vec<TapLeafHash>, KeySource)> {
+    let mut origins = BTreeMap::new();
+    origins.insert(xonly_pubkey, (vec![], (bitcoin::bip32::Fingerprint::default(), bitcoin::bip32::DerivationPath::default())));
+    origins
+}
+
+// reveal 脚本所在的 merkle root 和花费它的控制块
+fn reveal_leaf(script: &ScriptBuf, xonly_pubkey: XOnlyPublicKey) -> Result<(TapNodeHash, ControlBlock)> {
+    let secp = Secp256k1::new();
+    let taproot_info = TaprootBuilder::new()
+        .add_leaf(0, script.clone())?
+        .finalize(&secp, xonly_pubkey)?;
+    let tap_merkle_root = taproot_info.merkle_root()
+        .ok_or_else(|| Error::TransactionError("Failed to get merkle root".into()))?;
+    let control_block = taproot_info.control_block(&(script.clone(), LeafVersion::TapScript))
+        .ok_or_else(|| Error::TransactionError("Failed to create control block".into()))?;
+    Ok((tap_merkle_root, control_block))
+}
+
+// 通过脚本路径花费 commit 输出的 PSBT 输入
+fn script_path_input(script: &ScriptBuf, xonly_pubkey: XOnlyPublicKey, commit_output: TxOut) -> Result<psbt::Input> {
+    let (tap_merkle_root, control_block) = reveal_leaf(script, xonly_pubkey)?;
+    let mut tap_scripts = BTreeMap::new();
+    tap_scripts.insert(control_block, (script.clone(), LeafVersion::TapScript));
+    Ok(psbt::Input {
+        witness_utxo: Some(commit_output),
+        witness_script: Some(script.clone()),
+        tap_internal_key: Some(xonly_pubkey),
+        tap_key_origins: key_origins(xonly_pubkey),
+        tap_merkle_root: Some(tap_merkle_root),
+        tap_scripts,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::electrum_api_mock::mock_wallet;
+    use crate::api::MockAtomicalsApi;
+    use crate::operations::mining_backend::DefaultMiningBackend;
+    use bitcoin::hashes::Hash;
+    use bitcoin::Txid;
+
+    #[tokio::test]
+    async fn test_builds_reveal_with_extra_input() {
+        let api = MockAtomicalsApi::new();
+        api.set_fee_rate(2.0);
+        let wallet = mock_wallet(&api, Network::Testnet);
+        let funding_address = wallet.funding().address();
+        let funded = api.fund(&funding_address.script_pubkey(), Amount::from_sat(100_000));
+        let (xonly, _) = wallet.get_funding_public_key().await.unwrap().inner.x_only_public_key();
+
+        // 额外输入由主钱包签名
+        let primary_script = wallet.primary().address().script_pubkey();
+        let primary_xonly = wallet.primary().x_only_public_key();
+        let extra_txout = TxOut { value: Amount::from_sat(1_000), script_pubkey: primary_script.clone() };
+        let extra = RevealInput { outpoint: OutPoint::new(Txid::all_zeros(), 1), txout: extra_txout.clone(), internal_key: primary_xonly };
+        let commit_reveal = CommitRevealBuilder::new("mod", vec![0xa0], funding_address.clone(), xonly, Network::Testnet)
+            .reveal_output(TxOut { value: Amount::from_sat(600), script_pubkey: primary_script })
+            .reveal_input(extra.clone())
+            .reveal_output(extra_txout)
+            .build(&wallet, &DefaultMiningBackend::default(), None)
+            .await
+            .unwrap();
+
+        let commit_tx = &commit_reveal.commit_psbt.unsigned_tx;
+        let reveal_tx = &commit_reveal.reveal_psbt.unsigned_tx;
+        assert_eq!(commit_tx.input[0].previous_output, funded);
+        assert_eq!(commit_tx.output[1].script_pubkey, funding_address.script_pubkey());
+        assert_eq!(reveal_tx.input[0].previous_output, OutPoint::new(commit_tx.txid(), 0));
+        assert_eq!(reveal_tx.input[1].previous_output, extra.outpoint);
+
+        // 额外输入支付对应的输出，commit 输出按签名后 reveal 的重量正好支付手续费
+        let signed_reveal = wallet.sign_psbt(commit_reveal.reveal_psbt.clone()).await.unwrap().extract_tx().unwrap();
+        let reveal_fee = commit_tx.output[0].value - Amount::from_sat(600);
+        assert_eq!(reveal_fee, Amount::from_sat(signed_reveal.vsize() as u64 * 2));
+
+        // commit 交易的实际费率不低于请求的费率
+        let signed_commit = wallet.sign_psbt(commit_reveal.commit_psbt.clone()).await.unwrap().extract_tx().unwrap();
+        let commit_outputs: Amount = signed_commit.output.iter().map(|txout| txout.value).sum();
+        assert!(Amount::from_sat(100_000) - commit_outputs >= tx_size::fee_for_weight(signed_commit.weight(), 2.0));
+
+        assert_eq!(commit_reveal.commit_psbt.inputs[0].tap_internal_key, Some(xonly));
+
+        let commit_input = &commit_reveal.reveal_psbt.inputs[0];
+        assert_eq!(commit_input.tap_scripts.len(), 1);
+        assert_eq!(commit_input.witness_utxo.as_ref(), Some(&commit_tx.output[0]));
+        assert_eq!(commit_reveal.reveal_psbt.inputs[1].tap_internal_key, Some(primary_xonly));
+
+        // 未广播就丢弃时释放资金输入
+        let ledger = wallet.utxo_ledger().unwrap();
+        assert!(ledger.is_reserved(&funded));
+        drop(commit_reveal);
+        assert!(!ledger.is_reserved(&funded));
+    }
+
+    #[tokio::test]
+    async fn test_taproot_fields_only_for_own_key_path_outputs() {
+        let api = MockAtomicalsApi::new();
+        let wallet = mock_wallet(&api, Network::Testnet);
+        let xonly = wallet.funding().x_only_public_key();
+        let txout = |script_pubkey| TxOut { value: Amount::from_sat(1_000), script_pubkey };
+
+        assert!(is_key_path_output(&txout(wallet.funding().address().script_pubkey()), xonly));
+        // 其他密钥的 P2TR 输出和非 taproot 输出都不设置
+        assert!(!is_key_path_output(&txout(wallet.primary().address().script_pubkey()), xonly));
+        let p2wpkh = Address::p2wpkh(&wallet.get_funding_public_key().await.unwrap(), Network::Testnet).unwrap();
+        assert!(!is_key_path_output(&txout(p2wpkh.script_pubkey()), xonly));
+    }
+}
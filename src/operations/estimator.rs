@@ -0,0 +1,301 @@
+use crate::errors::{Error, Result};
+use crate::operations::mining::{mine_bitwork_with_session, MiningOptions};
+use crate::operations::mining_session::MiningSession;
+use crate::types::arc20::Arc20Config;
+use crate::types::bitwork::Bitwork;
+use crate::types::mint::BitworkInfo;
+use crate::types::realm::RealmConfig;
+use crate::types::subrealm::SubrealmConfig;
+use bitcoin::{
+    absolute::LockTime, transaction::Version, Amount, OutPoint, ScriptBuf, Sequence, Transaction,
+    TxIn, TxOut, Witness,
+};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// 单个 bitwork 的成本估算
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitworkEstimate {
+    pub bitwork: String,
+    /// 以比特计的难度
+    pub difficulty_bits: f64,
+    /// 单次哈希成功的概率
+    pub success_probability: f64,
+    /// 期望哈希次数
+    pub expected_hashes: f64,
+    /// 估算使用的算力（次/秒）
+    pub hashrate: f64,
+    /// 期望耗时（秒）
+    pub expected_secs: f64,
+}
+
+impl BitworkEstimate {
+    pub fn new(bitwork: &Bitwork, hashrate: f64) -> Self {
+        let expected_hashes = bitwork.expected_hashes();
+        Self {
+            bitwork: bitwork.to_string(),
+            difficulty_bits: bitwork.difficulty_bits(),
+            success_probability: bitwork.success_probability(),
+            expected_hashes,
+            hashrate,
+            expected_secs: seconds_at(expected_hashes, hashrate),
+        }
+    }
+
+    /// 在 `attempts` 次哈希内至少成功一次的概率
+    pub fn probability_within(&self, attempts: f64) -> f64 {
+        probability_within(self.success_probability, attempts)
+    }
+
+    /// 达到给定成功概率所需的哈希次数
+    pub fn attempts_for_probability(&self, probability: f64) -> f64 {
+        if probability <= 0.0 {
+            return 0.0;
+        }
+        if probability >= 1.0 {
+            return f64::INFINITY;
+        }
+        (-probability).ln_1p() / (-self.success_probability).ln_1p()
+    }
+
+    /// 达到给定成功概率所需的时间（秒）
+    pub fn secs_for_probability(&self, probability: f64) -> f64 {
+        seconds_at(self.attempts_for_probability(probability), self.hashrate)
+    }
+}
+
+/// 一次铸造中 commit/reveal 两个 bitwork 的成本估算
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintEstimate {
+    pub commit: Option<BitworkEstimate>,
+    pub reveal: Option<BitworkEstimate>,
+    /// commit 和 reveal 依次挖矿的期望总哈希次数
+    pub expected_hashes: f64,
+    /// 期望总耗时（秒）
+    pub expected_secs: f64,
+}
+
+impl MintEstimate {
+    pub fn new(bitworkc: Option<&Bitwork>, bitworkr: Option<&Bitwork>, hashrate: f64) -> Self {
+        let commit = bitworkc.map(|bitwork| BitworkEstimate::new(bitwork, hashrate));
+        let reveal = bitworkr.map(|bitwork| BitworkEstimate::new(bitwork, hashrate));
+        let expected_hashes = commit.iter().chain(reveal.iter()).map(|e| e.expected_hashes).sum();
+
+        Self {
+            commit,
+            reveal,
+            expected_hashes,
+            expected_secs: seconds_at(expected_hashes, hashrate),
+        }
+    }
+}
+
+/// 带有 commit/reveal bitwork 要求的铸造配置
+pub trait BitworkRequirements {
+    fn bitwork_requirements(&self) -> Result<(Option<Bitwork>, Option<Bitwork>)>;
+}
+
+impl BitworkRequirements for Arc20Config {
+    fn bitwork_requirements(&self) -> Result<(Option<Bitwork>, Option<Bitwork>)> {
+        self.bitworks()
+    }
+}
+
+impl BitworkRequirements for RealmConfig {
+    fn bitwork_requirements(&self) -> Result<(Option<Bitwork>, Option<Bitwork>)> {
+        self.bitworks()
+    }
+}
+
+impl BitworkRequirements for SubrealmConfig {
+    fn bitwork_requirements(&self) -> Result<(Option<Bitwork>, Option<Bitwork>)> {
+        self.bitworks()
+    }
+}
+
+/// 按给定算力估算 bitwork 成本
+#[derive(Debug, Clone, Copy)]
+pub struct BitworkEstimator {
+    hashrate: f64,
+}
+
+impl BitworkEstimator {
+    /// 使用调用方提供的算力（次/秒）
+    pub fn with_hashrate(hashrate: f64) -> Result<Self> {
+        if !hashrate.is_finite() || hashrate <= 0.0 {
+            return Err(Error::InvalidInput(format!("Hashrate must be positive: {}", hashrate)));
+        }
+        Ok(Self { hashrate })
+    }
+
+    /// 使用正在运行或已结束的挖矿会话测得的算力
+    pub fn from_session(session: &MiningSession) -> Result<Self> {
+        Self::with_hashrate(session.progress().hashrate)
+    }
+
+    /// 按 `options` 实际挖矿 `duration_ms` 毫秒测量算力
+    pub async fn measure(options: &MiningOptions, duration_ms: u32) -> Result<Self> {
+        Self::with_hashrate(measure_hashrate(options, duration_ms).await?)
+    }
+
+    pub fn hashrate(&self) -> f64 {
+        self.hashrate
+    }
+
+    pub fn estimate(&self, bitwork: &Bitwork) -> BitworkEstimate {
+        BitworkEstimate::new(bitwork, self.hashrate)
+    }
+
+    /// 解析 `prefix` 或 `prefix.N` 形式的 bitwork 字符串并估算
+    pub fn estimate_str(&self, bitwork: &str) -> Result<BitworkEstimate> {
+        Ok(self.estimate(&bitwork.parse()?))
+    }
+
+    pub fn estimate_info(&self, bitwork: &BitworkInfo) -> Result<BitworkEstimate> {
+        Ok(self.estimate(&bitwork.to_bitwork()?))
+    }
+
+    /// 估算铸造配置中 commit/reveal 两个 bitwork 的总成本
+    pub fn estimate_mint<C: BitworkRequirements>(&self, config: &C) -> Result<MintEstimate> {
+        let (bitworkc, bitworkr) = config.bitwork_requirements()?;
+        Ok(MintEstimate::new(bitworkc.as_ref(), bitworkr.as_ref(), self.hashrate))
+    }
+}
+
+/// 用不可能满足的 bitwork 挖矿 `duration_ms` 毫秒，返回测得的算力（次/秒）
+pub async fn measure_hashrate(options: &MiningOptions, duration_ms: u32) -> Result<f64> {
+    if duration_ms == 0 {
+        return Err(Error::InvalidInput("Measurement duration must be positive".into()));
+    }
+
+    let bitwork = Bitwork::new(&"0".repeat(Bitwork::MAX_PREFIX_LEN), None)?;
+    let options = MiningOptions {
+        timeout_ms: duration_ms,
+        ..options.clone()
+    };
+    let session = MiningSession::new();
+
+    match mine_bitwork_with_session(&benchmark_tx(), &bitwork, &options, &session).await {
+        Ok(_) | Err(Error::MiningTimeout(_)) => Ok(session.progress().hashrate),
+        Err(e) => Err(e),
+    }
+}
+
+/// 在 `attempts` 次独立哈希内至少成功一次的概率：1 - (1 - p)^n
+pub fn probability_within(success_probability: f64, attempts: f64) -> f64 {
+    if attempts <= 0.0 {
+        return 0.0;
+    }
+    -(attempts * (-success_probability).ln_1p()).exp_m1()
+}
+
+fn seconds_at(hashes: f64, hashrate: f64) -> f64 {
+    if hashrate > 0.0 {
+        hashes / hashrate
+    } else {
+        f64::INFINITY
+    }
+}
+
+// 与 commit 交易结构相近的测速交易
+fn benchmark_tx() -> Transaction {
+    Transaction {
+        version: Version(2),
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ZERO,
+            witness: Witness::new(),
+        }],
+        output: vec![
+            TxOut { value: Amount::from_sat(1000), script_pubkey: ScriptBuf::from_bytes(vec![0x51; 34]) },
+            TxOut { value: Amount::from_sat(1000), script_pubkey: ScriptBuf::from_bytes(vec![0x51; 34]) },
+        ],
+    }
+}
+
+/// 按给定算力估算 bitwork 成本，返回估算结果对象
+#[wasm_bindgen]
+pub fn estimate_bitwork(bitwork: &str, hashrate: f64) -> Result<JsValue> {
+    let estimate = BitworkEstimator::with_hashrate(hashrate)?.estimate_str(bitwork)?;
+    Ok(serde_wasm_bindgen::to_value(&estimate)?)
+}
+
+/// 在 `attempts` 次哈希内满足 bitwork 的概率
+#[wasm_bindgen]
+pub fn bitwork_success_probability(bitwork: &str, attempts: f64) -> Result<f64> {
+    let bitwork: Bitwork = bitwork.parse()?;
+    Ok(probability_within(bitwork.success_probability(), attempts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_accounts_for_extension() {
+        let estimator = BitworkEstimator::with_hashrate(1000.0).unwrap();
+
+        let plain = estimator.estimate_str("0000").unwrap();
+        assert_eq!(plain.expected_hashes, 65536.0);
+        assert_eq!(plain.expected_secs, 65.536);
+
+        let ext = estimator.estimate_str("0000.8").unwrap();
+        assert_eq!(ext.expected_hashes, 131072.0);
+        assert_eq!(ext.difficulty_bits, 17.0);
+
+        let info = BitworkInfo::parse("0000.8").unwrap();
+        assert_eq!(info.difficulty, 17.0);
+        assert_eq!(estimator.estimate_info(&info).unwrap().expected_hashes, 131072.0);
+    }
+
+    #[test]
+    fn test_probability_within_attempts() {
+        let estimate = BitworkEstimator::with_hashrate(1.0).unwrap().estimate_str("0").unwrap();
+
+        assert_eq!(estimate.probability_within(0.0), 0.0);
+        assert!((estimate.probability_within(1.0) - 1.0 / 16.0).abs() < 1e-12);
+        // 1 - (15/16)^16，难度较高时趋近 1 - 1/e
+        let p = estimate.probability_within(estimate.expected_hashes);
+        assert!((p - 0.643926).abs() < 1e-6, "{}", p);
+
+        let attempts = estimate.attempts_for_probability(0.5);
+        assert!((estimate.probability_within(attempts) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_mint_config() {
+        let estimator = BitworkEstimator::with_hashrate(100.0).unwrap();
+        let mut config = SubrealmConfig::new(
+            "parent.child".into(),
+            "id".into(),
+            crate::types::subrealm::SubrealmClaimType::Rule,
+        );
+        config.bitworkc = Some("00".into());
+        config.bitworkr = Some("0.8".into());
+
+        let estimate = estimator.estimate_mint(&config).unwrap();
+        assert_eq!(estimate.commit.as_ref().unwrap().expected_hashes, 256.0);
+        assert_eq!(estimate.reveal.as_ref().unwrap().expected_hashes, 32.0);
+        assert_eq!(estimate.expected_hashes, 288.0);
+        assert_eq!(estimate.expected_secs, 2.88);
+
+        config.bitworkr = Some("0.99".into());
+        assert!(estimator.estimate_mint(&config).is_err());
+    }
+
+    #[test]
+    fn test_rejects_invalid_hashrate() {
+        assert!(BitworkEstimator::with_hashrate(0.0).is_err());
+        assert!(BitworkEstimator::with_hashrate(f64::NAN).is_err());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_measure_hashrate() {
+        let options = MiningOptions { num_workers: 1, batch_size: 1000, ..MiningOptions::new() };
+        let estimator = BitworkEstimator::measure(&options, 50).await.unwrap();
+        assert!(estimator.hashrate() > 0.0);
+    }
+}
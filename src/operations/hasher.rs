@@ -0,0 +1,175 @@
+use bitcoin::consensus;
+use bitcoin::Transaction;
+use sha2::{Digest, Sha256};
+
+/// 基于 SHA-256 midstate 的 txid 计算器
+///
+/// 交易只序列化一次，`input[0].sequence` 之前的字节预先送入哈希状态并缓存，
+/// 每个 nonce 只需从缓存的状态继续哈希 sequence 及其后的字节。
+#[derive(Clone)]
+pub struct TxidHasher {
+    midstate: Sha256,
+    suffix: Vec<u8>,
+}
+
+impl TxidHasher {
+    /// 交易没有输入时返回 None
+    pub fn new(tx: &Transaction) -> Option<Self> {
+        if tx.input.is_empty() {
+            return None;
+        }
+
+        // txid 基于不含见证数据的序列化
+        let mut stripped = tx.clone();
+        for input in stripped.input.iter_mut() {
+            input.witness.clear();
+        }
+        let bytes = consensus::serialize(&stripped);
+
+        // version(4) + 输入数量 + outpoint(36) + script_sig 长度 + script_sig
+        let script_sig_len = tx.input[0].script_sig.len();
+        let offset = 4
+            + compact_size_len(tx.input.len() as u64)
+            + 36
+            + compact_size_len(script_sig_len as u64)
+            + script_sig_len;
+
+        let mut midstate = Sha256::new();
+        midstate.update(&bytes[..offset]);
+
+        Some(Self {
+            midstate,
+            suffix: bytes[offset + 4..].to_vec(),
+        })
+    }
+
+    /// 计算指定 sequence 下的 txid（内部字节序，与 `Txid::to_byte_array` 相同）
+    #[inline]
+    pub fn txid_hash(&self, sequence: u32) -> [u8; 32] {
+        let mut engine = self.midstate.clone();
+        engine.update(sequence.to_le_bytes());
+        engine.update(&self.suffix);
+        Sha256::digest(engine.finalize()).into()
+    }
+}
+
+/// 将十六进制前缀解析为半字节序列，包含非十六进制字符时返回 None
+pub fn parse_prefix_nibbles(prefix: &str) -> Option<Vec<u8>> {
+    prefix
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect()
+}
+
+/// 取 txid 十六进制显示形式中第 `index` 个字符对应的半字节
+///
+/// txid 的显示顺序与哈希字节序相反，第 0 个字符是最后一个字节的高半字节。
+#[inline]
+pub fn txid_nibble(hash: &[u8; 32], index: usize) -> u8 {
+    let byte = hash[31 - index / 2];
+    if index.is_multiple_of(2) {
+        byte >> 4
+    } else {
+        byte & 0x0f
+    }
+}
+
+/// 检查 txid 的显示形式是否以给定半字节序列开头
+#[inline]
+pub fn hash_has_prefix(hash: &[u8; 32], nibbles: &[u8]) -> bool {
+    nibbles.len() <= 64
+        && nibbles
+            .iter()
+            .enumerate()
+            .all(|(i, nibble)| txid_nibble(hash, i) == *nibble)
+}
+
+fn compact_size_len(n: u64) -> usize {
+    match n {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x10000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::hashes::Hash;
+    use bitcoin::{
+        absolute::LockTime, transaction::Version, Amount, OutPoint, ScriptBuf, Sequence, TxIn,
+        TxOut, Txid, Witness,
+    };
+    use std::str::FromStr;
+
+    fn tx_with(script_sig: ScriptBuf, num_inputs: usize, witness: bool) -> Transaction {
+        let input = (0..num_inputs)
+            .map(|i| TxIn {
+                previous_output: OutPoint::new(
+                    Txid::from_str("8a5d6bb5cd5c3bbbd79c2a5a8e1bbbc72e1f4e0f4b1c79d2c4b1b1f2c2e4a6b8").unwrap(),
+                    i as u32,
+                ),
+                script_sig: if i == 0 { script_sig.clone() } else { ScriptBuf::new() },
+                sequence: Sequence::ZERO,
+                witness: if witness { Witness::from_slice(&[vec![0xab; 64]]) } else { Witness::new() },
+            })
+            .collect();
+
+        Transaction {
+            version: Version(2),
+            lock_time: LockTime::ZERO,
+            input,
+            output: vec![
+                TxOut { value: Amount::from_sat(1000), script_pubkey: ScriptBuf::from_bytes(vec![0x51; 34]) },
+                TxOut { value: Amount::from_sat(2000), script_pubkey: ScriptBuf::from_bytes(vec![0x00; 22]) },
+            ],
+        }
+    }
+
+    fn assert_matches_txid(tx: &Transaction) {
+        let hasher = TxidHasher::new(tx).unwrap();
+        for sequence in [0u32, 1, 0xdead_beef, u32::MAX] {
+            let mut expected = tx.clone();
+            expected.input[0].sequence = Sequence(sequence);
+            assert_eq!(hasher.txid_hash(sequence), expected.txid().to_byte_array());
+        }
+    }
+
+    #[test]
+    fn test_txid_hash_matches_transaction_txid() {
+        assert_matches_txid(&tx_with(ScriptBuf::new(), 1, false));
+        assert_matches_txid(&tx_with(ScriptBuf::new(), 3, false));
+        // 见证数据不影响 txid
+        assert_matches_txid(&tx_with(ScriptBuf::new(), 2, true));
+        // 较长的 script_sig 使 sequence 跨越 64 字节分块边界
+        assert_matches_txid(&tx_with(ScriptBuf::from_bytes(vec![0x01; 300]), 1, false));
+    }
+
+    #[test]
+    fn test_hash_prefix_matches_display_hex() {
+        let tx = tx_with(ScriptBuf::new(), 1, false);
+        let hasher = TxidHasher::new(&tx).unwrap();
+        let hash = hasher.txid_hash(42);
+
+        let mut expected = tx.clone();
+        expected.input[0].sequence = Sequence(42);
+        let txid = expected.txid().to_string();
+
+        for len in [0, 1, 2, 5, 64] {
+            let nibbles = parse_prefix_nibbles(&txid[..len]).unwrap();
+            assert!(hash_has_prefix(&hash, &nibbles));
+        }
+
+        let mut wrong = parse_prefix_nibbles(&txid[..4]).unwrap();
+        wrong[3] ^= 1;
+        assert!(!hash_has_prefix(&hash, &wrong));
+    }
+
+    #[test]
+    fn test_parse_prefix_nibbles() {
+        assert_eq!(parse_prefix_nibbles("0aF9"), Some(vec![0x0, 0xa, 0xf, 0x9]));
+        assert_eq!(parse_prefix_nibbles("xyz"), None);
+        assert!(TxidHasher::new(&tx_with(ScriptBuf::new(), 0, false)).is_none());
+    }
+}
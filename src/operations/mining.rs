@@ -1,15 +1,15 @@
 use crate::errors::{Error, Result};
+use crate::types::bitwork::Bitwork;
 use crate::types::mint::BitworkInfo;
 use crate::types::wasm::{WasmTransaction, WasmBitworkInfo};
-use bitcoin::Transaction;
+use crate::operations::hasher::TxidHasher;
+use crate::operations::mining_backend::{DefaultMiningBackend, MiningBackend};
+use crate::operations::mining_session::MiningSession;
+use crate::operations::worker_pool::WorkerPool;
+use bitcoin::{Address, ScriptBuf, Transaction};
 use bitcoin::transaction::Version;
 use wasm_bindgen::prelude::*;
 use serde::{Serialize, Deserialize};
-use js_sys::{Promise, Object, Reflect};
-use wasm_bindgen_futures::JsFuture;
-use web_sys::{Worker, MessageEvent, Event};
-use std::cell::RefCell;
-use std::rc::Rc;
 use serde_wasm_bindgen;
 use hex;
 use sha2::{Sha256, Digest};
@@ -30,6 +30,20 @@ macro_rules! log {
 pub struct MiningOptions {
     pub num_workers: u32,
     pub batch_size: u32,
+    /// 每个 payload 搜索的最大 sequence 值，超出后更换 payload nonce
+    pub max_sequence: u32,
+    /// sequence 空间耗尽后最多更换 payload nonce 的次数
+    pub max_rerolls: u32,
+    /// 挖矿超时时间（毫秒），0 表示不限时
+    pub timeout_ms: u32,
+    /// worker 脚本 URL，相对地址按页面地址解析；为空时使用 wasm 模块同目录下的 worker_entry.js
+    #[wasm_bindgen(getter_with_clone)]
+    pub worker_url: Option<String>,
+    /// 为 true 时由库生成 Blob URL 启动 worker，不需要单独部署 worker_entry.js
+    pub blob_worker: bool,
+    /// Blob worker 加载的 wasm-bindgen JS 模块 URL；为空时使用当前模块的地址
+    #[wasm_bindgen(getter_with_clone)]
+    pub module_url: Option<String>,
 }
 
 #[wasm_bindgen]
@@ -37,12 +51,29 @@ impl MiningOptions {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
         Self {
-            num_workers: 4,
+            num_workers: default_num_workers(),
             batch_size: 100000,  // 增加默认批处理大小
+            max_sequence: 0xffffffff,
+            max_rerolls: 64,
+            timeout_ms: 300000,  // 5 minutes
+            worker_url: None,
+            blob_worker: false,
+            module_url: None,
         }
     }
 }
 
+#[cfg(target_arch = "wasm32")]
+fn default_num_workers() -> u32 {
+    4
+}
+
+// 原生环境下默认使用全部 CPU 核心
+#[cfg(not(target_arch = "wasm32"))]
+fn default_num_workers() -> u32 {
+    num_cpus::get().max(1) as u32
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct MiningResult {
     pub success: bool,
@@ -78,6 +109,11 @@ pub fn mine_nonce_range(
 ) -> Option<u32> {
     log!("开始挖矿 - 范围: {} 到 {}, bitwork: {}", start_nonce, end_nonce, bitwork);
     
+    // 交易只反序列化一次，之后每个 nonce 只从缓存的 midstate 继续哈希
+    let mut transaction = tx_wrapper.to_transaction()?;
+    let hasher = TxidHasher::new(&transaction)?;
+    let bitwork: Bitwork = bitwork.parse().ok()?;
+    
     let progress_interval = 10000; // 每10000个nonce报告一次进度
     let mut nonces_tried = 0;
     
@@ -90,9 +126,12 @@ pub fn mine_nonce_range(
         }
         
         // 检查当前nonce是否产生有效的交易ID
-        if let Some(valid_nonce) = check_nonce(tx_wrapper, nonce, bitwork) {
-            log!("找到有效nonce: {}, 满足bitwork要求: {}", valid_nonce, bitwork);
-            return Some(valid_nonce);
+        if bitwork.matches_hash(&hasher.txid_hash(nonce)) {
+            // 更新WasmTransaction
+            transaction.input[0].sequence = bitcoin::transaction::Sequence(nonce);
+            *tx_wrapper = WasmTransaction::from_transaction(&transaction);
+            log!("找到有效nonce: {}, 满足bitwork要求: {}", nonce, bitwork);
+            return Some(nonce);
         }
     }
     
@@ -100,279 +139,38 @@ pub fn mine_nonce_range(
     None
 }
 
-fn check_nonce(tx: &mut WasmTransaction, nonce: u32, bitwork: &str) -> Option<u32> {
-    if let Some(mut transaction) = tx.to_transaction() {
-        if !transaction.input.is_empty() {
-            // 确保我们使用原始nonce值
-            transaction.input[0].sequence = bitcoin::transaction::Sequence(nonce);
-            let txid = transaction.txid().to_string();
-            if has_valid_bitwork(&txid, bitwork, None) {
-                // 更新WasmTransaction
-                *tx = WasmTransaction::from_transaction(&transaction);
-                log!("Found valid nonce: {}, sequence: {:?}", nonce, transaction.input[0].sequence);
-                return Some(nonce);
-            }
-        }
-    }
-    None
-}
-
-fn has_valid_bitwork(txid: &str, bitwork: &str, bitworkx: Option<u32>) -> bool {
-    if txid.starts_with(bitwork) {
-        if let Some(x) = bitworkx {
-            if let Some(next_char) = txid.chars().nth(bitwork.len()) {
-                let char_value = match next_char {
-                    '0'..='9' => next_char as u32 - '0' as u32,
-                    'a'..='f' => next_char as u32 - 'a' as u32 + 10,
-                    _ => return false,
-                };
-                return char_value >= x;
-            }
-        }
-        return true;
-    }
-    false
-}
-
-// 创建Web Worker实例
-fn create_worker(
-    tx_wrapper: &WasmTransaction,
-    start_nonce: u32,
-    end_nonce: u32,
-    bitwork_info: &BitworkInfo,
-    shared_result: Rc<RefCell<MiningResult>>,
-    workers: Rc<RefCell<Vec<Worker>>>,
-) -> Result<Worker> {
-    log!("Creating worker for nonce range: {} to {}", start_nonce, end_nonce);
-    
-    // 获取当前脚本的位置
-    let location = web_sys::window()
-        .ok_or_else(|| Error::WorkerError("Failed to get window".into()))?
-        .location();
-    
-    let origin = location.origin()
-        .map_err(|_| Error::WorkerError("Failed to get origin".into()))?;
-    
-    // 构建完整的 Worker 脚本路径
-    let worker_url = format!("{}/worker_entry.js", origin);
-    log!("Worker script URL: {}", worker_url);
-    
-    // 创建Worker
-    let worker = Worker::new(&worker_url)
-        .map_err(|e| Error::WorkerError(format!("Failed to create worker: {:?}", e)))?;
-    
-    // 监听 worker 消息
-    let worker_clone = worker.clone();
-    let workers_ref = workers.clone();
-    let onmessage = Closure::wrap(Box::new(move |e: MessageEvent| {
-        if let Ok(data) = e.data().dyn_into::<Object>() {
-            if let Ok(msg_type) = Reflect::get(&data, &"type".into()) {
-                match msg_type.as_string().as_deref() {
-                    Some("success") => {
-                        if let (Ok(success), Ok(nonce), Ok(tx_data)) = (
-                            Reflect::get(&data, &"success".into()),
-                            Reflect::get(&data, &"nonce".into()),
-                            Reflect::get(&data, &"tx".into()),
-                        ) {
-                            if success.as_bool().unwrap_or(false) {
-                                let nonce_value = nonce.as_f64().map(|n| n as u32);
-                                log!("Worker received result: success=true, nonce={:?}", nonce_value);
-                                
-                                // 更新共享结果
-                                let mut result = shared_result.borrow_mut();
-                                result.success = true;
-                                result.nonce = nonce_value;
-                                
-                                // 保存修改后的交易hex
-                                if let Ok(tx_obj) = tx_data.dyn_into::<Object>() {
-                                    if let Ok(tx_hex) = Reflect::get(&tx_obj, &"hex".into()) {
-                                        if let Some(hex_str) = tx_hex.as_string() {
-                                            result.tx_hex = Some(hex_str);
-                                            log!("Successfully saved modified transaction hex");
-                                        }
-                                    }
-                                }
-                                
-                                // 向所有 worker 发送停止信号
-                                for w in workers_ref.borrow().iter() {
-                                    let stop_msg = Object::new();
-                                    Reflect::set(&stop_msg, &"type".into(), &"stop".into()).unwrap();
-                                    let _ = w.post_message(&stop_msg);
-                                }
-                            }
-                        }
-                    }
-                    Some("progress") => {
-                        // 处理进度更新
-                        if let Ok(progress) = Reflect::get(&data, &"progress".into()) {
-                            log!("Mining progress update: {:?}", progress);
-                        }
-                    }
-                    Some("exhausted") => {
-                        log!("Worker exhausted its nonce range");
-                    }
-                    Some("error") => {
-                        log!("Worker encountered an error");
-                    }
-                    _ => {}
-                }
-            }
-        }
-    }) as Box<dyn FnMut(MessageEvent)>);
-    
-    worker.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
-    onmessage.forget();
-    
-    // 设置错误处理器
-    let worker_clone = worker.clone();
-    let onerror_callback = Closure::wrap(Box::new(move |e: Event| {
-        log!("Worker error occurred, terminating worker");
-        worker_clone.terminate();
-    }) as Box<dyn FnMut(Event)>);
-
-    worker.set_onerror(Some(onerror_callback.as_ref().unchecked_ref()));
-    onerror_callback.forget();
-    
-    // 准备发送到 Worker 的数据
-    let mut task_obj = Object::new();
-    
-    // 使用 serde_wasm_bindgen 序列化数据
-    let tx_value = serde_wasm_bindgen::to_value(tx_wrapper)
-        .map_err(|e| Error::SerializationError(format!("Failed to serialize tx_wrapper: {}", e)))?;
-    
-    Reflect::set(&task_obj, &"tx_wrapper".into(), &tx_value)?;
-    Reflect::set(&task_obj, &"start_nonce".into(), &JsValue::from(start_nonce))?;
-    Reflect::set(&task_obj, &"end_nonce".into(), &JsValue::from(end_nonce))?;
-    Reflect::set(&task_obj, &"bitwork".into(), &JsValue::from(&bitwork_info.prefix))?;
-    
-    // 发送任务到Worker
-    worker.post_message(&task_obj)
-        .map_err(|e| Error::WorkerError(format!("Failed to post message: {:?}", e)))?;
-    
-    log!("Worker initialized successfully");
-    Ok(worker)
-}
-
 #[wasm_bindgen]
 pub async fn mine_transaction(
     tx_wrapper: WasmTransaction,
     bitwork_wrapper: WasmBitworkInfo,
     options: MiningOptions,
+) -> Result<JsValue> {
+    mine_transaction_with_session(tx_wrapper, bitwork_wrapper, options, MiningSession::new()).await
+}
+
+/// 在给定会话中挖矿：进度写入会话，会话被取消或超时时终止所有 worker
+///
+/// 每次调用都会创建并在结束后终止一组 worker；需要连续挖矿时使用 [`WorkerPool`] 复用 worker。
+pub async fn mine_transaction_with_session(
+    tx_wrapper: WasmTransaction,
+    bitwork_wrapper: WasmBitworkInfo,
+    options: MiningOptions,
+    session: MiningSession,
 ) -> Result<JsValue> {
     log!("Starting mining with {} workers", options.num_workers);
     
     // 验证输入交易
     let tx = tx_wrapper.to_transaction()
         .ok_or_else(|| Error::DeserializationError("Failed to deserialize transaction".into()))?;
-    let bitwork = bitwork_wrapper.to_bitwork_info();
-    
-    let shared_result = Rc::new(RefCell::new(MiningResult {
-        success: false,
-        nonce: None,
-        tx_hex: None,
-    }));
+    let bitwork = bitwork_wrapper.to_bitwork()?;
     
-    let workers = Rc::new(RefCell::new(Vec::new()));
+    let pool = WorkerPool::create(&options)?;
+    let result = pool.run(&tx, &bitwork, &options, &session).await;
+    let _ = pool.terminate();
+    let final_result = result?;
     
-    // 定义最大 nonce 值
-    const MAX_SEQUENCE: u32 = 0xffffffff;
-    let range_per_worker = MAX_SEQUENCE / options.num_workers;
-    
-    // 创建多个Worker进行并行挖矿
-    for i in 0..options.num_workers {
-        let start_nonce = i * range_per_worker;
-        let end_nonce = if i == options.num_workers - 1 {
-            MAX_SEQUENCE  // 最后一个 worker 处理到最大值
-        } else {
-            start_nonce + range_per_worker - 1
-        };
-        
-        log!("Creating worker {} with range: {} to {}", i, start_nonce, end_nonce);
-        
-        let worker = create_worker(
-            &tx_wrapper,
-            start_nonce,
-            end_nonce,
-            &bitwork,
-            shared_result.clone(),
-            workers.clone(),
-        )?;
-        
-        workers.borrow_mut().push(worker);
-    }
-    
-    // 等待挖矿完成或超时
-    let workers_clone = workers.clone();
-    let shared_result_clone = shared_result.clone();
-    
-    let promise = Promise::new(&mut |resolve, _reject| {
-        let timeout_ms = 300000; // 5 minutes
-        let window = web_sys::window().unwrap();
-        
-        // 创建检查结果的间隔
-        let check_interval = 100; // 每100ms检查一次
-        
-        // 创建用于检查结果的闭包
-        let check_callback = {
-            let workers_inner = workers_clone.clone();
-            let shared_result_inner = shared_result_clone.clone();
-            let window = window.clone();
-            let resolve = resolve.clone();
-            
-            Closure::wrap(Box::new(move || {
-                let result = shared_result_inner.borrow();
-                if result.success {
-                    // 找到有效 nonce，停止所有 worker
-                    for worker in workers_inner.borrow().iter() {
-                        let stop_msg = Object::new();
-                        Reflect::set(&stop_msg, &"type".into(), &"stop".into()).unwrap();
-                        let _ = worker.post_message(&stop_msg);
-                        worker.terminate();
-                    }
-                    
-                    // 解析结果
-                    resolve.call1(&JsValue::NULL, &serde_wasm_bindgen::to_value(&*result).unwrap()).unwrap();
-                }
-            }) as Box<dyn FnMut()>)
-        };
-        
-        // 设置定时器
-        let _ = window.set_interval_with_callback_and_timeout_and_arguments_0(
-            check_callback.as_ref().unchecked_ref(),
-            check_interval,
-        ).unwrap();
-        
-        // 设置超时
-        let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
-            &resolve,
-            timeout_ms,
-        ).unwrap();
-        
-        // 保持closure存活
-        check_callback.forget();
-    });
-    
-    // 等待结果
-    JsFuture::from(promise).await
-        .map_err(|e| Error::AsyncError(format!("Promise error: {:?}", e)))?;
-    
-    // 获取最终结果
-    let final_result = shared_result.borrow().clone();
-    
-    // 确保所有 worker 都已停止
-    for worker in workers.borrow().iter() {
-        worker.terminate();
-    }
-    
-    // 在返回结果之前验证交易
-    if final_result.success {
-        if let Some(tx) = final_result.get_transaction() {
-            log!("Validating mined transaction...");
-            // 验证第一个输入的sequence是否已被修改
-            if !tx.input.is_empty() {
-                log!("Mined transaction sequence: {:?}", tx.input[0].sequence);
-            }
-        }
+    if let Some(tx) = final_result.get_transaction() {
+        log!("Mined transaction sequence: {:?}", tx.input[0].sequence);
     }
     
     // 返回结果
@@ -380,7 +178,482 @@ pub async fn mine_transaction(
         .map_err(|e| Error::SerializationError(format!("Failed to serialize result: {}", e)))
 }
 
+/// 将 [0, max_sequence] 平均切分为 `parts` 个连续区间，最后一个区间处理到最大值
+pub fn split_sequence_range(max_sequence: u32, parts: u32) -> Vec<(u32, u32)> {
+    split_range(0, max_sequence, parts)
+}
+
+/// 将 [start, end] 平均切分为 `parts` 个连续区间，最后一个区间处理到 `end`
+pub(crate) fn split_range(start: u32, end: u32, parts: u32) -> Vec<(u32, u32)> {
+    let parts = parts.min((end - start).saturating_add(1)).max(1);
+    let range_per_part = ((end - start) / parts).max(1);
+
+    (0..parts)
+        .map(|i| {
+            let part_start = start + i * range_per_part;
+            let part_end = if i == parts - 1 {
+                end
+            } else {
+                part_start + range_per_part - 1
+            };
+            (part_start, part_end)
+        })
+        .collect()
+}
+
 pub fn verify_bitwork(tx: &Transaction, bitwork: &BitworkInfo) -> bool {
     let tx_hash = tx.txid().to_string();
-    tx_hash.starts_with(&bitwork.prefix)
+    bitwork.matches(&tx_hash)
+}
+
+/// 原生多线程挖矿：将 sequence 空间按线程数切分，任一线程找到结果后全部停止
+#[cfg(not(target_arch = "wasm32"))]
+pub fn mine_transaction_native(
+    tx: &Transaction,
+    bitwork: &Bitwork,
+    options: &MiningOptions,
+) -> Result<MiningResult> {
+    mine_transaction_native_with_session(tx, bitwork, options, &MiningSession::new())
+}
+
+/// 原生多线程挖矿，进度写入会话，会话被取消或超时时所有线程停止
+#[cfg(not(target_arch = "wasm32"))]
+pub fn mine_transaction_native_with_session(
+    tx: &Transaction,
+    bitwork: &Bitwork,
+    options: &MiningOptions,
+    session: &MiningSession,
+) -> Result<MiningResult> {
+    use rayon::prelude::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let num_threads = options.num_workers.max(1);
+    log!("Starting native mining with {} threads, bitwork: {}", num_threads, bitwork);
+
+    let hasher = TxidHasher::new(tx)
+        .ok_or_else(|| Error::MiningError("Transaction has no inputs to mine".into()))?;
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads as usize)
+        .build()
+        .map_err(|e| Error::MiningError(format!("Failed to build mining thread pool: {}", e)))?;
+
+    let max_sequence = options.max_sequence;
+    let ranges = session.plan_round(tx, bitwork, max_sequence, num_threads);
+    let stop = AtomicBool::new(false);
+
+    let found = pool.install(|| {
+        ranges.par_iter().enumerate().find_map_any(|(i, &(start_nonce, end_nonce))| {
+            let worker = RangeWorker { index: i as u32, session, timeout_ms: options.timeout_ms };
+            let nonce = mine_sequence_range(&hasher, start_nonce, end_nonce, bitwork, &stop, options.batch_size, &worker)?;
+            stop.store(true, Ordering::Relaxed);
+            Some(nonce)
+        })
+    });
+    session.end_round(found.is_some());
+
+    match found {
+        Some(nonce) => {
+            let mut mined_tx = tx.clone();
+            mined_tx.input[0].sequence = bitcoin::transaction::Sequence(nonce);
+            log!("Native mining found nonce: {}, txid: {}", nonce, mined_tx.txid());
+            Ok(MiningResult::with_transaction(true, Some(nonce), Some(&mined_tx)))
+        }
+        None => {
+            // 未找到结果时区分取消、超时和区间耗尽
+            session.check(options.timeout_ms)?;
+            log!("Native mining exhausted sequence range 0..={} without a match", max_sequence);
+            Ok(MiningResult::with_transaction(false, None, None))
+        }
+    }
+}
+
+// 线程在会话中的区间编号及超时设置
+struct RangeWorker<'a> {
+    index: u32,
+    session: &'a MiningSession,
+    timeout_ms: u32,
+}
+
+// 单个线程搜索 [start_nonce, end_nonce]，每处理 batch_size 个 nonce 上报进度并检查停止标志
+fn mine_sequence_range(
+    hasher: &TxidHasher,
+    start_nonce: u32,
+    end_nonce: u32,
+    bitwork: &Bitwork,
+    stop: &std::sync::atomic::AtomicBool,
+    batch_size: u32,
+    worker: &RangeWorker,
+) -> Option<u32> {
+    use std::sync::atomic::Ordering;
+
+    let check_interval = batch_size.max(1);
+
+    for nonce in start_nonce..=end_nonce {
+        let scanned = nonce - start_nonce;
+        if scanned.is_multiple_of(check_interval) {
+            worker.session.record_scanned(worker.index, scanned as u64);
+            if stop.load(Ordering::Relaxed) || worker.session.check(worker.timeout_ms).is_err() {
+                return None;
+            }
+        }
+
+        if bitwork.matches_hash(&hasher.txid_hash(nonce)) {
+            worker.session.record_scanned(worker.index, scanned as u64 + 1);
+            return Some(nonce);
+        }
+    }
+    worker.session.finish_range(worker.index);
+    None
+}
+
+/// 在当前线程中依次搜索所有区间，不依赖线程池或 Web Worker
+pub fn mine_transaction_in_process(
+    tx: &Transaction,
+    bitwork: &Bitwork,
+    options: &MiningOptions,
+    session: &MiningSession,
+) -> Result<MiningResult> {
+    use std::sync::atomic::AtomicBool;
+
+    let hasher = TxidHasher::new(tx)
+        .ok_or_else(|| Error::MiningError("Transaction has no inputs to mine".into()))?;
+
+    // 区间划分与多线程实现保持一致，进度按区间上报
+    let ranges = session.plan_round(tx, bitwork, options.max_sequence, options.num_workers);
+    let stop = AtomicBool::new(false);
+
+    for (i, &(start_nonce, end_nonce)) in ranges.iter().enumerate() {
+        let worker = RangeWorker { index: i as u32, session, timeout_ms: options.timeout_ms };
+        if let Some(nonce) = mine_sequence_range(&hasher, start_nonce, end_nonce, bitwork, &stop, options.batch_size, &worker) {
+            session.end_round(true);
+            let mut mined_tx = tx.clone();
+            mined_tx.input[0].sequence = bitcoin::transaction::Sequence(nonce);
+            return Ok(MiningResult::with_transaction(true, Some(nonce), Some(&mined_tx)));
+        }
+        if let Err(e) = session.check(options.timeout_ms) {
+            session.end_round(false);
+            return Err(e);
+        }
+    }
+
+    session.end_round(false);
+    Ok(MiningResult::with_transaction(false, None, None))
+}
+
+/// 根据编译目标选择挖矿实现：wasm 下使用 Web Worker，原生环境使用线程池
+pub async fn mine_bitwork(
+    tx: &Transaction,
+    bitwork: &Bitwork,
+    options: &MiningOptions,
+) -> Result<MiningResult> {
+    mine_bitwork_with_session(tx, bitwork, options, &MiningSession::new()).await
+}
+
+/// 同 [`mine_bitwork`]，进度写入给定会话，可通过会话取消
+pub async fn mine_bitwork_with_session(
+    tx: &Transaction,
+    bitwork: &Bitwork,
+    options: &MiningOptions,
+    session: &MiningSession,
+) -> Result<MiningResult> {
+    DefaultMiningBackend::default().mine(tx, bitwork, options, session).await
+}
+
+/// 可挖矿的 commit 交易及其对应的 reveal 脚本和 commit 地址
+#[derive(Clone, Debug)]
+pub struct CommitCandidate {
+    pub tx: Transaction,
+    pub script: ScriptBuf,
+    pub address: Address,
+}
+
+/// commit 挖矿结果，包含最终采用的 reveal 脚本和 commit 地址
+#[derive(Clone)]
+pub struct CommitMiningResult {
+    pub result: MiningResult,
+    pub script: ScriptBuf,
+    pub address: Address,
+    /// 更换 payload nonce 的次数
+    pub rerolls: u32,
+}
+
+/// 挖掘 commit 交易；sequence 空间耗尽时调用 `reroll` 用新的 payload nonce
+/// 重建 reveal 脚本和 commit 输出，然后继续搜索
+pub async fn mine_commit_with_reroll<B, F>(
+    backend: &B,
+    initial: CommitCandidate,
+    bitwork: &Bitwork,
+    options: &MiningOptions,
+    session: &MiningSession,
+    mut reroll: F,
+) -> Result<CommitMiningResult>
+where
+    B: MiningBackend + ?Sized,
+    F: FnMut(&CommitCandidate) -> Result<CommitCandidate>,
+{
+    let mut candidate = initial;
+    let mut rerolls = 0;
+
+    loop {
+        let result = backend.mine(&candidate.tx, bitwork, options, session).await?;
+        if result.success {
+            return Ok(CommitMiningResult {
+                result,
+                script: candidate.script,
+                address: candidate.address,
+                rerolls,
+            });
+        }
+
+        if rerolls >= options.max_rerolls {
+            return Err(Error::MiningError(format!(
+                "No valid nonce found after {} payload re-rolls", rerolls
+            )));
+        }
+
+        rerolls += 1;
+        log!("Sequence space exhausted, re-rolling payload nonce ({}/{})", rerolls, options.max_rerolls);
+        candidate = reroll(&candidate)?;
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::operations::mining_backend::SingleThreadBackend;
+    use bitcoin::{absolute::LockTime, Amount, Network, OutPoint, Sequence, TxIn, TxOut, Witness};
+
+    fn dummy_tx() -> Transaction {
+        Transaction {
+            version: Version(2),
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ZERO,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(1000),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_native_mining_finds_prefix() {
+        let tx = dummy_tx();
+        let bitwork: Bitwork = "ab".parse().unwrap();
+        let options = MiningOptions { num_workers: 4, batch_size: 1000, ..MiningOptions::new() };
+
+        let result = mine_transaction_native(&tx, &bitwork, &options).unwrap();
+        assert!(result.success);
+
+        let mined = result.get_transaction().unwrap();
+        assert_eq!(mined.input[0].sequence.0, result.nonce.unwrap());
+        assert!(mined.txid().to_string().starts_with("ab"));
+        assert!(verify_bitwork(&mined, &BitworkInfo::from_bitwork(&bitwork)));
+    }
+
+    #[test]
+    fn test_native_mining_respects_bitwork_extension() {
+        let tx = dummy_tx();
+        let bitwork: Bitwork = "a.14".parse().unwrap();
+        let options = MiningOptions { num_workers: 2, batch_size: 1000, ..MiningOptions::new() };
+
+        let result = mine_transaction_native(&tx, &bitwork, &options).unwrap();
+        let txid = result.get_transaction().unwrap().txid().to_string();
+        assert!(txid.starts_with('a'));
+        assert!(matches!(txid.as_bytes()[1], b'e' | b'f'));
+    }
+
+    #[test]
+    fn test_native_mining_rejects_tx_without_inputs() {
+        let mut tx = dummy_tx();
+        tx.input.clear();
+        let bitwork: Bitwork = "ab".parse().unwrap();
+
+        assert!(mine_transaction_native(&tx, &bitwork, &MiningOptions::new()).is_err());
+    }
+
+    #[test]
+    fn test_native_mining_limits_sequence_range() {
+        let tx = dummy_tx();
+        let bitwork: Bitwork = "0000000000".parse().unwrap();
+        let options = MiningOptions { num_workers: 2, batch_size: 10, max_sequence: 99, max_rerolls: 0, timeout_ms: 0, ..MiningOptions::new() };
+
+        let result = mine_transaction_native(&tx, &bitwork, &options).unwrap();
+        assert!(!result.success);
+        assert!(result.nonce.is_none());
+    }
+
+    #[test]
+    fn test_mining_resumes_from_checkpoint() {
+        use crate::operations::checkpoint::{CheckpointStore, MemoryCheckpointStore, MiningCheckpoint};
+        use std::sync::Arc;
+
+        let tx = dummy_tx();
+        let hard: Bitwork = "0000000000".parse().unwrap();
+        let store = Arc::new(MemoryCheckpointStore::new());
+        let options = MiningOptions { num_workers: 2, batch_size: 10, max_sequence: 499, timeout_ms: 0, ..MiningOptions::new() };
+
+        let session = MiningSession::new();
+        session.set_checkpoint_store(store.clone());
+        assert!(!mine_transaction_in_process(&tx, &hard, &options, &session).unwrap().success);
+        let key = MiningCheckpoint::for_template(&tx, &hard).unwrap().key();
+        assert_eq!(store.load(&key).unwrap().unwrap().searched, vec![(0, 499)]);
+
+        // 扩大 sequence 上限后只搜索新增的部分
+        let options = MiningOptions { max_sequence: 999, ..options };
+        let session = MiningSession::new();
+        session.set_checkpoint_store(store.clone());
+        assert!(!mine_transaction_native_with_session(&tx, &hard, &options, &session).unwrap().success);
+        let progress = session.progress();
+        assert_eq!(progress.attempts, 500);
+        assert!(progress.ranges.iter().all(|range| range.start >= 500));
+        assert_eq!(store.load(&key).unwrap().unwrap().searched, vec![(0, 999)]);
+
+        // 已搜索过的 nonce 不会再被找到，找到结果后检查点被删除
+        let easy: Bitwork = "0".parse().unwrap();
+        let options = MiningOptions { num_workers: 1, ..options };
+        let first = mine_transaction_in_process(&tx, &easy, &options, &MiningSession::new()).unwrap().nonce.unwrap();
+        let mut checkpoint = MiningCheckpoint::for_template(&tx, &easy).unwrap();
+        checkpoint.add_range(0, first);
+        store.save(&checkpoint).unwrap();
+
+        let session = MiningSession::new();
+        session.set_checkpoint_store(store.clone());
+        let second = mine_transaction_in_process(&tx, &easy, &options, &session).unwrap().nonce.unwrap();
+        assert!(second > first);
+        assert!(store.load(&checkpoint.key()).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_commit_mining_rerolls_payload() {
+        // 每次重建时改变 commit 输出脚本，模拟新的 payload nonce
+        fn candidate(round: u8) -> CommitCandidate {
+            let script = ScriptBuf::from_bytes(vec![0x51, round]);
+            let address = Address::p2wsh(&script, Network::Regtest);
+            let mut tx = dummy_tx();
+            tx.output[0].script_pubkey = address.script_pubkey();
+            CommitCandidate { tx, script, address }
+        }
+
+        let bitwork: Bitwork = "000".parse().unwrap();
+        let options = MiningOptions { num_workers: 2, batch_size: 4, max_sequence: 15, max_rerolls: 255, timeout_ms: 0, ..MiningOptions::new() };
+
+        let mut round = 0u8;
+        let mined = mine_commit_with_reroll(&SingleThreadBackend, candidate(0), &bitwork, &options, &MiningSession::new(), |_| {
+            round += 1;
+            Ok(candidate(round))
+        }).await.unwrap();
+
+        assert!(mined.rerolls > 0);
+        assert_eq!(mined.script, candidate(mined.rerolls as u8).script);
+
+        let tx = mined.result.get_transaction().unwrap();
+        assert!(tx.txid().to_string().starts_with("000"));
+        assert!(tx.input[0].sequence.0 <= 15);
+        assert_eq!(tx.output[0].script_pubkey, mined.address.script_pubkey());
+    }
+
+    #[tokio::test]
+    async fn test_commit_mining_gives_up_after_max_rerolls() {
+        let tx = dummy_tx();
+        let script = ScriptBuf::new();
+        let address = Address::p2wsh(&script, Network::Regtest);
+        let bitwork: Bitwork = "0000000000".parse().unwrap();
+        let options = MiningOptions { num_workers: 1, batch_size: 4, max_sequence: 3, max_rerolls: 2, timeout_ms: 0, ..MiningOptions::new() };
+
+        let mut calls = 0;
+        let result = mine_commit_with_reroll(
+            &SingleThreadBackend,
+            CommitCandidate { tx, script, address },
+            &bitwork,
+            &options,
+            &MiningSession::new(),
+            |prev| {
+                calls += 1;
+                Ok(prev.clone())
+            },
+        ).await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_native_mining_reports_progress() {
+        let tx = dummy_tx();
+        let bitwork: Bitwork = "0000000000".parse().unwrap();
+        let options = MiningOptions { num_workers: 2, batch_size: 10, max_sequence: 999, max_rerolls: 0, timeout_ms: 0, ..MiningOptions::new() };
+        let session = MiningSession::new();
+
+        let result = mine_transaction_native_with_session(&tx, &bitwork, &options, &session).unwrap();
+        assert!(!result.success);
+
+        let progress = session.progress();
+        assert_eq!(progress.attempts, 1000);
+        assert_eq!(progress.expected_attempts, bitwork.expected_hashes());
+        assert_eq!(progress.ranges.len(), 2);
+        assert!(progress.ranges.iter().all(|range| range.done));
+        assert_eq!(progress.ranges[1].end, 999);
+    }
+
+    #[test]
+    fn test_native_mining_stops_when_cancelled() {
+        let tx = dummy_tx();
+        let bitwork: Bitwork = "0000000000".parse().unwrap();
+        let session = MiningSession::new();
+        let handle = session.cancel_handle();
+
+        let miner = {
+            let session = session.clone();
+            std::thread::spawn(move || {
+                let options = MiningOptions { num_workers: 2, batch_size: 1000, ..MiningOptions::new() };
+                mine_transaction_native_with_session(&tx, &bitwork, &options, &session)
+            })
+        };
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        handle.cancel();
+
+        let result = miner.join().unwrap();
+        assert!(matches!(result, Err(Error::MiningCancelled(_))));
+        assert!(session.progress().attempts > 0);
+    }
+
+    #[test]
+    fn test_native_mining_times_out() {
+        let tx = dummy_tx();
+        let bitwork: Bitwork = "0000000000".parse().unwrap();
+        let options = MiningOptions { num_workers: 2, batch_size: 1000, timeout_ms: 50, ..MiningOptions::new() };
+
+        let result = mine_transaction_native(&tx, &bitwork, &options);
+        assert!(matches!(result, Err(Error::MiningTimeout(_))));
+    }
+
+    #[test]
+    fn test_split_sequence_range() {
+        assert_eq!(split_sequence_range(99, 4), vec![(0, 23), (24, 47), (48, 71), (72, 99)]);
+        assert_eq!(split_sequence_range(u32::MAX, 1), vec![(0, u32::MAX)]);
+        // 区间数不超过 nonce 数量
+        assert_eq!(split_sequence_range(1, 8), vec![(0, 0), (1, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_single_thread_backend_matches_thread_pool() {
+        let tx = dummy_tx();
+        let bitwork: Bitwork = "ab".parse().unwrap();
+        let options = MiningOptions { num_workers: 1, batch_size: 1000, ..MiningOptions::new() };
+
+        let single = SingleThreadBackend.mine(&tx, &bitwork, &options, &MiningSession::new()).await.unwrap();
+        let pool = mine_transaction_native(&tx, &bitwork, &options).unwrap();
+
+        // 单区间时两种实现都返回最小的满足条件的 nonce
+        assert!(single.success);
+        assert_eq!(single.nonce, pool.nonce);
+        assert_eq!(single.tx_hex, pool.tx_hex);
+    }
 }
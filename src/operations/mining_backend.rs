@@ -0,0 +1,163 @@
+use crate::errors::Result;
+use crate::operations::mining::{MiningOptions, MiningResult};
+use crate::operations::mining_session::MiningSession;
+use crate::types::bitwork::Bitwork;
+use async_trait::async_trait;
+use bitcoin::Transaction;
+
+/// 挖矿后端：在 `tx.input[0].sequence` 上搜索满足 bitwork 的 nonce
+///
+/// 铸造操作只依赖这个 trait，集成方可以实现自己的后端（例如远程矿机）。
+/// 实现应将进度写入 `session`，并在会话被取消或超时时尽快返回对应错误；
+/// 搜索完 `options.max_sequence` 仍未找到时返回 `success == false` 的结果。
+#[async_trait(?Send)]
+pub trait MiningBackend {
+    async fn mine(
+        &self,
+        tx: &Transaction,
+        bitwork: &Bitwork,
+        options: &MiningOptions,
+        session: &MiningSession,
+    ) -> Result<MiningResult>;
+}
+
+/// 浏览器 Web Worker 后端，每个 worker 负责一段 sequence 区间
+///
+/// 第一次挖矿时按该次的 `MiningOptions` 创建 worker 池，之后的挖矿（例如同一次铸造中的
+/// reveal）复用这些 worker，并按新的 `num_workers` 调整池大小；后端被释放时 worker 随之终止。
+/// 通过 `set_pool` 交给后端的池由调用方负责调整大小，后端不会改动它。
+#[cfg(target_arch = "wasm32")]
+#[derive(Default)]
+pub struct WorkerBackend {
+    pool: std::cell::RefCell<Option<crate::operations::worker_pool::WorkerPool>>,
+    external: std::cell::Cell<bool>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl WorkerBackend {
+    /// 使用调用方创建的 worker 池
+    pub fn with_pool(pool: crate::operations::worker_pool::WorkerPool) -> Self {
+        let backend = Self::default();
+        backend.set_pool(Some(pool));
+        backend
+    }
+
+    /// 替换后端使用的 worker 池；传入 `None` 时下次挖矿按 `MiningOptions` 重新创建
+    pub fn set_pool(&self, pool: Option<crate::operations::worker_pool::WorkerPool>) {
+        self.external.set(pool.is_some());
+        *self.pool.borrow_mut() = pool;
+    }
+
+    /// 当前的 worker 池，尚未挖矿时为 `None`
+    pub fn current_pool(&self) -> Option<crate::operations::worker_pool::WorkerPool> {
+        self.pool.borrow().clone()
+    }
+
+    fn pool(&self, options: &MiningOptions) -> Result<crate::operations::worker_pool::WorkerPool> {
+        let mut slot = self.pool.borrow_mut();
+        match slot.as_ref() {
+            Some(pool) => {
+                if !self.external.get() && pool.size() != options.num_workers.max(1) {
+                    pool.resize(options.num_workers.max(1))?;
+                }
+                Ok(pool.clone())
+            }
+            None => Ok(slot.insert(crate::operations::worker_pool::WorkerPool::create(options)?).clone()),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[async_trait(?Send)]
+impl MiningBackend for WorkerBackend {
+    async fn mine(
+        &self,
+        tx: &Transaction,
+        bitwork: &Bitwork,
+        options: &MiningOptions,
+        session: &MiningSession,
+    ) -> Result<MiningResult> {
+        self.pool(options)?.run(tx, bitwork, options, session).await
+    }
+}
+
+/// 直接使用调用方持有的 worker 池，池的大小由调用方通过 `resize` 控制
+#[cfg(target_arch = "wasm32")]
+#[async_trait(?Send)]
+impl MiningBackend for crate::operations::worker_pool::WorkerPool {
+    async fn mine(
+        &self,
+        tx: &Transaction,
+        bitwork: &Bitwork,
+        options: &MiningOptions,
+        session: &MiningSession,
+    ) -> Result<MiningResult> {
+        self.run(tx, bitwork, options, session).await
+    }
+}
+
+/// 原生线程池后端，在阻塞线程中运行 rayon 多线程挖矿
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreadPoolBackend;
+
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait(?Send)]
+impl MiningBackend for ThreadPoolBackend {
+    async fn mine(
+        &self,
+        tx: &Transaction,
+        bitwork: &Bitwork,
+        options: &MiningOptions,
+        session: &MiningSession,
+    ) -> Result<MiningResult> {
+        use crate::errors::Error;
+        use crate::operations::mining::mine_transaction_native_with_session;
+
+        let tx = tx.clone();
+        let bitwork = bitwork.clone();
+        let options = options.clone();
+        let session = session.clone();
+        tokio::task::spawn_blocking(move || mine_transaction_native_with_session(&tx, &bitwork, &options, &session))
+            .await
+            .map_err(|e| Error::MiningError(format!("Mining task failed: {}", e)))?
+    }
+}
+
+/// 在调用线程中同步挖矿的后端，结果可复现，主要用于测试
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SingleThreadBackend;
+
+#[async_trait(?Send)]
+impl MiningBackend for SingleThreadBackend {
+    async fn mine(
+        &self,
+        tx: &Transaction,
+        bitwork: &Bitwork,
+        options: &MiningOptions,
+        session: &MiningSession,
+    ) -> Result<MiningResult> {
+        crate::operations::mining::mine_transaction_in_process(tx, bitwork, options, session)
+    }
+}
+
+/// 当前编译目标的默认后端：wasm 下使用 Web Worker，原生环境使用线程池
+#[cfg(target_arch = "wasm32")]
+pub type DefaultMiningBackend = WorkerBackend;
+
+/// 当前编译目标的默认后端：wasm 下使用 Web Worker，原生环境使用线程池
+#[cfg(not(target_arch = "wasm32"))]
+pub type DefaultMiningBackend = ThreadPoolBackend;
+
+#[async_trait(?Send)]
+impl<B: MiningBackend + ?Sized> MiningBackend for &B {
+    async fn mine(
+        &self,
+        tx: &Transaction,
+        bitwork: &Bitwork,
+        options: &MiningOptions,
+        session: &MiningSession,
+    ) -> Result<MiningResult> {
+        (**self).mine(tx, bitwork, options, session).await
+    }
+}
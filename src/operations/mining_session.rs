@@ -0,0 +1,443 @@
+use crate::errors::{Error, Result};
+use crate::operations::checkpoint::{plan_ranges, CheckpointStore, MiningCheckpoint};
+use crate::types::bitwork::Bitwork;
+use bitcoin::Transaction;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use wasm_bindgen::prelude::*;
+
+#[cfg(target_arch = "wasm32")]
+macro_rules! log {
+    ($($t:tt)*) => (web_sys::console::log_1(&format!($($t)*).into()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+macro_rules! log {
+    ($($t:tt)*) => (log::info!($($t)*))
+}
+
+/// 两次进度回调之间的最小间隔（毫秒），检查点按同样的间隔保存
+const PROGRESS_REPORT_INTERVAL_MS: f64 = 1000.0;
+
+/// 单个 worker/线程负责的 nonce 区间及已搜索数量
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct NonceRange {
+    pub worker: u32,
+    pub start: u32,
+    pub end: u32,
+    /// 从 `start` 开始已搜索的 nonce 数量
+    pub scanned: u64,
+    /// 区间是否已搜索完毕
+    pub done: bool,
+}
+
+/// 挖矿进度快照
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MiningProgress {
+    /// 本次会话累计尝试的哈希次数（包括更换 payload 之前的轮次）
+    pub attempts: u64,
+    /// 平均每秒尝试次数
+    pub hashrate: f64,
+    pub elapsed_ms: u64,
+    /// 按 bitwork 难度计算的期望哈希次数
+    pub expected_attempts: f64,
+    /// 按当前算力估算找到结果还需的时间；算力未知时为 None
+    pub eta_ms: Option<u64>,
+    /// 当前这一轮的 nonce 区间
+    pub ranges: Vec<NonceRange>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type ProgressCallback = Box<dyn Fn(&MiningProgress) + Send + Sync>;
+
+#[cfg(target_arch = "wasm32")]
+type ProgressCallback = Box<dyn Fn(&MiningProgress)>;
+
+struct SessionState {
+    cancelled: AtomicBool,
+    attempts: AtomicU64,
+    expected_attempts: Mutex<f64>,
+    started_ms: Mutex<Option<f64>>,
+    last_report_ms: Mutex<f64>,
+    ranges: Mutex<Vec<NonceRange>>,
+    callback: Mutex<Option<ProgressCallback>>,
+    checkpoint_store: Mutex<Option<Arc<dyn CheckpointStore>>>,
+    // 当前模板在本轮之前已搜索过的区间
+    checkpoint: Mutex<Option<MiningCheckpoint>>,
+}
+
+/// 挖矿会话：汇总各 worker 的进度，并提供取消和超时控制
+///
+/// 会话可以克隆，所有克隆共享同一状态；同一会话可跨多轮挖矿（例如更换 payload nonce）使用。
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct MiningSession {
+    state: Arc<SessionState>,
+}
+
+/// 只能用于取消挖矿的句柄
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct CancelHandle {
+    state: Arc<SessionState>,
+}
+
+#[wasm_bindgen]
+impl CancelHandle {
+    /// 取消挖矿，所有 worker 会被终止
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for MiningSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl MiningSession {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(SessionState {
+                cancelled: AtomicBool::new(false),
+                attempts: AtomicU64::new(0),
+                expected_attempts: Mutex::new(0.0),
+                started_ms: Mutex::new(None),
+                last_report_ms: Mutex::new(0.0),
+                ranges: Mutex::new(Vec::new()),
+                callback: Mutex::new(None),
+                checkpoint_store: Mutex::new(None),
+                checkpoint: Mutex::new(None),
+            }),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle {
+            state: self.state.clone(),
+        }
+    }
+
+    /// 以 JS 对象返回当前进度
+    #[wasm_bindgen(js_name = progress)]
+    pub fn progress_js(&self) -> Result<JsValue> {
+        Ok(serde_wasm_bindgen::to_value(&self.progress())?)
+    }
+}
+
+impl MiningSession {
+    /// 当前进度快照
+    pub fn progress(&self) -> MiningProgress {
+        let attempts = self.state.attempts.load(Ordering::Relaxed);
+        let expected_attempts = *self.state.expected_attempts.lock().unwrap();
+        let elapsed_ms = self.elapsed_ms();
+
+        let hashrate = if elapsed_ms > 0 {
+            attempts as f64 * 1000.0 / elapsed_ms as f64
+        } else {
+            0.0
+        };
+
+        // 每次哈希相互独立，剩余期望时间与已尝试次数无关
+        let eta_ms = if hashrate > 0.0 && expected_attempts > 0.0 {
+            Some((expected_attempts / hashrate * 1000.0) as u64)
+        } else {
+            None
+        };
+
+        MiningProgress {
+            attempts,
+            hashrate,
+            elapsed_ms,
+            expected_attempts,
+            eta_ms,
+            ranges: self.state.ranges.lock().unwrap().clone(),
+        }
+    }
+
+    /// 设置进度回调，最多每秒调用一次
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_progress<F>(&self, callback: F)
+    where
+        F: Fn(&MiningProgress) + Send + Sync + 'static,
+    {
+        *self.state.callback.lock().unwrap() = Some(Box::new(callback));
+    }
+
+    /// 设置进度回调，最多每秒调用一次
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_progress<F>(&self, callback: F)
+    where
+        F: Fn(&MiningProgress) + 'static,
+    {
+        *self.state.callback.lock().unwrap() = Some(Box::new(callback));
+    }
+
+    /// 设置检查点存储：之后对同一交易模板挖矿时跳过已搜索过的区间
+    pub fn set_checkpoint_store<S: CheckpointStore + 'static>(&self, store: S) {
+        *self.state.checkpoint_store.lock().unwrap() = Some(Arc::new(store));
+    }
+
+    /// 当前模板的检查点，包括本轮已搜索的部分；未设置检查点存储时为 None
+    pub fn checkpoint(&self) -> Option<MiningCheckpoint> {
+        let mut checkpoint = self.state.checkpoint.lock().unwrap().clone()?;
+        for range in self.state.ranges.lock().unwrap().iter() {
+            if range.scanned > 0 {
+                checkpoint.add_range(range.start, (range.start as u64 + range.scanned - 1) as u32);
+            }
+        }
+        Some(checkpoint)
+    }
+
+    pub fn elapsed_ms(&self) -> u64 {
+        match *self.state.started_ms.lock().unwrap() {
+            Some(started) => (now_ms() - started).max(0.0) as u64,
+            None => 0,
+        }
+    }
+
+    /// 超过 `timeout_ms` 时返回 true，0 表示不限时
+    pub fn is_timed_out(&self, timeout_ms: u32) -> bool {
+        timeout_ms > 0 && self.elapsed_ms() >= timeout_ms as u64
+    }
+
+    /// 已取消或超时时返回对应错误
+    pub fn check(&self, timeout_ms: u32) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::MiningCancelled("Mining was cancelled".into()));
+        }
+        if self.is_timed_out(timeout_ms) {
+            return Err(Error::MiningTimeout(format!("Mining timed out after {} ms", timeout_ms)));
+        }
+        Ok(())
+    }
+
+    /// 开始新一轮挖矿：记录难度并替换 nonce 区间，累计尝试次数和计时保留
+    pub(crate) fn begin_round(&self, bitwork: &Bitwork, ranges: &[(u32, u32)]) {
+        *self.state.expected_attempts.lock().unwrap() = bitwork.expected_hashes();
+        self.state.started_ms.lock().unwrap().get_or_insert_with(now_ms);
+        *self.state.ranges.lock().unwrap() = ranges
+            .iter()
+            .enumerate()
+            .map(|(i, &(start, end))| NonceRange {
+                worker: i as u32,
+                start,
+                end,
+                scanned: 0,
+                done: false,
+            })
+            .collect();
+    }
+
+    /// 开始对 `tx` 的新一轮挖矿，返回本轮需要搜索的区间
+    ///
+    /// 设置了检查点存储时先读取该模板的检查点，只分配尚未搜索的部分；
+    /// 返回空列表表示整个 sequence 空间都已搜索过。
+    pub(crate) fn plan_round(&self, tx: &Transaction, bitwork: &Bitwork, max_sequence: u32, parts: u32) -> Vec<(u32, u32)> {
+        let store = self.state.checkpoint_store.lock().unwrap().clone();
+        let checkpoint = store.and_then(|store| {
+            let mut checkpoint = MiningCheckpoint::for_template(tx, bitwork)?;
+            match store.load(&checkpoint.key()) {
+                Ok(Some(saved)) => checkpoint.searched = saved.searched,
+                Ok(None) => {}
+                Err(e) => log!("Failed to load mining checkpoint: {}", e),
+            }
+            Some(checkpoint)
+        });
+
+        let searched = checkpoint.as_ref().map(|c| c.searched.as_slice()).unwrap_or_default();
+        let ranges = plan_ranges(max_sequence, searched, parts);
+        if let Some(checkpoint) = &checkpoint {
+            log!("Resuming template {}: {} nonces already searched", checkpoint.template, checkpoint.searched_count());
+        }
+
+        *self.state.checkpoint.lock().unwrap() = checkpoint;
+        self.begin_round(bitwork, &ranges);
+        ranges
+    }
+
+    /// 结束当前轮次：找到结果时删除检查点，否则保存已搜索的区间
+    pub(crate) fn end_round(&self, found: bool) {
+        if found {
+            let key = self.state.checkpoint.lock().unwrap().take().map(|c| c.key());
+            if let (Some(store), Some(key)) = (self.checkpoint_store(), key) {
+                if let Err(e) = store.remove(&key) {
+                    log!("Failed to remove mining checkpoint: {}", e);
+                }
+            }
+        } else {
+            self.save_checkpoint();
+        }
+    }
+
+    fn checkpoint_store(&self) -> Option<Arc<dyn CheckpointStore>> {
+        self.state.checkpoint_store.lock().unwrap().clone()
+    }
+
+    fn save_checkpoint(&self) {
+        if let (Some(store), Some(checkpoint)) = (self.checkpoint_store(), self.checkpoint()) {
+            if let Err(e) = store.save(&checkpoint) {
+                log!("Failed to save mining checkpoint: {}", e);
+            }
+        }
+    }
+
+    /// 更新某个区间的已搜索数量
+    pub(crate) fn record_scanned(&self, worker: u32, scanned: u64) {
+        {
+            let mut ranges = self.state.ranges.lock().unwrap();
+            if let Some(range) = ranges.get_mut(worker as usize) {
+                if scanned > range.scanned {
+                    self.state.attempts.fetch_add(scanned - range.scanned, Ordering::Relaxed);
+                    range.scanned = scanned;
+                }
+            }
+        }
+        self.maybe_report();
+    }
+
+    /// 标记某个区间已搜索完毕
+    pub(crate) fn finish_range(&self, worker: u32) {
+        let scanned = {
+            let mut ranges = self.state.ranges.lock().unwrap();
+            match ranges.get_mut(worker as usize) {
+                Some(range) => {
+                    range.done = true;
+                    range.end as u64 - range.start as u64 + 1
+                }
+                None => return,
+            }
+        };
+        self.record_scanned(worker, scanned);
+    }
+
+    /// 当前轮次的所有区间是否都已搜索完毕
+    pub(crate) fn all_ranges_done(&self) -> bool {
+        let ranges = self.state.ranges.lock().unwrap();
+        !ranges.is_empty() && ranges.iter().all(|range| range.done)
+    }
+
+    fn maybe_report(&self) {
+        let now = now_ms();
+        {
+            let mut last = self.state.last_report_ms.lock().unwrap();
+            if now - *last < PROGRESS_REPORT_INTERVAL_MS {
+                return;
+            }
+            *last = now;
+        }
+
+        self.save_checkpoint();
+
+        let callback = self.state.callback.lock().unwrap();
+        if let Some(callback) = callback.as_ref() {
+            callback(&self.progress());
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+impl MiningSession {
+    /// 设置 JS 进度回调，参数为进度对象
+    #[wasm_bindgen(js_name = onProgress)]
+    pub fn on_progress_js(&self, callback: js_sys::Function) {
+        self.on_progress(move |progress| {
+            if let Ok(value) = serde_wasm_bindgen::to_value(progress) {
+                let _ = callback.call1(&JsValue::NULL, &value);
+            }
+        });
+    }
+
+    /// 把检查点保存到 localStorage，`prefix` 为空时使用默认键前缀
+    #[wasm_bindgen(js_name = enableCheckpoints)]
+    pub fn enable_checkpoints(&self, prefix: Option<String>) {
+        self.set_checkpoint_store(crate::operations::checkpoint::LocalStorageCheckpointStore::new(prefix));
+    }
+
+    /// 在当前会话中挖矿，返回 Promise
+    pub fn mine(
+        &self,
+        tx_wrapper: crate::types::wasm::WasmTransaction,
+        bitwork_wrapper: crate::types::wasm::WasmBitworkInfo,
+        options: super::mining::MiningOptions,
+    ) -> js_sys::Promise {
+        let session = self.clone();
+        wasm_bindgen_futures::future_to_promise(async move {
+            super::mining::mine_transaction_with_session(tx_wrapper, bitwork_wrapper, options, session)
+                .await
+                .map_err(JsValue::from)
+        })
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_accumulates_across_rounds() {
+        let session = MiningSession::new();
+        let bitwork: Bitwork = "0000".parse().unwrap();
+
+        session.begin_round(&bitwork, &[(0, 99), (100, 199)]);
+        session.record_scanned(0, 40);
+        session.record_scanned(1, 10);
+        // 回退的进度不重复计数
+        session.record_scanned(1, 5);
+        assert_eq!(session.progress().attempts, 50);
+        assert!(!session.all_ranges_done());
+
+        session.finish_range(0);
+        session.finish_range(1);
+        assert!(session.all_ranges_done());
+        assert_eq!(session.progress().attempts, 200);
+
+        session.begin_round(&bitwork, &[(0, 9)]);
+        session.record_scanned(0, 5);
+        let progress = session.progress();
+        assert_eq!(progress.attempts, 205);
+        assert_eq!(progress.expected_attempts, 65536.0);
+        assert_eq!(progress.ranges, vec![NonceRange { worker: 0, start: 0, end: 9, scanned: 5, done: false }]);
+    }
+
+    #[test]
+    fn test_cancel_handle_cancels_session() {
+        let session = MiningSession::new();
+        let handle = session.cancel_handle();
+        assert!(session.check(0).is_ok());
+
+        handle.cancel();
+        assert!(session.is_cancelled());
+        assert!(matches!(session.check(0), Err(Error::MiningCancelled(_))));
+    }
+}
@@ -1,9 +1,25 @@
+pub mod checkpoint;
+pub mod commit_reveal;
+pub mod estimator;
+pub mod hasher;
 pub mod mining;
+pub mod mining_backend;
+pub mod mining_session;
 pub mod mint_ft;
 pub mod mint_realm;
 pub mod mint_subrealm;
+pub mod worker_pool;
+pub mod worker_protocol;
+pub mod worker_script;
 
-pub use mint_ft::mint_ft;
-pub use mint_realm::mint_realm;
-pub use mint_subrealm::mint_subrealm;
+pub use commit_reveal::{CommitReveal, CommitRevealBuilder, RevealInput};
+pub use mint_ft::{mint_ft, mint_ft_with_backend};
+pub use mint_realm::{mint_realm, mint_realm_with_backend};
+pub use mint_subrealm::{mint_subrealm, mint_subrealm_with_backend};
 pub use mining::{mine_transaction, MiningOptions, MiningResult};
+pub use mining_backend::{DefaultMiningBackend, MiningBackend, SingleThreadBackend};
+pub use mining_session::{CancelHandle, MiningProgress, MiningSession};
+pub use worker_pool::WorkerPool;
+pub use worker_script::WorkerScript;
+pub use checkpoint::{CheckpointStore, MemoryCheckpointStore, MiningCheckpoint};
+pub use estimator::{BitworkEstimate, BitworkEstimator, MintEstimate};
@@ -0,0 +1,425 @@
+use crate::errors::{Error, Result};
+use crate::operations::mining::{MiningOptions, MiningResult};
+use crate::operations::mining_session::MiningSession;
+use crate::operations::worker_protocol::{
+    decode_job_message, encode_message, encode_task_message, SharedStopFlag, WorkerRequest, WorkerResponse,
+};
+use crate::operations::worker_script::{WorkerScript, WorkerScriptUrl};
+use crate::types::bitwork::Bitwork;
+use crate::types::wasm::{WasmBitworkInfo, WasmTransaction};
+use bitcoin::Transaction;
+use js_sys::{Function, Promise};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Event, MessageEvent, Worker};
+
+#[cfg(target_arch = "wasm32")]
+macro_rules! log {
+    ($($t:tt)*) => (web_sys::console::log_1(&format!($($t)*).into()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+macro_rules! log {
+    ($($t:tt)*) => (log::info!($($t)*))
+}
+
+/// 主线程检查挖矿是否结束的间隔（毫秒）
+const CHECK_INTERVAL_MS: i32 = 100;
+
+// window 和 worker 全局作用域都提供的定时器
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setInterval)]
+    fn set_interval(handler: &Function, timeout: i32) -> i32;
+
+    #[wasm_bindgen(js_name = clearInterval)]
+    fn clear_interval(handle: i32);
+}
+
+type MessageHandler = Closure<dyn FnMut(MessageEvent)>;
+type ErrorHandler = Closure<dyn FnMut(Event)>;
+
+/// 常驻的 Web Worker 池
+///
+/// worker 只在创建或扩容时加载一次 wasm 模块，之后在多次挖矿（包括 commit 和 reveal、
+/// 多次铸造）之间复用。每次挖矿有独立的任务编号，上一次任务残留的消息会被忽略。
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct WorkerPool {
+    inner: Rc<PoolInner>,
+}
+
+struct PoolInner {
+    script: WorkerScriptUrl,
+    workers: Rc<RefCell<Vec<Worker>>>,
+    // 当前任务的消息和错误处理器，替换时旧的处理器被释放
+    handlers: RefCell<Vec<(MessageHandler, ErrorHandler)>>,
+    next_job: Cell<u32>,
+    busy: Cell<bool>,
+}
+
+struct BusyGuard<'a>(&'a Cell<bool>);
+
+impl Drop for BusyGuard<'_> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
+impl Drop for PoolInner {
+    fn drop(&mut self) {
+        for worker in self.workers.borrow().iter() {
+            worker.terminate();
+        }
+    }
+}
+
+impl WorkerPool {
+    /// 按 `options` 中的 worker 数量和脚本地址创建 worker 池
+    pub fn create(options: &MiningOptions) -> Result<Self> {
+        let script = WorkerScriptUrl::create(&WorkerScript::from_options(options))?;
+        log!("Worker script URL: {}", script.url());
+
+        let pool = Self {
+            inner: Rc::new(PoolInner {
+                script,
+                workers: Rc::new(RefCell::new(Vec::new())),
+                handlers: RefCell::new(Vec::new()),
+                next_job: Cell::new(0),
+                busy: Cell::new(false),
+            }),
+        };
+        pool.resize(options.num_workers.max(1))?;
+        Ok(pool)
+    }
+
+    /// 用池中的 worker 搜索 `tx.input[0].sequence`
+    ///
+    /// 区间按池的大小切分，`options.num_workers` 不影响已创建的池；
+    /// 同一时间只能执行一个任务，结束后 worker 停止搜索但不会被终止。
+    pub async fn run(
+        &self,
+        tx: &Transaction,
+        bitwork: &Bitwork,
+        options: &MiningOptions,
+        session: &MiningSession,
+    ) -> Result<MiningResult> {
+        if self.inner.busy.replace(true) {
+            return Err(Error::WorkerError("Worker pool is already mining".into()));
+        }
+        // Promise 被丢弃时同样释放占用标记
+        let _busy = BusyGuard(&self.inner.busy);
+        self.run_job(tx, bitwork, options, session).await
+    }
+
+    async fn run_job(
+        &self,
+        tx: &Transaction,
+        bitwork: &Bitwork,
+        options: &MiningOptions,
+        session: &MiningSession,
+    ) -> Result<MiningResult> {
+        if tx.input.is_empty() {
+            return Err(Error::MiningError("Transaction has no inputs to mine".into()));
+        }
+
+        let size = self.size();
+        log!("Starting mining job with {} pooled workers", size);
+
+        // 按 worker 数量切分 sequence 空间，跳过检查点中已搜索的区间
+        let ranges = session.plan_round(tx, bitwork, options.max_sequence, size);
+        if ranges.is_empty() {
+            log!("Checkpoint covers the whole sequence range, nothing left to mine");
+            session.end_round(false);
+            return Ok(MiningResult::with_transaction(false, None, None));
+        }
+
+        let job = self.inner.next_job.get().wrapping_add(1).max(1);
+        self.inner.next_job.set(job);
+        let state = JobState::new(job, session.clone(), self.inner.workers.clone());
+        let tx_hex = hex::encode(bitcoin::consensus::serialize(tx));
+
+        let mut outcome = self.start_tasks(&state, &tx_hex, bitwork, &ranges, options.batch_size);
+        if outcome.is_ok() {
+            outcome = wait_until_finished(&state, options.timeout_ms).await;
+        }
+
+        state.stop_all();
+        self.detach_handlers();
+        self.replace_failed_workers(&state);
+        outcome?;
+
+        let result = state.result.borrow().clone();
+        session.end_round(result.success);
+
+        if !result.success {
+            if let Some(message) = state.error.borrow().clone() {
+                return Err(Error::WorkerError(message));
+            }
+            // 未找到结果时区分取消、超时和区间耗尽
+            session.check(options.timeout_ms)?;
+        }
+        Ok(result)
+    }
+
+    // 为本次任务绑定消息处理器并向各 worker 发送区间
+    fn start_tasks(&self, state: &JobState, tx_hex: &str, bitwork: &Bitwork, ranges: &[(u32, u32)], batch_size: u32) -> Result<()> {
+        let workers = self.inner.workers.borrow();
+        let mut handlers = self.inner.handlers.borrow_mut();
+        handlers.clear();
+
+        for (i, (worker, &(start_nonce, end_nonce))) in workers.iter().zip(ranges).enumerate() {
+            let index = i as u32;
+            log!("Worker {} assigned range: {} to {}", index, start_nonce, end_nonce);
+
+            let onmessage = message_handler(state.clone());
+            worker.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+            let onerror = error_handler(index, state.clone());
+            worker.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+            handlers.push((onmessage, onerror));
+
+            let task = encode_task_message(state.job, WorkerRequest::Task {
+                worker: index,
+                tx_hex: tx_hex.to_string(),
+                start_nonce,
+                end_nonce,
+                bitwork: bitwork.to_string(),
+                batch_size,
+                shared_stop: false,
+            }, state.stop_flag.as_ref())?;
+            worker.post_message(&task)
+                .map_err(|e| Error::WorkerError(format!("Failed to post message: {:?}", e)))?;
+        }
+        Ok(())
+    }
+
+    fn detach_handlers(&self) {
+        for worker in self.inner.workers.borrow().iter() {
+            worker.set_onmessage(None);
+            worker.set_onerror(None);
+        }
+        self.inner.handlers.borrow_mut().clear();
+    }
+
+    // 脚本出错的 worker 不再可靠，终止后重新创建
+    fn replace_failed_workers(&self, state: &JobState) {
+        let mut workers = self.inner.workers.borrow_mut();
+        for &index in state.failed.borrow().iter() {
+            let Some(slot) = workers.get_mut(index as usize) else {
+                continue;
+            };
+            slot.terminate();
+            match self.inner.script.spawn() {
+                Ok(worker) => *slot = worker,
+                Err(e) => log!("Failed to replace worker {}: {}", index, e),
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl WorkerPool {
+    #[wasm_bindgen(constructor)]
+    pub fn new(options: &MiningOptions) -> Result<WorkerPool> {
+        Self::create(options)
+    }
+
+    pub fn size(&self) -> u32 {
+        self.inner.workers.borrow().len() as u32
+    }
+
+    /// 调整 worker 数量：新增的 worker 立即开始加载 wasm 模块，多余的 worker 被终止
+    pub fn resize(&self, num_workers: u32) -> Result<()> {
+        if num_workers == 0 {
+            return Err(Error::InvalidInput("Worker pool needs at least one worker".into()));
+        }
+        if self.inner.busy.get() {
+            return Err(Error::WorkerError("Cannot resize worker pool while mining".into()));
+        }
+
+        let mut workers = self.inner.workers.borrow_mut();
+        while workers.len() > num_workers as usize {
+            if let Some(worker) = workers.pop() {
+                worker.terminate();
+            }
+        }
+        while workers.len() < num_workers as usize {
+            workers.push(self.inner.script.spawn()?);
+        }
+        log!("Worker pool resized to {} workers", num_workers);
+        Ok(())
+    }
+
+    /// 终止所有 worker，之后需要调用 `resize` 才能再次挖矿
+    pub fn terminate(&self) -> Result<()> {
+        if self.inner.busy.get() {
+            return Err(Error::WorkerError("Cannot terminate worker pool while mining".into()));
+        }
+        for worker in self.inner.workers.borrow_mut().drain(..) {
+            worker.terminate();
+        }
+        Ok(())
+    }
+
+    /// 挖矿，返回 Promise
+    pub fn mine(&self, tx_wrapper: WasmTransaction, bitwork_wrapper: WasmBitworkInfo, options: &MiningOptions) -> Promise {
+        self.mine_with_session(tx_wrapper, bitwork_wrapper, options, &MiningSession::new())
+    }
+
+    /// 在给定会话中挖矿，返回 Promise
+    #[wasm_bindgen(js_name = mineWithSession)]
+    pub fn mine_with_session(
+        &self,
+        tx_wrapper: WasmTransaction,
+        bitwork_wrapper: WasmBitworkInfo,
+        options: &MiningOptions,
+        session: &MiningSession,
+    ) -> Promise {
+        let pool = self.clone();
+        let options = options.clone();
+        let session = session.clone();
+        wasm_bindgen_futures::future_to_promise(async move {
+            let tx = tx_wrapper.to_transaction()
+                .ok_or_else(|| Error::DeserializationError("Failed to deserialize transaction".into()))?;
+            let bitwork = bitwork_wrapper.to_bitwork()?;
+            let result = pool.run(&tx, &bitwork, &options, &session).await?;
+            Ok(serde_wasm_bindgen::to_value(&result)?)
+        })
+    }
+}
+
+// 单次挖矿任务中各 worker 共享的状态
+#[derive(Clone)]
+struct JobState {
+    job: u32,
+    result: Rc<RefCell<MiningResult>>,
+    error: Rc<RefCell<Option<String>>>,
+    // 脚本出错、任务结束后需要替换的 worker
+    failed: Rc<RefCell<Vec<u32>>>,
+    workers: Rc<RefCell<Vec<Worker>>>,
+    session: MiningSession,
+    // 跨源隔离时 worker 在哈希循环内检查的共享停止标志，否则只依赖 stop 消息
+    stop_flag: Option<SharedStopFlag>,
+}
+
+impl JobState {
+    fn new(job: u32, session: MiningSession, workers: Rc<RefCell<Vec<Worker>>>) -> Self {
+        Self {
+            job,
+            result: Rc::new(RefCell::new(MiningResult::with_transaction(false, None, None))),
+            error: Rc::new(RefCell::new(None)),
+            failed: Rc::new(RefCell::new(Vec::new())),
+            workers,
+            session,
+            stop_flag: SharedStopFlag::new_if_supported(),
+        }
+    }
+
+    // 通知所有 worker 停止当前任务，worker 本身保持运行
+    fn stop_all(&self) {
+        if let Some(flag) = &self.stop_flag {
+            flag.stop();
+        }
+        if let Ok(stop_msg) = encode_message(WorkerRequest::Stop) {
+            for worker in self.workers.borrow().iter() {
+                let _ = worker.post_message(&stop_msg);
+            }
+        }
+    }
+
+    // 记录第一个错误，挖矿随后以该错误结束
+    fn fail(&self, message: String) {
+        log!("Worker failure: {}", message);
+        self.error.borrow_mut().get_or_insert(message);
+    }
+
+    fn is_finished(&self, timeout_ms: u32) -> bool {
+        self.result.borrow().success
+            || self.error.borrow().is_some()
+            || self.session.all_ranges_done()
+            || self.session.check(timeout_ms).is_err()
+    }
+
+    fn handle(&self, response: WorkerResponse) {
+        match response {
+            WorkerResponse::Progress { worker, scanned, .. } => {
+                self.session.record_scanned(worker, scanned as u64);
+            }
+            WorkerResponse::Found { worker, nonce, tx_hex } => {
+                log!("Worker {} found nonce: {}", worker, nonce);
+                {
+                    let mut result = self.result.borrow_mut();
+                    if !result.success {
+                        result.success = true;
+                        result.nonce = Some(nonce);
+                        result.tx_hex = Some(tx_hex);
+                    }
+                }
+                self.stop_all();
+            }
+            WorkerResponse::Exhausted { worker } => {
+                log!("Worker {} exhausted its nonce range", worker);
+                self.session.finish_range(worker);
+            }
+            WorkerResponse::Stopped { worker } => {
+                log!("Worker {} stopped by shared flag", worker);
+            }
+            WorkerResponse::Error { worker, message } => {
+                self.fail(format!("Worker {:?} reported an error: {}", worker, message));
+            }
+        }
+    }
+}
+
+// 只处理属于当前任务的消息；任务编号为 0 的错误来自尚未解析出任务的 worker
+fn message_handler(state: JobState) -> MessageHandler {
+    Closure::wrap(Box::new(move |e: MessageEvent| {
+        match decode_job_message::<WorkerResponse>(e.data()) {
+            Ok((job, response)) if job == state.job => state.handle(response),
+            Ok((0, response @ WorkerResponse::Error { .. })) => state.handle(response),
+            Ok((job, _)) => log!("Ignoring stale message from job {}", job),
+            Err(e) => state.fail(e.to_string()),
+        }
+    }) as Box<dyn FnMut(MessageEvent)>)
+}
+
+fn error_handler(index: u32, state: JobState) -> ErrorHandler {
+    Closure::wrap(Box::new(move |_e: Event| {
+        state.failed.borrow_mut().push(index);
+        state.fail(format!("Worker {} script error", index));
+    }) as Box<dyn FnMut(Event)>)
+}
+
+// 定期检查任务状态，直到找到结果、全部区间搜索完毕、出错、取消或超时
+async fn wait_until_finished(state: &JobState, timeout_ms: u32) -> Result<()> {
+    let resolve_slot: Rc<RefCell<Option<Function>>> = Rc::new(RefCell::new(None));
+    let interval_id = Rc::new(Cell::new(None::<i32>));
+
+    let check_callback = {
+        let state = state.clone();
+        let resolve_slot = resolve_slot.clone();
+        let interval_id = interval_id.clone();
+        Closure::wrap(Box::new(move || {
+            if !state.is_finished(timeout_ms) {
+                return;
+            }
+            if let Some(id) = interval_id.take() {
+                clear_interval(id);
+            }
+            if let Some(resolve) = resolve_slot.borrow_mut().take() {
+                let _ = resolve.call0(&JsValue::NULL);
+            }
+        }) as Box<dyn FnMut()>)
+    };
+
+    let promise = Promise::new(&mut |resolve, _reject| {
+        *resolve_slot.borrow_mut() = Some(resolve);
+        interval_id.set(Some(set_interval(check_callback.as_ref().unchecked_ref(), CHECK_INTERVAL_MS)));
+    });
+
+    JsFuture::from(promise).await
+        .map_err(|e| Error::AsyncError(format!("Promise error: {:?}", e)))?;
+    Ok(())
+}
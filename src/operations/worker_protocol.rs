@@ -0,0 +1,525 @@
+use crate::errors::{Error, Result};
+use crate::operations::hasher::TxidHasher;
+use crate::types::bitwork::Bitwork;
+use bitcoin::Transaction;
+use js_sys::{Atomics, Int32Array, Reflect};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// 主线程与挖矿 worker 之间的消息协议版本，消息格式变化时递增
+pub const PROTOCOL_VERSION: u32 = 3;
+
+/// 任务消息中携带共享停止标志的属性名，不经过 serde 编码
+const STOP_FLAG_KEY: &str = "stop_flag";
+
+/// 哈希循环中检查共享停止标志的间隔
+const STOP_CHECK_INTERVAL: u64 = 1024;
+
+/// 带协议版本的消息信封
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkerMessage<T> {
+    pub version: u32,
+    /// 消息所属的挖矿任务编号，复用 worker 时用于丢弃上一个任务的残留消息；0 表示未知
+    #[serde(default)]
+    pub job: u32,
+    #[serde(flatten)]
+    pub body: T,
+}
+
+impl<T> WorkerMessage<T> {
+    pub fn new(body: T) -> Self {
+        Self::for_job(0, body)
+    }
+
+    pub fn for_job(job: u32, body: T) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            job,
+            body,
+        }
+    }
+
+    /// 版本不一致时返回错误，而不是按错误的格式继续处理
+    pub fn into_body(self) -> Result<T> {
+        if self.version != PROTOCOL_VERSION {
+            return Err(Error::WorkerError(format!(
+                "Worker protocol version mismatch: expected {}, got {}",
+                PROTOCOL_VERSION, self.version
+            )));
+        }
+        Ok(self.body)
+    }
+}
+
+/// 主线程发给 worker 的消息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkerRequest {
+    /// 在 [start_nonce, end_nonce] 中搜索满足 bitwork 的 sequence
+    Task {
+        worker: u32,
+        tx_hex: String,
+        start_nonce: u32,
+        end_nonce: u32,
+        bitwork: String,
+        batch_size: u32,
+        /// 是否随消息附带了共享停止标志
+        #[serde(default)]
+        shared_stop: bool,
+    },
+    Stop,
+}
+
+/// worker 发回主线程的消息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkerResponse {
+    /// `current` 为下一个待搜索的 nonce
+    Progress { worker: u32, current: u32, scanned: u32 },
+    Found { worker: u32, nonce: u32, tx_hex: String },
+    Exhausted { worker: u32 },
+    /// 共享停止标志已被设置（其他 worker 找到结果或挖矿被取消）
+    Stopped { worker: u32 },
+    Error { worker: Option<u32>, message: String },
+}
+
+/// 解析 JS 消息并检查协议版本
+pub fn decode_message<T: for<'de> Deserialize<'de>>(value: JsValue) -> Result<T> {
+    Ok(decode_job_message(value)?.1)
+}
+
+/// 解析 JS 消息并检查协议版本，同时返回任务编号
+pub fn decode_job_message<T: for<'de> Deserialize<'de>>(value: JsValue) -> Result<(u32, T)> {
+    let message: WorkerMessage<T> = serde_wasm_bindgen::from_value(value)
+        .map_err(|e| Error::WorkerError(format!("Malformed worker message: {}", e)))?;
+    let job = message.job;
+    Ok((job, message.into_body()?))
+}
+
+/// 将消息连同协议版本编码为 JS 对象
+pub fn encode_message<T: Serialize>(body: T) -> Result<JsValue> {
+    encode_job_message(0, body)
+}
+
+/// 将属于某个任务的消息连同协议版本编码为 JS 对象
+pub fn encode_job_message<T: Serialize>(job: u32, body: T) -> Result<JsValue> {
+    // 信封中 flatten 的消息体按 map 编码，默认会得到 ES Map，JS 端无法读取 `type` 等属性
+    const SERIALIZER: serde_wasm_bindgen::Serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    Ok(WorkerMessage::for_job(job, body).serialize(&SERIALIZER)?)
+}
+
+/// 在哈希循环内检查的停止信号
+pub trait StopSignal {
+    fn is_stopped(&self) -> bool;
+
+    /// 以找到结果的身份设置停止标志，只有第一个设置者返回 true
+    fn claim_found(&self, nonce: u32) -> bool;
+}
+
+/// 基于 SharedArrayBuffer 的停止标志，主线程和所有 worker 共享同一块内存
+///
+/// 第 0 个槽位为状态（运行中/已找到/已停止），第 1 个槽位为找到的 nonce。
+#[derive(Clone)]
+pub struct SharedStopFlag {
+    view: Int32Array,
+}
+
+impl SharedStopFlag {
+    const STATE: u32 = 0;
+    const NONCE: u32 = 1;
+    const RUNNING: i32 = 0;
+    const FOUND: i32 = 1;
+    const STOPPED: i32 = 2;
+
+    /// 页面处于跨源隔离且支持 SharedArrayBuffer 时创建，否则返回 None
+    pub fn new_if_supported() -> Option<Self> {
+        let global = js_sys::global();
+        let isolated = Reflect::get(&global, &"crossOriginIsolated".into())
+            .ok()
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let supported = Reflect::has(&global, &"SharedArrayBuffer".into()).unwrap_or(false);
+        if !isolated || !supported {
+            return None;
+        }
+
+        let buffer = js_sys::SharedArrayBuffer::new(8);
+        Some(Self {
+            view: Int32Array::new(&buffer),
+        })
+    }
+
+    /// 主线程停止所有 worker（取消、超时或出错时）
+    pub fn stop(&self) {
+        let _ = Atomics::compare_exchange(&self.view, Self::STATE, Self::RUNNING, Self::STOPPED);
+    }
+
+    /// 已有 worker 找到结果时返回其 nonce
+    pub fn found_nonce(&self) -> Option<u32> {
+        match Atomics::load(&self.view, Self::STATE) {
+            Ok(Self::FOUND) => Atomics::load(&self.view, Self::NONCE).ok().map(|n| n as u32),
+            _ => None,
+        }
+    }
+}
+
+impl StopSignal for SharedStopFlag {
+    fn is_stopped(&self) -> bool {
+        Atomics::load(&self.view, Self::STATE)
+            .map(|state| state != Self::RUNNING)
+            .unwrap_or(false)
+    }
+
+    fn claim_found(&self, nonce: u32) -> bool {
+        match Atomics::compare_exchange(&self.view, Self::STATE, Self::RUNNING, Self::FOUND) {
+            Ok(Self::RUNNING) => {
+                let _ = Atomics::store(&self.view, Self::NONCE, nonce as i32);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// 编码任务消息；有共享停止标志时作为普通对象的属性附加，`postMessage` 的结构化克隆会保留它
+pub fn encode_task_message(job: u32, mut request: WorkerRequest, stop_flag: Option<&SharedStopFlag>) -> Result<JsValue> {
+    if let WorkerRequest::Task { shared_stop, .. } = &mut request {
+        *shared_stop = stop_flag.is_some();
+    }
+
+    let message = encode_job_message(job, request)?;
+    if let Some(flag) = stop_flag {
+        Reflect::set(&message, &STOP_FLAG_KEY.into(), &flag.view)?;
+    }
+    Ok(message)
+}
+
+/// worker 端的任务执行器：按批次搜索区间，每批返回一条响应消息
+#[wasm_bindgen]
+pub struct WorkerTaskRunner {
+    job: u32,
+    worker: u32,
+    tx: Transaction,
+    hasher: TxidHasher,
+    bitwork: Bitwork,
+    start: u32,
+    end: u32,
+    // 下一个待搜索的 nonce，用 u64 避免搜索到 u32::MAX 时溢出
+    next: u64,
+    batch_size: u32,
+    stop_signal: Option<Box<dyn StopSignal>>,
+}
+
+impl WorkerTaskRunner {
+    /// 根据请求创建执行器，`Stop` 请求返回 None
+    pub fn from_request(request: WorkerRequest, stop_signal: Option<Box<dyn StopSignal>>) -> Result<Option<Self>> {
+        let (worker, tx_hex, start_nonce, end_nonce, bitwork, batch_size) = match request {
+            WorkerRequest::Stop => return Ok(None),
+            WorkerRequest::Task { shared_stop, .. } if shared_stop && stop_signal.is_none() => {
+                return Err(Error::WorkerError("Task expects a shared stop flag but none was attached".into()));
+            }
+            WorkerRequest::Task { worker, tx_hex, start_nonce, end_nonce, bitwork, batch_size, .. } => {
+                (worker, tx_hex, start_nonce, end_nonce, bitwork, batch_size)
+            }
+        };
+
+        if start_nonce > end_nonce {
+            return Err(Error::WorkerError(format!(
+                "Invalid nonce range: {} > {}", start_nonce, end_nonce
+            )));
+        }
+
+        let tx: Transaction = bitcoin::consensus::deserialize(&hex::decode(&tx_hex)?)?;
+        let hasher = TxidHasher::new(&tx)
+            .ok_or_else(|| Error::MiningError("Transaction has no inputs to mine".into()))?;
+
+        Ok(Some(Self {
+            job: 0,
+            worker,
+            tx,
+            hasher,
+            bitwork: bitwork.parse()?,
+            start: start_nonce,
+            end: end_nonce,
+            next: start_nonce as u64,
+            batch_size: batch_size.max(1),
+            stop_signal,
+        }))
+    }
+
+    /// 搜索下一批 nonce，返回 progress、found、exhausted 或 stopped
+    pub fn next_response(&mut self) -> WorkerResponse {
+        let batch_end = (self.next + self.batch_size as u64 - 1).min(self.end as u64);
+
+        for nonce in self.next..=batch_end {
+            // 有共享停止标志时在批次内部也检查，不必等到批次结束
+            if let Some(signal) = &self.stop_signal {
+                if (nonce - self.next).is_multiple_of(STOP_CHECK_INTERVAL) && signal.is_stopped() {
+                    return self.stopped();
+                }
+            }
+
+            let nonce = nonce as u32;
+            if self.bitwork.matches_hash(&self.hasher.txid_hash(nonce)) {
+                if let Some(signal) = &self.stop_signal {
+                    if !signal.claim_found(nonce) {
+                        return self.stopped();
+                    }
+                }
+
+                let mut tx = self.tx.clone();
+                tx.input[0].sequence = bitcoin::transaction::Sequence(nonce);
+                self.next = self.end as u64 + 1;
+                return WorkerResponse::Found {
+                    worker: self.worker,
+                    nonce,
+                    tx_hex: hex::encode(bitcoin::consensus::serialize(&tx)),
+                };
+            }
+        }
+
+        self.next = batch_end + 1;
+        if self.next > self.end as u64 {
+            WorkerResponse::Exhausted { worker: self.worker }
+        } else {
+            WorkerResponse::Progress {
+                worker: self.worker,
+                current: self.next as u32,
+                scanned: self.next as u32 - self.start,
+            }
+        }
+    }
+
+    fn stopped(&mut self) -> WorkerResponse {
+        self.next = self.end as u64 + 1;
+        WorkerResponse::Stopped { worker: self.worker }
+    }
+}
+
+#[wasm_bindgen]
+impl WorkerTaskRunner {
+    /// 解析主线程消息；收到 stop 时返回 undefined，格式或版本不符时抛出错误
+    pub fn from_message(message: JsValue) -> Result<Option<WorkerTaskRunner>> {
+        // 共享停止标志不经过 serde，先取出再解析其余字段
+        let stop_flag = Reflect::get(&message, &STOP_FLAG_KEY.into())
+            .ok()
+            .and_then(|value| value.dyn_into::<Int32Array>().ok());
+        if stop_flag.is_some() {
+            Reflect::delete_property(message.unchecked_ref::<js_sys::Object>(), &STOP_FLAG_KEY.into())?;
+        }
+
+        let stop_signal = stop_flag.map(|view| Box::new(SharedStopFlag { view }) as Box<dyn StopSignal>);
+        let (job, request) = decode_job_message(message)?;
+        let runner = Self::from_request(request, stop_signal)?;
+        Ok(runner.map(|runner| Self { job, ..runner }))
+    }
+
+    /// 搜索下一批 nonce，返回可直接 postMessage 的响应对象
+    pub fn next_batch(&mut self) -> Result<JsValue> {
+        encode_job_message(self.job, self.next_response())
+    }
+
+    pub fn worker(&self) -> u32 {
+        self.worker
+    }
+
+    pub fn job(&self) -> u32 {
+        self.job
+    }
+}
+
+/// 构造 worker 错误响应，供 JS 在捕获异常时发回主线程；`job` 未知时传 0
+#[wasm_bindgen]
+pub fn worker_error_message(job: u32, worker: Option<u32>, message: String) -> Result<JsValue> {
+    encode_job_message(job, WorkerResponse::Error { worker, message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::{absolute::LockTime, transaction::Version, Amount, OutPoint, ScriptBuf, Sequence, TxIn, TxOut, Witness};
+
+    fn task(start_nonce: u32, end_nonce: u32, bitwork: &str, batch_size: u32) -> WorkerRequest {
+        let tx = Transaction {
+            version: Version(2),
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ZERO,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut { value: Amount::from_sat(1000), script_pubkey: ScriptBuf::from_bytes(vec![0x51; 34]) }],
+        };
+        WorkerRequest::Task {
+            worker: 3,
+            tx_hex: hex::encode(bitcoin::consensus::serialize(&tx)),
+            start_nonce,
+            end_nonce,
+            bitwork: bitwork.into(),
+            batch_size,
+            shared_stop: false,
+        }
+    }
+
+    // 测试用的进程内停止标志，语义与 SharedStopFlag 相同
+    #[derive(Default)]
+    struct LocalStopFlag {
+        state: std::sync::Arc<std::sync::atomic::AtomicI32>,
+    }
+
+    impl StopSignal for LocalStopFlag {
+        fn is_stopped(&self) -> bool {
+            self.state.load(std::sync::atomic::Ordering::SeqCst) != 0
+        }
+
+        fn claim_found(&self, _nonce: u32) -> bool {
+            self.state
+                .compare_exchange(0, 1, std::sync::atomic::Ordering::SeqCst, std::sync::atomic::Ordering::SeqCst)
+                .is_ok()
+        }
+    }
+
+    #[test]
+    fn test_message_roundtrip() {
+        let json = serde_json::to_value(WorkerMessage::new(WorkerRequest::Stop)).unwrap();
+        assert_eq!(json, serde_json::json!({ "version": PROTOCOL_VERSION, "job": 0, "type": "stop" }));
+
+        let found = WorkerMessage::for_job(5, WorkerResponse::Found { worker: 1, nonce: 7, tx_hex: "00".into() });
+        let json = serde_json::to_string(&found).unwrap();
+        let decoded: WorkerMessage<WorkerResponse> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, found);
+        assert_eq!(decoded.job, 5);
+
+        // JS 在模块加载前构造的错误消息不带任务编号
+        let error = serde_json::json!({ "version": PROTOCOL_VERSION, "type": "error", "worker": null, "message": "boom" });
+        let decoded: WorkerMessage<WorkerResponse> = serde_json::from_value(error).unwrap();
+        assert_eq!(decoded.job, 0);
+    }
+
+    #[test]
+    fn test_rejects_mismatched_messages() {
+        let future = serde_json::json!({ "version": PROTOCOL_VERSION + 1, "type": "stop" });
+        let message: WorkerMessage<WorkerRequest> = serde_json::from_value(future).unwrap();
+        assert!(matches!(message.into_body(), Err(Error::WorkerError(_))));
+
+        // 旧格式的任务消息缺少版本和类型字段
+        let legacy = serde_json::json!({ "tx_wrapper": { "hex": "00" }, "start_nonce": 0, "end_nonce": 1, "bitwork": "00" });
+        assert!(serde_json::from_value::<WorkerMessage<WorkerRequest>>(legacy).is_err());
+    }
+
+    #[test]
+    fn test_runner_reports_progress_then_exhausts() {
+        let mut runner = WorkerTaskRunner::from_request(task(10, 29, &"0".repeat(64), 8), None).unwrap().unwrap();
+
+        assert_eq!(runner.next_response(), WorkerResponse::Progress { worker: 3, current: 18, scanned: 8 });
+        assert_eq!(runner.next_response(), WorkerResponse::Progress { worker: 3, current: 26, scanned: 16 });
+        assert_eq!(runner.next_response(), WorkerResponse::Exhausted { worker: 3 });
+        assert!(WorkerTaskRunner::from_request(WorkerRequest::Stop, None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_runner_finds_nonce_at_end_of_sequence_space() {
+        let mut runner = WorkerTaskRunner::from_request(task(u32::MAX - 300, u32::MAX, "0", 1000), None).unwrap().unwrap();
+
+        match runner.next_response() {
+            WorkerResponse::Found { worker, nonce, tx_hex } => {
+                assert_eq!(worker, 3);
+                let tx: Transaction = bitcoin::consensus::deserialize(&hex::decode(tx_hex).unwrap()).unwrap();
+                assert_eq!(tx.input[0].sequence.0, nonce);
+                assert!(tx.txid().to_string().starts_with('0'));
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_runner_stops_inside_batch_on_shared_flag() {
+        let flag = LocalStopFlag::default();
+        let state = flag.state.clone();
+        let mut runner = WorkerTaskRunner::from_request(task(0, 1_000_000, &"0".repeat(64), 1_000_000), Some(Box::new(flag)))
+            .unwrap()
+            .unwrap();
+
+        state.store(2, std::sync::atomic::Ordering::SeqCst);
+        assert_eq!(runner.next_response(), WorkerResponse::Stopped { worker: 3 });
+        assert_eq!(runner.next_response(), WorkerResponse::Exhausted { worker: 3 });
+    }
+
+    #[test]
+    fn test_runner_yields_when_another_worker_found_first() {
+        // 其他 worker 在本 worker 上次检查标志之后抢先找到了结果
+        struct LostRace;
+
+        impl StopSignal for LostRace {
+            fn is_stopped(&self) -> bool {
+                false
+            }
+
+            fn claim_found(&self, _nonce: u32) -> bool {
+                false
+            }
+        }
+
+        let mut runner = WorkerTaskRunner::from_request(task(0, 300, "0", 1000), Some(Box::new(LostRace))).unwrap().unwrap();
+        assert_eq!(runner.next_response(), WorkerResponse::Stopped { worker: 3 });
+
+        let flag = LocalStopFlag::default();
+        let state = flag.state.clone();
+        let mut runner = WorkerTaskRunner::from_request(task(0, 300, "0", 1000), Some(Box::new(flag))).unwrap().unwrap();
+        assert!(matches!(runner.next_response(), WorkerResponse::Found { .. }));
+        assert_eq!(state.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    mod wasm {
+        use super::*;
+        use wasm_bindgen_test::wasm_bindgen_test;
+
+        #[wasm_bindgen_test]
+        fn test_encoded_message_is_plain_object() {
+            let message = encode_job_message(2, WorkerResponse::Progress { worker: 1, current: 10, scanned: 5 }).unwrap();
+            assert!(!message.is_instance_of::<js_sys::Map>());
+            assert_eq!(Reflect::get(&message, &"type".into()).unwrap().as_string().as_deref(), Some("progress"));
+            assert_eq!(Reflect::get(&message, &"job".into()).unwrap().as_f64(), Some(2.0));
+        }
+
+        // 经 MessageChannel 做一次与 postMessage 相同的结构化克隆
+        async fn post_through_channel(message: &JsValue) -> JsValue {
+            let channel = web_sys::MessageChannel::new().unwrap();
+            let received = js_sys::Promise::new(&mut |resolve, _reject| {
+                let onmessage = Closure::once_into_js(move |event: web_sys::MessageEvent| {
+                    resolve.call1(&JsValue::NULL, &event.data()).unwrap();
+                });
+                channel.port2().set_onmessage(Some(onmessage.unchecked_ref()));
+            });
+            channel.port1().post_message(message).unwrap();
+            let data = wasm_bindgen_futures::JsFuture::from(received).await.unwrap();
+            channel.port1().close();
+            channel.port2().close();
+            data
+        }
+
+        #[wasm_bindgen_test]
+        async fn test_task_keeps_stop_flag_through_post_message() {
+            let flag = SharedStopFlag { view: Int32Array::new(&js_sys::SharedArrayBuffer::new(8)) };
+            let message = encode_task_message(4, task(0, 1_000_000, &"0".repeat(64), 1_000_000), Some(&flag)).unwrap();
+
+            let mut runner = WorkerTaskRunner::from_message(post_through_channel(&message).await).unwrap().unwrap();
+            assert_eq!(runner.job(), 4);
+
+            // 主线程设置的停止标志在接收端可见
+            flag.stop();
+            assert_eq!(runner.next_response(), WorkerResponse::Stopped { worker: 3 });
+        }
+    }
+
+    #[test]
+    fn test_task_requires_attached_flag() {
+        let mut request = task(0, 10, "0", 10);
+        if let WorkerRequest::Task { shared_stop, .. } = &mut request {
+            *shared_stop = true;
+        }
+        assert!(WorkerTaskRunner::from_request(request, None).is_err());
+    }
+}
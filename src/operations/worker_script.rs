@@ -0,0 +1,119 @@
+use crate::errors::{Error, Result};
+use crate::operations::mining::MiningOptions;
+use wasm_bindgen::prelude::*;
+use web_sys::{Blob, BlobPropertyBag, Url, Worker};
+
+/// 随库一同编译的 worker 入口脚本，即 `src/worker_entry.js`；`dist/worker_entry.js` 由 build.sh 构建时从它复制
+pub const WORKER_ENTRY_SOURCE: &str = include_str!("../worker_entry.js");
+
+/// 默认的 worker 脚本文件名，与 wasm-bindgen 生成的 JS 模块放在同一目录
+pub const DEFAULT_WORKER_SCRIPT: &str = "worker_entry.js";
+
+/// wasm-bindgen 生成的 JS 模块文件名
+pub const DEFAULT_MODULE_SCRIPT: &str = "atomicals_rs.js";
+
+#[wasm_bindgen(inline_js = "export function package_url(path) { return new URL('../../' + path, import.meta.url).href; }")]
+extern "C" {
+    // inline_js 片段位于包目录下的 snippets/<crate>/ 中，向上两级即为包目录
+    fn package_url(path: &str) -> String;
+}
+
+/// worker 脚本来源
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorkerScript {
+    /// 从给定 URL 加载脚本，相对地址按页面地址解析
+    Url(String),
+    /// 由库生成 Blob URL，脚本从 `module_url` 加载 wasm 模块
+    Blob { module_url: Option<String> },
+}
+
+impl WorkerScript {
+    pub fn from_options(options: &MiningOptions) -> Self {
+        if options.blob_worker {
+            WorkerScript::Blob { module_url: options.module_url.clone() }
+        } else {
+            WorkerScript::Url(options.worker_url.clone().unwrap_or_else(|| package_url(DEFAULT_WORKER_SCRIPT)))
+        }
+    }
+}
+
+/// Blob worker 的脚本内容：先写入 wasm 模块地址，再执行内置入口脚本
+pub fn worker_bootstrap_source(module_url: &str) -> String {
+    // JSON 字符串同时也是合法的 JS 字符串字面量
+    let module_url = serde_json::to_string(module_url).unwrap_or_else(|_| "null".into());
+    format!("self.ATOMICALS_MODULE_URL = {};\n{}", module_url, WORKER_ENTRY_SOURCE)
+}
+
+/// 一次挖矿中所有 worker 共用的脚本地址，Blob URL 在释放时撤销
+pub(crate) struct WorkerScriptUrl {
+    url: String,
+    is_blob: bool,
+}
+
+impl WorkerScriptUrl {
+    pub(crate) fn create(script: &WorkerScript) -> Result<Self> {
+        match script {
+            WorkerScript::Url(url) => Ok(Self { url: url.clone(), is_blob: false }),
+            WorkerScript::Blob { module_url } => {
+                let module_url = module_url.clone().unwrap_or_else(|| package_url(DEFAULT_MODULE_SCRIPT));
+                let parts = js_sys::Array::of1(&worker_bootstrap_source(&module_url).into());
+                let bag = BlobPropertyBag::new();
+                bag.set_type("text/javascript");
+                let blob = Blob::new_with_str_sequence_and_options(&parts, &bag)
+                    .map_err(|e| Error::WorkerError(format!("Failed to create worker blob: {:?}", e)))?;
+                let url = Url::create_object_url_with_blob(&blob)
+                    .map_err(|e| Error::WorkerError(format!("Failed to create worker blob URL: {:?}", e)))?;
+                Ok(Self { url, is_blob: true })
+            }
+        }
+    }
+
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub(crate) fn spawn(&self) -> Result<Worker> {
+        Worker::new(&self.url)
+            .map_err(|e| Error::WorkerError(format!("Failed to create worker from {}: {:?}", self.url, e)))
+    }
+}
+
+impl Drop for WorkerScriptUrl {
+    fn drop(&mut self) {
+        if self.is_blob {
+            let _ = Url::revoke_object_url(&self.url);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bootstrap_sets_module_url_before_entry() {
+        let source = worker_bootstrap_source("https://cdn.example/pkg/atomicals_rs.js?v=\"1\"");
+        assert!(source.starts_with("self.ATOMICALS_MODULE_URL = \"https://cdn.example/pkg/atomicals_rs.js?v=\\\"1\\\"\";\n"));
+        assert!(source.ends_with(WORKER_ENTRY_SOURCE));
+        assert!(WORKER_ENTRY_SOURCE.contains("self.ATOMICALS_MODULE_URL"));
+    }
+
+    #[test]
+    fn test_explicit_worker_url_is_used() {
+        let options = MiningOptions {
+            worker_url: Some("/app/static/worker_entry.js".into()),
+            ..MiningOptions::new()
+        };
+        assert_eq!(WorkerScript::from_options(&options), WorkerScript::Url("/app/static/worker_entry.js".into()));
+
+        let options = MiningOptions {
+            blob_worker: true,
+            module_url: Some("https://cdn.example/atomicals_rs.js".into()),
+            ..options
+        };
+        assert_eq!(
+            WorkerScript::from_options(&options),
+            WorkerScript::Blob { module_url: Some("https://cdn.example/atomicals_rs.js".into()) }
+        );
+    }
+}
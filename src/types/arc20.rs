@@ -1,4 +1,5 @@
 use super::Amount;
+use super::bitwork::{self, Bitwork};
 use crate::errors::{Error, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -10,6 +11,9 @@ pub struct Arc20Config {
     pub mint_bitworkc: Option<String>,
     pub mint_bitworkr: Option<String>,
     pub meta: HashMap<String, serde_json::Value>,
+    /// 接收铸造结果的地址，默认为钱包主地址
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receive_address: Option<String>,
 }
 
 impl Arc20Config {
@@ -35,6 +39,7 @@ impl Arc20Config {
             mint_bitworkc: None,
             mint_bitworkr: None,
             meta: HashMap::new(),
+            receive_address: None,
         })
     }
 
@@ -63,6 +68,11 @@ impl Arc20Config {
         self
     }
 
+    pub fn with_receive_address(mut self, address: String) -> Self {
+        self.receive_address = Some(address);
+        self
+    }
+
     // Validate ticker format (3-5 lowercase letters/numbers)
     fn is_valid_ticker(tick: &str) -> bool {
         let len = tick.chars().count();
@@ -72,12 +82,17 @@ impl Arc20Config {
         tick.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
     }
 
-    // Validate bitwork format (hex string)
+    // Validate bitwork format (`prefix` or `prefix.N`)
     fn is_valid_bitwork(bitwork: &str) -> bool {
-        if bitwork.is_empty() {
-            return false;
-        }
-        bitwork.chars().all(|c| c.is_ascii_hexdigit())
+        bitwork.parse::<Bitwork>().is_ok()
+    }
+
+    /// 解析 commit/reveal 的 bitwork 要求
+    pub fn bitworks(&self) -> Result<(Option<Bitwork>, Option<Bitwork>)> {
+        Ok((
+            bitwork::parse_optional(self.mint_bitworkc.as_deref())?,
+            bitwork::parse_optional(self.mint_bitworkr.as_deref())?,
+        ))
     }
 
     // Helper methods
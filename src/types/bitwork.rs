@@ -0,0 +1,245 @@
+use crate::errors::{Error, Result};
+use crate::operations::hasher::{hash_has_prefix, txid_nibble};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Atomicals 协议的 bitwork 要求
+///
+/// 格式为 `prefix` 或 `prefix.N`：txid 必须以 `prefix` 开头；
+/// 带扩展 `N`（0-15）时，紧随前缀的下一个十六进制字符的值还必须不小于 `N`。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Bitwork {
+    prefix: String,
+    ext: Option<u8>,
+    nibbles: Vec<u8>,
+}
+
+impl Bitwork {
+    /// 前缀最大长度（txid 共 64 个十六进制字符）
+    pub const MAX_PREFIX_LEN: usize = 64;
+
+    pub fn new(prefix: &str, ext: Option<u8>) -> Result<Self> {
+        if prefix.is_empty() || prefix.len() > Self::MAX_PREFIX_LEN {
+            return Err(Error::InvalidBitwork(format!(
+                "Bitwork prefix must be 1-{} characters: {}", Self::MAX_PREFIX_LEN, prefix
+            )));
+        }
+
+        // 索引器只接受小写十六进制
+        if !prefix.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')) {
+            return Err(Error::InvalidBitwork(format!(
+                "Bitwork prefix must be lowercase hex: {}", prefix
+            )));
+        }
+
+        if let Some(ext) = ext {
+            if ext > 15 {
+                return Err(Error::InvalidBitwork(format!(
+                    "Bitwork extension must be between 0 and 15: {}", ext
+                )));
+            }
+            // 扩展作用于前缀之后的字符，因此前缀不能占满整个 txid
+            if prefix.len() == Self::MAX_PREFIX_LEN {
+                return Err(Error::InvalidBitwork(
+                    "Bitwork extension requires a prefix shorter than 64 characters".into(),
+                ));
+            }
+        }
+
+        let nibbles = prefix
+            .chars()
+            .filter_map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect();
+
+        Ok(Self {
+            prefix: prefix.to_string(),
+            ext,
+            nibbles,
+        })
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn ext(&self) -> Option<u8> {
+        self.ext
+    }
+
+    /// 前缀对应的半字节序列
+    pub fn nibbles(&self) -> &[u8] {
+        &self.nibbles
+    }
+
+    /// 单次哈希满足要求的概率：前缀每个字符 1/16，扩展 `N` 再乘以 (16-N)/16
+    pub fn success_probability(&self) -> f64 {
+        let ext_factor = match self.ext {
+            Some(ext) => (16 - ext) as f64 / 16.0,
+            None => 1.0,
+        };
+        16f64.powi(-(self.prefix.len() as i32)) * ext_factor
+    }
+
+    /// 找到满足要求的 txid 平均需要的哈希次数
+    pub fn expected_hashes(&self) -> f64 {
+        1.0 / self.success_probability()
+    }
+
+    /// 以比特计的难度，包含扩展带来的小数部分
+    pub fn difficulty_bits(&self) -> f64 {
+        self.expected_hashes().log2()
+    }
+
+    /// 检查十六进制 txid 是否满足要求
+    pub fn matches_txid(&self, txid: &str) -> bool {
+        if !txid.starts_with(&self.prefix) {
+            return false;
+        }
+
+        match self.ext {
+            Some(ext) => txid[self.prefix.len()..]
+                .chars()
+                .next()
+                .and_then(|c| c.to_digit(16))
+                .is_some_and(|value| value >= ext as u32),
+            None => true,
+        }
+    }
+
+    /// 检查原始 txid 哈希（内部字节序）是否满足要求，挖矿时使用
+    #[inline]
+    pub fn matches_hash(&self, hash: &[u8; 32]) -> bool {
+        if !hash_has_prefix(hash, &self.nibbles) {
+            return false;
+        }
+
+        match self.ext {
+            Some(ext) => txid_nibble(hash, self.nibbles.len()) >= ext,
+            None => true,
+        }
+    }
+}
+
+impl FromStr for Bitwork {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once('.') {
+            None => Self::new(s, None),
+            Some((prefix, ext)) => {
+                let ext = ext.parse::<u8>().map_err(|_| {
+                    Error::InvalidBitwork(format!("Invalid bitwork extension: {}", s))
+                })?;
+                Self::new(prefix, Some(ext))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Bitwork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.ext {
+            Some(ext) => write!(f, "{}.{}", self.prefix, ext),
+            None => write!(f, "{}", self.prefix),
+        }
+    }
+}
+
+impl TryFrom<String> for Bitwork {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Bitwork> for String {
+    fn from(bitwork: Bitwork) -> Self {
+        bitwork.to_string()
+    }
+}
+
+/// 解析可选的 bitwork 字符串
+pub fn parse_optional(bitwork: Option<&str>) -> Result<Option<Bitwork>> {
+    bitwork.map(Bitwork::from_str).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bitwork() {
+        let bitwork: Bitwork = "abc".parse().unwrap();
+        assert_eq!(bitwork.prefix(), "abc");
+        assert_eq!(bitwork.ext(), None);
+        assert_eq!(bitwork.to_string(), "abc");
+
+        let bitwork: Bitwork = "0000.7".parse().unwrap();
+        assert_eq!(bitwork.prefix(), "0000");
+        assert_eq!(bitwork.ext(), Some(7));
+        assert_eq!(bitwork.to_string(), "0000.7");
+
+        assert!("0000.0".parse::<Bitwork>().is_ok());
+        assert!("0000.15".parse::<Bitwork>().is_ok());
+    }
+
+    #[test]
+    fn test_parse_invalid_bitwork() {
+        for input in ["", "ABC", "xyz", "00:ab", "0000.16", "0000.", "0000.a", ".5", "00.1.2"] {
+            assert!(input.parse::<Bitwork>().is_err(), "{} should be rejected", input);
+        }
+        assert!("0".repeat(65).parse::<Bitwork>().is_err());
+        assert!(format!("{}.1", "0".repeat(64)).parse::<Bitwork>().is_err());
+    }
+
+    #[test]
+    fn test_matches_txid() {
+        let txid = "abc7f00000000000000000000000000000000000000000000000000000000000";
+
+        assert!("abc".parse::<Bitwork>().unwrap().matches_txid(txid));
+        assert!(!"abd".parse::<Bitwork>().unwrap().matches_txid(txid));
+        assert!("abc.7".parse::<Bitwork>().unwrap().matches_txid(txid));
+        assert!("abc.0".parse::<Bitwork>().unwrap().matches_txid(txid));
+        assert!(!"abc.8".parse::<Bitwork>().unwrap().matches_txid(txid));
+        assert!("abc7.15".parse::<Bitwork>().unwrap().matches_txid(txid));
+    }
+
+    #[test]
+    fn test_matches_hash_agrees_with_txid() {
+        // 显示形式为 "abc7f000...0" 的 txid 对应的内部字节序哈希
+        let mut hash = [0u8; 32];
+        hash[31] = 0xab;
+        hash[30] = 0xc7;
+        hash[29] = 0xf0;
+        let txid = "abc7f00000000000000000000000000000000000000000000000000000000000";
+
+        for input in ["a", "abc", "abc7", "abd", "abc.6", "abc.7", "abc.8", "abc7.15", "abc7f.0", "abc7f.1"] {
+            let bitwork: Bitwork = input.parse().unwrap();
+            assert_eq!(bitwork.matches_hash(&hash), bitwork.matches_txid(txid), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_expected_hashes() {
+        assert_eq!("0".parse::<Bitwork>().unwrap().expected_hashes(), 16.0);
+        assert_eq!("0000".parse::<Bitwork>().unwrap().expected_hashes(), 65536.0);
+        assert_eq!("0000.0".parse::<Bitwork>().unwrap().expected_hashes(), 65536.0);
+        // 扩展 8 只接受 8-f，概率减半
+        assert_eq!("0000.8".parse::<Bitwork>().unwrap().expected_hashes(), 131072.0);
+        assert_eq!("00.15".parse::<Bitwork>().unwrap().expected_hashes(), 4096.0);
+        assert_eq!("0000".parse::<Bitwork>().unwrap().difficulty_bits(), 16.0);
+        assert_eq!("0000.8".parse::<Bitwork>().unwrap().difficulty_bits(), 17.0);
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let bitwork: Bitwork = "1234.5".parse().unwrap();
+        let json = serde_json::to_string(&bitwork).unwrap();
+        assert_eq!(json, "\"1234.5\"");
+        assert_eq!(serde_json::from_str::<Bitwork>(&json).unwrap(), bitwork);
+        assert!(serde_json::from_str::<Bitwork>("\"1234.99\"").is_err());
+    }
+}
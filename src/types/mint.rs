@@ -1,7 +1,19 @@
 use super::{Arc20Config, Arc20Token};
 use bitcoin::Transaction;
+use super::bitwork::Bitwork;
+use crate::errors::Result;
 use serde::{Deserialize, Serialize};
 
+#[cfg(target_arch = "wasm32")]
+macro_rules! log {
+    ($($t:tt)*) => (web_sys::console::log_1(&format!($($t)*).into()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+macro_rules! log {
+    ($($t:tt)*) => (log::info!($($t)*))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MintConfig {
     pub arc20: Arc20Config,
@@ -19,90 +31,58 @@ pub struct MintResult {
 #[derive(Debug, Clone)]
 pub struct BitworkInfo {
     pub prefix: String,
-    pub ext: Option<String>,
-    pub difficulty: u32,
+    pub ext: Option<u8>,
+    /// 以比特计的难度（log2 期望哈希次数），包含扩展的贡献
+    pub difficulty: f64,
 }
 
 impl BitworkInfo {
+    /// 宽松解析：输入无效时回退到默认的 "0000"，需要报错时使用 [`BitworkInfo::parse`]
     pub fn new(input: String) -> Self {
-        web_sys::console::log_1(&format!("Converting bitwork input: {}", input).into());
-        
-        // 如果输入为空，使用默认值
-        if input.is_empty() {
-            return Self {
-                prefix: "0000".to_string(),
-                ext: None,
-                difficulty: 16,
-            };
-        }
+        log!("Converting bitwork input: {}", input);
 
-        // 解析输入字符串，支持扩展要求（例如 "aabbcc:ext"）
-        let parts: Vec<&str> = input.split(':').collect();
-        let (prefix, ext) = match parts.len() {
-            1 => (parts[0].to_string(), None),
-            2 => (parts[0].to_string(), Some(parts[1].to_string())),
-            _ => {
-                web_sys::console::log_1(&"Invalid bitwork format, using default values".into());
-                return Self {
-                    prefix: "0000".to_string(),
-                    ext: None,
-                    difficulty: 16,
-                };
-            }
-        };
-
-        // 验证前缀是否有效（仅包含十六进制字符）
-        if !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
-            web_sys::console::log_1(&"Invalid prefix, using default values".into());
-            return Self {
-                prefix: "0000".to_string(),
-                ext: None,
-                difficulty: 16,
-            };
-        }
+        Self::parse(&input).unwrap_or_else(|e| {
+            log!("{}, using default values", e);
+            Self::from_bitwork(&Bitwork::new("0000", None).expect("default bitwork is valid"))
+        })
+    }
 
-        // 计算难度（基于前缀长度）
-        let difficulty = prefix.len() as u32 * 4;
+    /// 按协议格式 `prefix` 或 `prefix.N` 解析
+    pub fn parse(input: &str) -> Result<Self> {
+        let bitwork: Bitwork = input.parse()?;
+        let info = Self::from_bitwork(&bitwork);
 
-        web_sys::console::log_1(&format!(
+        log!(
             "Created BitworkInfo with prefix: {}, ext: {:?}, difficulty: {}",
-            prefix, ext, difficulty
-        ).into());
+            info.prefix, info.ext, info.difficulty
+        );
+
+        Ok(info)
+    }
 
+    pub fn from_bitwork(bitwork: &Bitwork) -> Self {
         Self {
-            prefix,
-            ext,
-            difficulty,
+            prefix: bitwork.prefix().to_string(),
+            ext: bitwork.ext(),
+            difficulty: bitwork.difficulty_bits(),
         }
     }
 
-    pub fn with_ext(mut self, ext: String) -> Self {
+    pub fn to_bitwork(&self) -> Result<Bitwork> {
+        Bitwork::new(&self.prefix, self.ext)
+    }
+
+    pub fn with_ext(mut self, ext: u8) -> Self {
         self.ext = Some(ext);
+        if let Ok(bitwork) = self.to_bitwork() {
+            self.difficulty = bitwork.difficulty_bits();
+        }
         self
     }
 
     pub fn matches(&self, txid: &str) -> bool {
-        // 检查txid是否以指定前缀开头
-        let required_prefix_len = self.prefix.len();
-        if txid.len() < required_prefix_len {
-            return false;
-        }
-
-        if !txid.starts_with(&self.prefix) {
-            return false;
-        }
-
-        // 检查扩展要求（如果有）
-        if let Some(ext) = &self.ext {
-            let ext_pos = required_prefix_len;
-            if ext_pos + ext.len() > txid.len() {
-                return false;
-            }
-            if &txid[ext_pos..ext_pos + ext.len()] != ext {
-                return false;
-            }
-        }
-
-        true
+        self.to_bitwork()
+            .map(|bitwork| bitwork.matches_txid(txid))
+            .unwrap_or(false)
     }
-}
\ No newline at end of file
+}
@@ -1,6 +1,8 @@
 pub mod amount;
 pub mod arc20;
+pub mod bitwork;
 pub mod mint;
+pub mod network;
 pub mod atomicals;
 pub mod wasm;
 pub mod transaction;
@@ -9,7 +11,9 @@ pub mod subrealm;
 
 pub use amount::Amount;
 pub use arc20::{Arc20Config, Arc20Token};
+pub use bitwork::Bitwork;
 pub use mint::{MintConfig, MintResult};
+pub use network::AtomicalsNetwork;
 pub use atomicals::*;
 pub use wasm::*;
 pub use transaction::AtomicalsTx;
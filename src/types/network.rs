@@ -0,0 +1,139 @@
+use crate::errors::{Error, Result};
+use bitcoin::{Address, Network};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+/// Atomicals 操作支持的网络
+///
+/// testnet3 和 testnet4 的地址编码相同（都对应 `bitcoin::Network::Testnet`），
+/// 区别只在 ElectrumX 代理和费率来源。从 `bitcoin::Network::Testnet` 转换时按 testnet4 处理。
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AtomicalsNetwork {
+    Mainnet,
+    Testnet,
+    Testnet4,
+    Signet,
+    Regtest,
+}
+
+impl AtomicalsNetwork {
+    /// 地址编码和 PSBT 使用的网络
+    pub fn bitcoin_network(self) -> Network {
+        match self {
+            AtomicalsNetwork::Mainnet => Network::Bitcoin,
+            AtomicalsNetwork::Testnet | AtomicalsNetwork::Testnet4 => Network::Testnet,
+            AtomicalsNetwork::Signet => Network::Signet,
+            AtomicalsNetwork::Regtest => Network::Regtest,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AtomicalsNetwork::Mainnet => "mainnet",
+            AtomicalsNetwork::Testnet => "testnet",
+            AtomicalsNetwork::Testnet4 => "testnet4",
+            AtomicalsNetwork::Signet => "signet",
+            AtomicalsNetwork::Regtest => "regtest",
+        }
+    }
+
+    /// 公共 Atomicals ElectrumX 代理，没有公共索引器的网络返回 None
+    pub fn electrumx_url(self) -> Option<&'static str> {
+        match self {
+            AtomicalsNetwork::Mainnet => Some("https://ep.wizz.cash/proxy"),
+            AtomicalsNetwork::Testnet4 => Some("https://eptestnet4.wizz.cash/proxy"),
+            _ => None,
+        }
+    }
+
+    /// mempool.space 费率接口，regtest 没有公共服务，由节点估算
+    pub fn mempool_url(self) -> Option<&'static str> {
+        match self {
+            AtomicalsNetwork::Mainnet => Some("https://mempool.space/api"),
+            AtomicalsNetwork::Testnet => Some("https://mempool.space/testnet/api"),
+            AtomicalsNetwork::Testnet4 => Some("https://mempool.space/testnet4/api"),
+            AtomicalsNetwork::Signet => Some("https://mempool.space/signet/api"),
+            AtomicalsNetwork::Regtest => None,
+        }
+    }
+
+    /// 解析地址并检查它属于本网络
+    pub fn parse_address(self, address: &str) -> Result<Address> {
+        Address::from_str(address)
+            .map_err(|e| Error::AddressError(format!("Invalid address {}: {}", address, e)))?
+            .require_network(self.bitcoin_network())
+            .map_err(|e| Error::NetworkError(format!("Address {} is not a {} address: {}", address, self, e)))
+    }
+}
+
+impl From<Network> for AtomicalsNetwork {
+    fn from(network: Network) -> Self {
+        match network {
+            Network::Bitcoin => AtomicalsNetwork::Mainnet,
+            Network::Testnet => AtomicalsNetwork::Testnet4,
+            Network::Signet => AtomicalsNetwork::Signet,
+            _ => AtomicalsNetwork::Regtest,
+        }
+    }
+}
+
+impl fmt::Display for AtomicalsNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 接受本库的名称、钱包扩展使用的 `livenet` 以及 `BITCOIN_TESTNET4` 形式的链名
+impl FromStr for AtomicalsNetwork {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().to_ascii_lowercase();
+        match name.strip_prefix("bitcoin_").unwrap_or(&name) {
+            "mainnet" | "livenet" | "bitcoin" | "main" => Ok(AtomicalsNetwork::Mainnet),
+            "testnet" | "testnet3" => Ok(AtomicalsNetwork::Testnet),
+            "testnet4" => Ok(AtomicalsNetwork::Testnet4),
+            "signet" => Ok(AtomicalsNetwork::Signet),
+            "regtest" => Ok(AtomicalsNetwork::Regtest),
+            _ => Err(Error::NetworkError(format!("Unsupported network: {}", s))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_network_names() {
+        assert_eq!("livenet".parse::<AtomicalsNetwork>().unwrap(), AtomicalsNetwork::Mainnet);
+        assert_eq!("BITCOIN_TESTNET4".parse::<AtomicalsNetwork>().unwrap(), AtomicalsNetwork::Testnet4);
+        assert_eq!(" Signet ".parse::<AtomicalsNetwork>().unwrap(), AtomicalsNetwork::Signet);
+        assert!("fractal".parse::<AtomicalsNetwork>().is_err());
+
+        for network in [AtomicalsNetwork::Mainnet, AtomicalsNetwork::Testnet, AtomicalsNetwork::Testnet4, AtomicalsNetwork::Signet, AtomicalsNetwork::Regtest] {
+            assert_eq!(network.to_string().parse::<AtomicalsNetwork>().unwrap(), network);
+        }
+    }
+
+    #[test]
+    fn test_bitcoin_network_mapping() {
+        assert_eq!(AtomicalsNetwork::Testnet4.bitcoin_network(), Network::Testnet);
+        assert_eq!(AtomicalsNetwork::from(Network::Testnet), AtomicalsNetwork::Testnet4);
+        assert_eq!(AtomicalsNetwork::from(Network::Regtest), AtomicalsNetwork::Regtest);
+        assert!(AtomicalsNetwork::Regtest.electrumx_url().is_none());
+        assert!(AtomicalsNetwork::Regtest.mempool_url().is_none());
+    }
+
+    #[test]
+    fn test_parse_address_checks_network() {
+        let mainnet = "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr";
+        assert!(AtomicalsNetwork::Mainnet.parse_address(mainnet).is_ok());
+        assert!(matches!(AtomicalsNetwork::Testnet4.parse_address(mainnet), Err(Error::NetworkError(_))));
+        assert!(matches!(AtomicalsNetwork::Mainnet.parse_address("not-an-address"), Err(Error::AddressError(_))));
+    }
+}
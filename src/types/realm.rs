@@ -1,5 +1,7 @@
 use serde::{Serialize, Deserialize};
 use bitcoin::Amount;
+use super::bitwork::{self, Bitwork};
+use crate::errors;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RealmConfig {
@@ -17,6 +19,9 @@ pub struct RealmConfig {
     pub parent_owner: Option<String>,
     /// 输出金额（聪）
     pub sats_output: u64,
+    /// 接收 Realm 的地址，默认为钱包主地址
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receive_address: Option<String>,
 }
 
 impl RealmConfig {
@@ -29,6 +34,7 @@ impl RealmConfig {
             parent: None,
             parent_owner: None,
             sats_output: 546, // 最小粉尘限额
+            receive_address: None,
         }
     }
 
@@ -58,15 +64,25 @@ impl RealmConfig {
     }
 
     /// 设置commit交易的工作量证明
-    pub fn with_bitworkc(mut self, bitworkc: String) -> Self {
+    pub fn with_bitworkc(mut self, bitworkc: String) -> errors::Result<Self> {
+        bitworkc.parse::<Bitwork>()?;
         self.bitworkc = Some(bitworkc);
-        self
+        Ok(self)
     }
 
     /// 设置reveal交易的工作量证明
-    pub fn with_bitworkr(mut self, bitworkr: String) -> Self {
+    pub fn with_bitworkr(mut self, bitworkr: String) -> errors::Result<Self> {
+        bitworkr.parse::<Bitwork>()?;
         self.bitworkr = Some(bitworkr);
-        self
+        Ok(self)
+    }
+
+    /// 解析 commit/reveal 的工作量证明要求
+    pub fn bitworks(&self) -> errors::Result<(Option<Bitwork>, Option<Bitwork>)> {
+        Ok((
+            bitwork::parse_optional(self.bitworkc.as_deref())?,
+            bitwork::parse_optional(self.bitworkr.as_deref())?,
+        ))
     }
 
     /// 设置容器
@@ -87,4 +103,10 @@ impl RealmConfig {
         self.sats_output = sats;
         self
     }
+
+    /// 设置接收地址
+    pub fn with_receive_address(mut self, address: String) -> Self {
+        self.receive_address = Some(address);
+        self
+    }
 }
@@ -1,6 +1,8 @@
 use serde::{Serialize, Deserialize};
 use bitcoin::Amount;
 use bitcoin::ScriptBuf;
+use super::bitwork::{self, Bitwork};
+use crate::errors;
 
 /// Subrealm 铸造类型
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -73,6 +75,9 @@ pub struct SubrealmConfig {
     /// 规则铸造的支付输出
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rule_outputs: Option<Vec<(ScriptBuf, RuleOutput)>>,
+    /// 接收 Subrealm 的地址，默认为钱包主地址
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receive_address: Option<String>,
 }
 
 impl SubrealmConfig {
@@ -90,6 +95,7 @@ impl SubrealmConfig {
             init: None,
             fee_rate: None,
             rule_outputs: None,
+            receive_address: None,
         }
     }
 
@@ -129,15 +135,25 @@ impl SubrealmConfig {
     }
 
     /// 设置 commit 交易的工作量证明
-    pub fn with_bitworkc(mut self, bitworkc: String) -> Self {
+    pub fn with_bitworkc(mut self, bitworkc: String) -> errors::Result<Self> {
+        bitworkc.parse::<Bitwork>()?;
         self.bitworkc = Some(bitworkc);
-        self
+        Ok(self)
     }
 
     /// 设置 reveal 交易的工作量证明
-    pub fn with_bitworkr(mut self, bitworkr: String) -> Self {
+    pub fn with_bitworkr(mut self, bitworkr: String) -> errors::Result<Self> {
+        bitworkr.parse::<Bitwork>()?;
         self.bitworkr = Some(bitworkr);
-        self
+        Ok(self)
+    }
+
+    /// 解析 commit/reveal 的工作量证明要求
+    pub fn bitworks(&self) -> errors::Result<(Option<Bitwork>, Option<Bitwork>)> {
+        Ok((
+            bitwork::parse_optional(self.bitworkc.as_deref())?,
+            bitwork::parse_optional(self.bitworkr.as_deref())?,
+        ))
     }
 
     /// 设置容器
@@ -175,4 +191,10 @@ impl SubrealmConfig {
         self.fee_rate = Some(fee_rate);
         self
     }
+
+    /// 设置接收地址
+    pub fn with_receive_address(mut self, address: String) -> Self {
+        self.receive_address = Some(address);
+        self
+    }
 }
@@ -2,6 +2,7 @@ use wasm_bindgen::prelude::*;
 use bitcoin::Transaction;
 use serde::{Serialize, Deserialize};
 use crate::types::mint::BitworkInfo;
+use crate::types::bitwork::Bitwork;
 use bitcoin::consensus;
 use hex;
 use wasm_bindgen::JsValue;
@@ -62,7 +63,7 @@ impl WasmTransaction {
 pub struct WasmBitworkInfo {
     difficulty: String,
     prefix: String,
-    ext: Option<String>,
+    ext: Option<u8>,
 }
 
 #[wasm_bindgen]
@@ -76,6 +77,13 @@ impl WasmBitworkInfo {
         }
     }
 
+    /// 按协议格式 `prefix` 或 `prefix.N` 解析
+    #[wasm_bindgen]
+    pub fn parse(bitwork: &str) -> Result<WasmBitworkInfo, JsValue> {
+        let bitwork: Bitwork = bitwork.parse()?;
+        Ok(Self::from_bitwork_info(&BitworkInfo::from_bitwork(&bitwork)))
+    }
+
     #[wasm_bindgen]
     pub fn get_difficulty(&self) -> String {
         self.difficulty.clone()
@@ -87,30 +95,44 @@ impl WasmBitworkInfo {
     }
 
     #[wasm_bindgen]
-    pub fn get_ext(&self) -> Option<String> {
-        self.ext.clone()
+    pub fn get_ext(&self) -> Option<u8> {
+        self.ext
     }
 
     #[wasm_bindgen]
-    pub fn set_ext(&mut self, ext: Option<String>) {
+    pub fn set_ext(&mut self, ext: Option<u8>) {
         self.ext = ext;
     }
+
+    /// 返回 `prefix` 或 `prefix.N` 形式的 bitwork 字符串
+    #[wasm_bindgen]
+    pub fn to_bitwork_string(&self) -> Result<String, JsValue> {
+        Ok(self.to_bitwork()?.to_string())
+    }
 }
 
 impl WasmBitworkInfo {
     pub fn to_bitwork_info(&self) -> BitworkInfo {
-        BitworkInfo {
-            difficulty: self.difficulty.parse().unwrap_or(16),
-            prefix: self.prefix.clone(),
-            ext: self.ext.clone(),
+        // 有效的 bitwork 按前缀和扩展重新计算难度
+        match self.to_bitwork() {
+            Ok(bitwork) => BitworkInfo::from_bitwork(&bitwork),
+            Err(_) => BitworkInfo {
+                difficulty: self.difficulty.parse().unwrap_or(16.0),
+                prefix: self.prefix.clone(),
+                ext: self.ext,
+            },
         }
     }
 
+    pub fn to_bitwork(&self) -> crate::errors::Result<Bitwork> {
+        Bitwork::new(&self.prefix, self.ext)
+    }
+
     pub fn from_bitwork_info(bitwork: &BitworkInfo) -> Self {
         Self {
             difficulty: bitwork.difficulty.to_string(),
             prefix: bitwork.prefix.clone(),
-            ext: bitwork.ext.clone(),
+            ext: bitwork.ext,
         }
     }
 }
@@ -212,7 +234,9 @@ impl WasmRealmConfig {
     pub fn validate(&self) -> Result<(), JsValue> {
         let realm_config: RealmConfig = RealmConfig::from(self.clone());
         realm_config.validate_name()
-            .map_err(|e| JsValue::from_str(&format!("Invalid realm name: {}", e)))
+            .map_err(|e| JsValue::from_str(&format!("Invalid realm name: {}", e)))?;
+        realm_config.bitworks()?;
+        Ok(())
     }
 }
 impl From<WasmRealmConfig> for RealmConfig {
@@ -225,6 +249,7 @@ impl From<WasmRealmConfig> for RealmConfig {
             parent: config.parent,
             parent_owner: config.parent_owner,
             sats_output: config.sats_output,
+            receive_address: None,
         }
     }
 }
@@ -0,0 +1,272 @@
+use crate::errors::{Error, Result};
+use crate::utils::tx_size::{input_vsize, output_vsize, ScriptType};
+use crate::wallet::Utxo;
+use bitcoin::Amount;
+
+/// 找零低于此值时不创建找零输出，并入手续费
+pub const DUST_LIMIT: Amount = Amount::from_sat(546);
+
+/// 分支定界搜索的最大尝试次数，超过后使用已找到的最优解或改用回退策略
+const BNB_MAX_TRIES: usize = 100_000;
+
+/// 选币参数
+#[derive(Debug, Clone, Copy)]
+pub struct CoinSelectionParams {
+    /// sat/vB
+    pub fee_rate: f64,
+    /// 不含资金输入和找零输出的交易 vsize，即基础开销和固定输出
+    pub base_vsize: f64,
+    /// 找零输出的脚本类型
+    pub change_type: ScriptType,
+    pub dust_limit: Amount,
+}
+
+impl CoinSelectionParams {
+    pub fn new(fee_rate: f64, base_vsize: f64, change_type: ScriptType) -> Self {
+        Self { fee_rate, base_vsize, change_type, dust_limit: DUST_LIMIT }
+    }
+
+    pub fn with_dust_limit(mut self, dust_limit: Amount) -> Self {
+        self.dust_limit = dust_limit;
+        self
+    }
+
+    fn fee(&self, vsize: f64) -> u64 {
+        (vsize * self.fee_rate).ceil() as u64
+    }
+
+    // 找零的代价：创建找零输出，以及以后花费它的输入
+    fn cost_of_change(&self) -> u64 {
+        self.fee(output_vsize(self.change_type) + input_vsize(self.change_type))
+    }
+}
+
+/// 选币结果
+#[derive(Debug, Clone)]
+pub struct CoinSelection {
+    pub selected: Vec<Utxo>,
+    pub fee: Amount,
+    /// None 表示不创建找零输出，多出的金额计入手续费
+    pub change: Option<Amount>,
+}
+
+impl CoinSelection {
+    pub fn total_input(&self) -> Amount {
+        self.selected.iter().map(|utxo| utxo.txout.value).sum()
+    }
+}
+
+struct Candidate<'a> {
+    utxo: &'a Utxo,
+    vsize: f64,
+    /// 扣除花费该输入所需手续费后的金额
+    effective: u64,
+}
+
+/// 选择支付 `target` 和手续费的输入
+///
+/// 先用分支定界寻找不需要找零的组合（多出的金额不超过找零的代价），找不到时按有效金额从大到小选择并找零。
+/// 找零低于 `dust_limit` 时并入手续费。无法识别脚本类型或不足以支付自身手续费的 UTXO 不参与选择。
+pub fn select_coins(utxos: &[Utxo], target: Amount, params: &CoinSelectionParams) -> Result<CoinSelection> {
+    let mut candidates: Vec<Candidate> = utxos.iter()
+        .filter_map(|utxo| {
+            let vsize = input_vsize(ScriptType::from_script(&utxo.txout.script_pubkey)?);
+            let effective = utxo.txout.value.to_sat().checked_sub(params.fee(vsize))?;
+            (effective > 0).then_some(Candidate { utxo, vsize, effective })
+        })
+        .collect();
+    // 有效金额相同时保持钱包给出的顺序
+    candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.effective));
+
+    let target_sat = target.to_sat();
+    let target_with_base = target_sat + params.fee(params.base_vsize);
+    if let Some(indices) = branch_and_bound(&candidates, target_with_base, params.cost_of_change()) {
+        if let Some(selection) = finish(&candidates, &indices, target_sat, params, false) {
+            return Ok(selection);
+        }
+    }
+
+    let goal = target_with_base + params.fee(output_vsize(params.change_type)) + params.dust_limit.to_sat();
+    let indices = largest_first(&candidates, goal);
+    finish(&candidates, &indices, target_sat, params, true).ok_or_else(|| {
+        let available: Amount = utxos.iter().map(|utxo| utxo.txout.value).sum();
+        Error::InvalidAmount(format!(
+            "Insufficient funds: need {} sats plus fees, {} sats available in {} UTXOs",
+            target_sat, available.to_sat(), utxos.len()
+        ))
+    })
+}
+
+// 按选中的输入计算实际手续费和找零
+fn finish(candidates: &[Candidate], indices: &[usize], target: u64, params: &CoinSelectionParams, allow_change: bool) -> Option<CoinSelection> {
+    let total: u64 = indices.iter().map(|&i| candidates[i].utxo.txout.value.to_sat()).sum();
+    let vsize = params.base_vsize + indices.iter().map(|&i| candidates[i].vsize).sum::<f64>();
+    let selected = || indices.iter().map(|&i| candidates[i].utxo.clone()).collect();
+
+    if allow_change {
+        let fee = params.fee(vsize + output_vsize(params.change_type));
+        if let Some(change) = total.checked_sub(target + fee).filter(|change| *change >= params.dust_limit.to_sat()) {
+            return Some(CoinSelection { selected: selected(), fee: Amount::from_sat(fee), change: Some(Amount::from_sat(change)) });
+        }
+    }
+
+    let fee = params.fee(vsize);
+    (total >= target + fee).then(|| CoinSelection {
+        selected: selected(),
+        fee: Amount::from_sat(total - target),
+        change: None,
+    })
+}
+
+// 从大到小累加到 `goal`，全部加上仍不够时返回所有候选，由调用方判断能否不找零
+fn largest_first(candidates: &[Candidate], goal: u64) -> Vec<usize> {
+    let mut value = 0;
+    let mut indices = Vec::new();
+    for (i, candidate) in candidates.iter().enumerate() {
+        if value >= goal {
+            break;
+        }
+        value += candidate.effective;
+        indices.push(i);
+    }
+    indices
+}
+
+// 深度优先搜索有效金额之和落在 [target, target + cost_of_change] 内的组合，
+// 取多出金额最少的，相同时取输入较少的
+fn branch_and_bound(candidates: &[Candidate], target: u64, cost_of_change: u64) -> Option<Vec<usize>> {
+    struct Search<'a, 'b> {
+        candidates: &'a [Candidate<'b>],
+        target: u64,
+        upper: u64,
+        tries: usize,
+        current: Vec<usize>,
+        best: Option<(u64, Vec<usize>)>,
+    }
+
+    impl Search<'_, '_> {
+        fn run(&mut self, index: usize, value: u64, remaining: u64) {
+            if self.tries >= BNB_MAX_TRIES || value > self.upper || value + remaining < self.target {
+                return;
+            }
+            self.tries += 1;
+
+            if value >= self.target {
+                let excess = value - self.target;
+                let better = self.best.as_ref()
+                    .is_none_or(|(best, indices)| (excess, self.current.len()) < (*best, indices.len()));
+                if better {
+                    self.best = Some((excess, self.current.clone()));
+                }
+                // 正好凑齐时不必继续
+                if excess == 0 {
+                    self.tries = BNB_MAX_TRIES;
+                }
+                return;
+            }
+            let Some(candidate) = self.candidates.get(index) else { return };
+
+            self.current.push(index);
+            self.run(index + 1, value + candidate.effective, remaining - candidate.effective);
+            self.current.pop();
+            self.run(index + 1, value, remaining - candidate.effective);
+        }
+    }
+
+    let total = candidates.iter().map(|candidate| candidate.effective).sum();
+    let mut search = Search {
+        candidates,
+        target,
+        upper: target + cost_of_change,
+        tries: 0,
+        current: Vec::new(),
+        best: None,
+    };
+    search.run(0, 0, total);
+    search.best.map(|(_, indices)| indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::hashes::Hash;
+    use bitcoin::{OutPoint, ScriptBuf, TxOut, Txid};
+
+    const P2TR: &str = "5120d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d";
+    const P2WPKH: &str = "0014751e76e8199196d454941c45d1b3a323f1433bd6";
+
+    fn utxo(n: u8, value: u64, script: &str) -> Utxo {
+        Utxo {
+            outpoint: OutPoint::new(Txid::from_byte_array([n; 32]), 0),
+            txout: TxOut { value: Amount::from_sat(value), script_pubkey: ScriptBuf::from_hex(script).unwrap() },
+            height: Some(100),
+        }
+    }
+
+    // 一个 P2TR 输出的 commit 交易
+    fn params(fee_rate: f64) -> CoinSelectionParams {
+        CoinSelectionParams::new(fee_rate, 53.5, ScriptType::P2TR)
+    }
+
+    #[test]
+    fn test_combines_small_utxos() {
+        let utxos: Vec<Utxo> = (1..=20).map(|n| utxo(n, 1_000, P2TR)).collect();
+        let selection = select_coins(&utxos, Amount::from_sat(10_000), &params(2.0)).unwrap();
+
+        assert!(selection.selected.len() > 10);
+        let spent = Amount::from_sat(10_000) + selection.fee + selection.change.unwrap_or(Amount::ZERO);
+        assert_eq!(selection.total_input(), spent);
+        // 手续费覆盖所有输入
+        let vsize = 53.5 + selection.selected.len() as f64 * 57.5 + selection.change.map_or(0.0, |_| 43.0);
+        assert!(selection.fee.to_sat() as f64 >= vsize * 2.0);
+    }
+
+    #[test]
+    fn test_branch_and_bound_avoids_change() {
+        // 输入手续费 58，基础手续费 54：3_058 + 5_058 正好支付 7_946 + 58 * 2 + 54
+        let utxos = vec![
+            utxo(1, 20_000, P2TR),
+            utxo(2, 3_058, P2TR),
+            utxo(3, 9_000, P2TR),
+            utxo(4, 5_058, P2TR),
+        ];
+        let selection = select_coins(&utxos, Amount::from_sat(7_946), &params(1.0)).unwrap();
+        assert_eq!(selection.change, None);
+        assert_eq!(selection.fee, Amount::from_sat(170));
+        let mut selected: Vec<u64> = selection.selected.iter().map(|utxo| utxo.txout.value.to_sat()).collect();
+        selected.sort();
+        assert_eq!(selected, vec![3_058, 5_058]);
+    }
+
+    #[test]
+    fn test_dust_change_goes_to_fee() {
+        let params = params(1.0);
+        // 找零 = 10_000 - 9_000 - ceil(53.5 + 57.5 + 43) = 846
+        let selection = select_coins(&[utxo(1, 10_000, P2TR)], Amount::from_sat(9_000), &params).unwrap();
+        assert_eq!(selection.change, Some(Amount::from_sat(846)));
+        assert_eq!(selection.fee, Amount::from_sat(154));
+
+        // 提高粉尘线后找零并入手续费
+        let selection = select_coins(&[utxo(1, 10_000, P2TR)], Amount::from_sat(9_000), &params.with_dust_limit(Amount::from_sat(1_000))).unwrap();
+        assert_eq!(selection.change, None);
+        assert_eq!(selection.fee, Amount::from_sat(1_000));
+    }
+
+    #[test]
+    fn test_input_weights_and_insufficient_funds() {
+        // P2WPKH 输入比 P2TR 重，同样金额下手续费更高
+        let taproot = select_coins(&[utxo(1, 50_000, P2TR)], Amount::from_sat(10_000), &params(10.0)).unwrap();
+        let segwit = select_coins(&[utxo(1, 50_000, P2WPKH)], Amount::from_sat(10_000), &params(10.0)).unwrap();
+        assert_eq!(segwit.fee.to_sat() - taproot.fee.to_sat(), 105);
+
+        // 不足以支付自身手续费的 UTXO 和无法识别的脚本不参与
+        let utxos = vec![utxo(1, 500, P2TR), utxo(2, 50_000, "6a"), utxo(3, 5_000, P2TR)];
+        match select_coins(&utxos, Amount::from_sat(10_000), &params(10.0)) {
+            Err(Error::InvalidAmount(message)) => assert!(message.contains("55500 sats available in 3 UTXOs")),
+            other => panic!("unexpected selection: {:?}", other),
+        }
+        let selection = select_coins(&utxos, Amount::from_sat(3_000), &params(10.0)).unwrap();
+        assert_eq!(selection.selected.len(), 1);
+        assert_eq!(selection.selected[0].outpoint, utxos[2].outpoint);
+    }
+}
@@ -6,6 +6,7 @@ pub use script_builder::*;
 
 pub mod tx_size;
 pub mod script;
+pub mod coin_selection;
 
 pub use tx_size::*;
 pub use script::*;
@@ -28,13 +29,30 @@ pub fn validate_ticker(ticker: &str) -> bool {
     ticker.chars().all(|c| c.is_ascii_alphanumeric())
 }
 
+/// 估算签名后的交易 vsize：已有 scriptSig 或见证的输入按实际大小计算，未签名的输入按 taproot key path 签名计算
 pub fn estimate_tx_size(tx: &Transaction) -> Result<usize> {
-    // 一个简单的估算，可以根据需要调整
-    let base_size = 10; // 版本 + 时间锁
-    let input_size = tx.input.len() * 150; // 每个输入约150字节
-    let output_size = tx.output.len() * 34; // 每个输出约34字节
-    
-    Ok(base_size + input_size + output_size)
+    let mut tx = tx.clone();
+    for input in tx.input.iter_mut().filter(|input| input.script_sig.is_empty() && input.witness.is_empty()) {
+        (input.script_sig, input.witness) = InputSpend::Key(ScriptType::P2TR).dummy_unlock();
+    }
+    Ok(tx.vsize())
+}
+
+/// 异步等待指定毫秒数（wasm 下使用 setTimeout，原生环境使用 tokio）
+pub async fn sleep_ms(ms: i32) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let promise = js_sys::Promise::new(&mut |resolve, _| {
+            web_sys::window()
+                .unwrap()
+                .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms)
+                .unwrap();
+        });
+        let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(std::time::Duration::from_millis(ms.max(0) as u64)).await;
 }
 
 pub fn validate_ticker_new(ticker: &str) -> Result<()> {
@@ -65,13 +83,20 @@ mod tests {
 
     #[test]
     fn test_estimate_tx_size() {
-        let tx = Transaction {
-            version: 2,
+        let p2tr = bitcoin::ScriptBuf::from_hex("5120d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d").unwrap();
+        let mut tx = Transaction {
+            version: bitcoin::transaction::Version(2),
             lock_time: bitcoin::locktime::absolute::LockTime::ZERO,
-            input: vec![],
-            output: vec![],
+            input: vec![bitcoin::TxIn::default()],
+            output: vec![bitcoin::TxOut { value: bitcoin::Amount::from_sat(1_000), script_pubkey: p2tr }],
         };
-        
-        assert_eq!(estimate_tx_size(&tx).unwrap(), 10);
+
+        // 10.5 + 57.5 + 43
+        assert_eq!(estimate_tx_size(&tx).unwrap(), 111);
+
+        // 已签名的输入按实际见证计算
+        tx.input[0].witness = bitcoin::Witness::from_slice(&[vec![0u8; 65]]);
+        assert_eq!(estimate_tx_size(&tx).unwrap(), tx.vsize());
+        assert_eq!(tx.vsize(), 112);
     }
 }
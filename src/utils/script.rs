@@ -75,7 +75,17 @@ pub fn time_nonce() -> (u64, u64) {
     (now, random)
 }
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(not(target_arch = "wasm32"))]
+pub fn time_nonce() -> (u64, u64) {
+    use rand::Rng;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let random = rand::thread_rng().gen_range(0..10_000_000u64);
+    (now, random)
+}
+
 pub fn cbor<T>(v: &T) -> Result<Vec<u8>>
 where
     T: Serialize,
@@ -84,4 +94,25 @@ where
     ciborium::ser::into_writer(v, &mut cbor)
         .map_err(|e| Error::Serialization(e.to_string()))?;
     Ok(cbor)
-}
\ No newline at end of file
+}
+/// 用新的 time/nonce 重新编码 payload，直到编码长度与原 payload 相同
+///
+/// 长度不变时 reveal 脚本长度和手续费也不变，commit 交易只需替换输出脚本。
+pub fn reroll_payload<F>(encoded_len: usize, mut encode: F) -> Result<Vec<u8>>
+where
+    F: FnMut(u64, u64) -> Result<Vec<u8>>,
+{
+    const MAX_ATTEMPTS: usize = 1000;
+
+    for _ in 0..MAX_ATTEMPTS {
+        let (time, nonce) = time_nonce();
+        let encoded = encode(time, nonce)?;
+        if encoded.len() == encoded_len {
+            return Ok(encoded);
+        }
+    }
+
+    Err(Error::Other(format!(
+        "Failed to re-roll payload with encoded length {}", encoded_len
+    )))
+}
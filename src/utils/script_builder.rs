@@ -58,12 +58,13 @@ mod tests {
         let script = build_atomicals_op_return(&payload).unwrap();
         
         // 验证脚本开始于 OP_RETURN
-        assert_eq!(script.as_bytes()[0], OP_RETURN.into_u8());
+        assert_eq!(script.as_bytes()[0], OP_RETURN.to_u8());
         
-        // 验证协议标识
-        assert_eq!(&script.as_bytes()[1..5], b"atom");
+        // 验证协议标识（跳过 push 长度字节）
+        assert_eq!(script.as_bytes()[1], 4);
+        assert_eq!(&script.as_bytes()[2..6], b"atom");
         
         // 验证协议版本
-        assert_eq!(script.as_bytes()[5], 0x01);
+        assert_eq!(&script.as_bytes()[6..8], &[0x01, 0x01]);
     }
 }
@@ -1,10 +1,11 @@
-use bitcoin::{Transaction, TxIn, TxOut, ScriptBuf};
+use bitcoin::{Amount, Transaction, TxIn, TxOut, ScriptBuf, Weight, Witness, taproot::ControlBlock};
 use log;
 
-pub const FEE_BASE_BYTES: f64 = 10.5;
-pub const FEE_INPUT_BYTES_BASE: f64 = 57.5;
-pub const FEE_OUTPUT_BYTES_BASE: f64 = 43.0;
-pub const REVEAL_INPUT_BYTES_BASE: f64 = 66.0; // 41 + 25 (witness weight/4)
+/// schnorr 签名长度，SIGHASH_DEFAULT 不附加 sighash 字节
+const SCHNORR_SIGNATURE_LEN: usize = 64;
+/// DER 编码 ECDSA 签名的最大长度加 sighash 字节
+const ECDSA_SIGNATURE_LEN: usize = 72;
+const COMPRESSED_PUBKEY_LEN: usize = 33;
 
 pub struct TransactionSize {
     pub base_size: f64,    // 非见证数据大小
@@ -26,20 +27,102 @@ impl TransactionSize {
 
 // 计算不同类型输入的大小
 pub fn get_input_size(script_type: &ScriptType) -> TransactionSize {
-    match script_type {
-        ScriptType::P2PKH => TransactionSize::new(148.0, 0.0),    // 非隔离见证
-        ScriptType::P2WPKH => TransactionSize::new(68.0, 107.0),  // 原生隔离见证
-        ScriptType::P2TR => TransactionSize::new(108.0, 108.0),  // Taproot
-    }
+    let input = dummy_input(InputSpend::Key(*script_type));
+    TransactionSize::new(input.base_size() as f64, input.witness.size() as f64)
 }
 
 // 计算不同类型输出的大小
 pub fn get_output_size(script_type: &ScriptType) -> f64 {
+    output_vsize(*script_type)
+}
+
+/// 花费一个该类型输出的输入 vsize（含见证），taproot 按 key path 签名计算
+pub fn input_vsize(script_type: ScriptType) -> f64 {
+    let input = dummy_input(InputSpend::Key(script_type));
+    let weight = match script_type {
+        ScriptType::P2PKH => input.legacy_weight(),
+        ScriptType::P2WPKH | ScriptType::P2TR => input.segwit_weight(),
+    };
+    weight.to_wu() as f64 / 4.0
+}
+
+/// 该类型输出的 vsize（金额、脚本长度和脚本）
+pub fn output_vsize(script_type: ScriptType) -> f64 {
     match script_type {
         ScriptType::P2PKH => 34.0,
         ScriptType::P2WPKH => 31.0,
-        ScriptType::P2TR => 34.0,
+        ScriptType::P2TR => 43.0,
+    }
+}
+
+/// 输入的花费方式，决定签名后 scriptSig 和见证的大小
+#[derive(Debug, Clone)]
+pub enum InputSpend {
+    /// 按输出脚本类型签名，taproot 走 key path
+    Key(ScriptType),
+    /// taproot script path：签名、叶子脚本和控制块
+    TapScript { script: ScriptBuf, control_block: ControlBlock },
+}
+
+impl InputSpend {
+    /// 与真实签名长度相同的占位 scriptSig 和见证
+    pub fn dummy_unlock(&self) -> (ScriptBuf, Witness) {
+        match self {
+            InputSpend::Key(ScriptType::P2PKH) => {
+                // <sig> <pubkey>，两个数据都用单字节长度前缀
+                let mut script_sig = vec![ECDSA_SIGNATURE_LEN as u8];
+                script_sig.extend([0u8; ECDSA_SIGNATURE_LEN]);
+                script_sig.push(COMPRESSED_PUBKEY_LEN as u8);
+                script_sig.extend([0u8; COMPRESSED_PUBKEY_LEN]);
+                (ScriptBuf::from_bytes(script_sig), Witness::new())
+            }
+            InputSpend::Key(ScriptType::P2WPKH) => (
+                ScriptBuf::new(),
+                Witness::from_slice(&[vec![0u8; ECDSA_SIGNATURE_LEN], vec![0u8; COMPRESSED_PUBKEY_LEN]]),
+            ),
+            InputSpend::Key(ScriptType::P2TR) => (
+                ScriptBuf::new(),
+                Witness::from_slice(&[vec![0u8; SCHNORR_SIGNATURE_LEN]]),
+            ),
+            InputSpend::TapScript { script, control_block } => (
+                ScriptBuf::new(),
+                Witness::from_slice(&[vec![0u8; SCHNORR_SIGNATURE_LEN], script.to_bytes(), control_block.serialize()]),
+            ),
+        }
+    }
+}
+
+fn dummy_input(spend: InputSpend) -> TxIn {
+    let (script_sig, witness) = spend.dummy_unlock();
+    TxIn { script_sig, witness, ..Default::default() }
+}
+
+/// 用占位签名解锁输入后的交易重量，与签名后的交易一致（ECDSA 签名按最大长度计算）
+///
+/// `spends` 与交易输入按顺序对应，多出的输入保持原样。
+pub fn signed_weight(tx: &Transaction, spends: &[InputSpend]) -> Weight {
+    let mut tx = tx.clone();
+    for (input, spend) in tx.input.iter_mut().zip(spends) {
+        (input.script_sig, input.witness) = spend.dummy_unlock();
     }
+    tx.weight()
+}
+
+/// 按 sat/vB 费率支付该重量的手续费，vsize 和手续费都向上取整
+pub fn fee_for_weight(weight: Weight, fee_rate: f64) -> Amount {
+    Amount::from_sat((weight.to_vbytes_ceil() as f64 * fee_rate).ceil() as u64)
+}
+
+/// 只含给定输出、尚未添加输入的隔离见证交易 vsize，作为选币的基础开销
+pub fn outputs_vsize(outputs: &[TxOut]) -> f64 {
+    let tx = Transaction {
+        version: bitcoin::transaction::Version(2),
+        lock_time: bitcoin::absolute::LockTime::ZERO,
+        input: vec![],
+        output: outputs.to_vec(),
+    };
+    // base_size 已包含 1 字节的输入数量，另加隔离见证的 marker 和 flag
+    tx.base_size() as f64 + 0.5
 }
 
 // 计算完整交易的大小
@@ -72,41 +155,6 @@ pub fn calculate_tx_size(
     TransactionSize::new(base_size, witness_size)
 }
 
-pub fn calculate_reveal_size(
-    input_num: usize,
-    output_num: usize,
-    hash_lock_script_len: usize,
-) -> f64 {
-    // 计算hash_lock_script的compact size字节数
-    let hash_lock_compact_size_bytes = if hash_lock_script_len <= 252 {
-        1
-    } else if hash_lock_script_len <= 0xffff {
-        3
-    } else if hash_lock_script_len <= 0xffffffff {
-        5
-    } else {
-        9
-    };
-
-    FEE_BASE_BYTES +
-        // Reveal input
-        REVEAL_INPUT_BYTES_BASE +
-        (hash_lock_compact_size_bytes as f64 + hash_lock_script_len as f64) / 4.0 +
-        // Additional inputs
-        (input_num as f64 * FEE_INPUT_BYTES_BASE) +
-        // Outputs
-        (output_num as f64 * FEE_OUTPUT_BYTES_BASE)
-}
-
-pub fn calculate_commit_size(
-    input_num: usize,
-    output_num: usize,
-) -> f64 {
-    FEE_BASE_BYTES +
-        (input_num as f64 * FEE_INPUT_BYTES_BASE) +
-        (output_num as f64 * FEE_OUTPUT_BYTES_BASE)
-}
-
 #[derive(Debug, Clone, Copy)]
 pub enum ScriptType {
     P2PKH,      // Pay to Public Key Hash
@@ -141,11 +189,55 @@ mod tests {
         let size = calculate_tx_size(&input_types, &output_types, false);
         
         // 验证计算结果
-        assert_eq!(size.base_size, 10.0 + (68.0 * 2.0) + (31.0 * 2.0));
-        assert_eq!(size.witness_size, 107.0 * 2.0);
+        assert_eq!(size.base_size, 10.0 + (41.0 * 2.0) + (31.0 * 2.0));
+        assert_eq!(size.witness_size, 108.0 * 2.0);
         
         // 验证 vsize 计算
         let expected_vsize = (size.base_size * 4.0 + size.witness_size + 3.0) / 4.0;
         assert_eq!(size.total_vsize, expected_vsize);
     }
+
+    #[test]
+    fn test_dummy_witness_matches_signatures() {
+        use bitcoin::secp256k1::{Keypair, Message, Secp256k1};
+
+        let secp = Secp256k1::new();
+        let keypair = Keypair::from_seckey_slice(&secp, &[1u8; 32]).unwrap();
+        let pubkey = bitcoin::PublicKey::new(keypair.public_key());
+
+        for n in 0..16u8 {
+            let msg = Message::from_digest([n; 32]);
+            let schnorr = bitcoin::taproot::Signature {
+                sig: secp.sign_schnorr_no_aux_rand(&msg, &keypair),
+                hash_ty: bitcoin::sighash::TapSighashType::Default,
+            };
+            let ecdsa = bitcoin::ecdsa::Signature::sighash_all(secp.sign_ecdsa(&msg, &keypair.secret_key()));
+
+            // taproot key path 签名长度固定
+            let taproot = TxIn { witness: Witness::from_slice(&[schnorr.to_vec()]), ..Default::default() };
+            assert_eq!(taproot.segwit_weight().to_wu() as f64 / 4.0, input_vsize(ScriptType::P2TR));
+
+            // ECDSA 签名按最大长度估算，多出不到 1 vB
+            let segwit = TxIn { witness: Witness::from_slice(&[ecdsa.to_vec(), pubkey.to_bytes()]), ..Default::default() };
+            let estimated = input_vsize(ScriptType::P2WPKH) * 4.0;
+            let actual = segwit.segwit_weight().to_wu() as f64;
+            assert!(estimated >= actual && estimated - actual < 4.0, "estimated {} actual {}", estimated, actual);
+
+            let mut script_sig = vec![ecdsa.to_vec().len() as u8];
+            script_sig.extend(ecdsa.to_vec());
+            script_sig.push(33);
+            script_sig.extend(pubkey.to_bytes());
+            let legacy = TxIn { script_sig: ScriptBuf::from_bytes(script_sig), ..Default::default() };
+            let estimated = input_vsize(ScriptType::P2PKH) * 4.0;
+            let actual = legacy.legacy_weight().to_wu() as f64;
+            assert!(estimated >= actual && estimated - actual < 16.0, "estimated {} actual {}", estimated, actual);
+        }
+    }
+
+    #[test]
+    fn test_fee_for_weight_rounds_up() {
+        // 445 WU = 111.25 vB，按 112 vB 计费
+        assert_eq!(fee_for_weight(Weight::from_wu(445), 2.0), Amount::from_sat(224));
+        assert_eq!(fee_for_weight(Weight::from_wu(444), 1.5), Amount::from_sat(167));
+    }
 }
@@ -0,0 +1,176 @@
+use crate::errors::{Error, Result};
+use crate::types::atomicals::AtomicalInfo;
+use crate::wallet::{Utxo, UtxoLedger, WalletProvider};
+use async_trait::async_trait;
+use bitcoin::psbt::Psbt;
+use bitcoin::{Amount, Network, PublicKey, Transaction, TxOut};
+
+/// 由主钱包和资金钱包组合成的钱包
+///
+/// 主钱包只提供接收 atomicals 的地址和公钥；UTXO、费率、广播等都走资金钱包。
+/// 签名时先用资金钱包，仍有未完成的输入（例如花费主地址上的父 Realm）再交给主钱包。
+pub struct DualWallet<P, F> {
+    primary: P,
+    funding: F,
+}
+
+impl<P: WalletProvider, F: WalletProvider> DualWallet<P, F> {
+    pub fn new(primary: P, funding: F) -> Self {
+        Self { primary, funding }
+    }
+
+    pub fn primary(&self) -> &P {
+        &self.primary
+    }
+
+    pub fn funding(&self) -> &F {
+        &self.funding
+    }
+}
+
+fn is_finalized(psbt: &Psbt) -> bool {
+    psbt.inputs.iter().all(|input| input.final_script_witness.is_some() || input.final_script_sig.is_some())
+}
+
+#[async_trait(?Send)]
+impl<P: WalletProvider, F: WalletProvider> WalletProvider for DualWallet<P, F> {
+    async fn get_network(&self) -> Result<Network> {
+        self.funding.get_network().await
+    }
+
+    async fn get_public_key(&self) -> Result<PublicKey> {
+        self.primary.get_public_key().await
+    }
+
+    async fn get_address(&self) -> Result<String> {
+        self.primary.get_address().await
+    }
+
+    async fn get_funding_address(&self) -> Result<String> {
+        self.funding.get_address().await
+    }
+
+    async fn get_funding_public_key(&self) -> Result<PublicKey> {
+        self.funding.get_public_key().await
+    }
+
+    async fn sign_transaction(&self, tx: Transaction, input_txouts: &[TxOut]) -> Result<Transaction> {
+        self.funding.sign_transaction(tx, input_txouts).await
+    }
+
+    async fn broadcast_transaction(&self, tx: Transaction) -> Result<String> {
+        self.funding.broadcast_transaction(tx).await
+    }
+
+    /// 资金钱包先签名，剩余输入交给主钱包；两者签完仍有未完成的输入时返回错误
+    async fn sign_psbt(&self, psbt: Psbt) -> Result<Psbt> {
+        let (signed, funding_error) = match self.funding.sign_psbt(psbt.clone()).await {
+            Ok(signed) if is_finalized(&signed) => return Ok(signed),
+            Ok(signed) => {
+                let signed = self.primary.sign_psbt(signed).await.map_err(|e| {
+                    Error::WalletError(format!("Primary wallet failed to sign remaining inputs: {}", e))
+                })?;
+                (signed, None)
+            }
+            // 资金钱包没有可签的输入时整个 PSBT 交给主钱包
+            Err(funding_error) => {
+                let signed = self.primary.sign_psbt(psbt).await.map_err(|e| {
+                    Error::WalletError(format!("Neither wallet could sign the PSBT: funding: {}; primary: {}", funding_error, e))
+                })?;
+                (signed, Some(funding_error))
+            }
+        };
+
+        if !is_finalized(&signed) {
+            return Err(Error::WalletError(match funding_error {
+                Some(e) => format!("PSBT is not fully signed, funding wallet failed: {}", e),
+                None => "PSBT has inputs neither wallet can sign".into(),
+            }));
+        }
+        Ok(signed)
+    }
+
+    async fn get_utxos(&self) -> Result<Vec<Utxo>> {
+        self.funding.get_utxos().await
+    }
+
+    async fn get_balance(&self) -> Result<Amount> {
+        self.funding.get_balance().await
+    }
+
+    fn utxo_ledger(&self) -> Option<UtxoLedger> {
+        self.funding.utxo_ledger()
+    }
+
+    async fn get_network_fee_rate(&self) -> Result<f64> {
+        self.funding.get_network_fee_rate().await
+    }
+
+    async fn sign_atomicals_transactions(&self, commit_psbt: Psbt, reveal_psbt: Psbt) -> Result<(Transaction, Transaction)> {
+        let commit = self.sign_psbt(commit_psbt).await?.extract_tx()
+            .map_err(|e| Error::TransactionError(format!("Failed to extract commit tx: {}", e)))?;
+        let reveal = self.sign_psbt(reveal_psbt).await?.extract_tx()
+            .map_err(|e| Error::TransactionError(format!("Failed to extract reveal tx: {}", e)))?;
+        Ok((commit, reveal))
+    }
+
+    async fn get_atomical_by_id(&self, atomical_id: &str) -> Result<AtomicalInfo> {
+        self.funding.get_atomical_by_id(atomical_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::hd::{bip86_funding_path, bip86_path, HdWallet};
+    use bitcoin::{absolute::LockTime, transaction::Version, OutPoint, ScriptBuf, Sequence, TxIn, Witness};
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn dual_wallet() -> DualWallet<HdWallet, HdWallet> {
+        let network = Network::Regtest;
+        DualWallet::new(
+            HdWallet::from_mnemonic_at_path(MNEMONIC, "", network, bip86_path(network, 0, 0).unwrap()).unwrap(),
+            HdWallet::from_mnemonic_at_path(MNEMONIC, "", network, bip86_funding_path(network, 0).unwrap()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_roles_use_distinct_keys() {
+        let wallet = dual_wallet();
+        assert_eq!(wallet.get_address().await.unwrap(), wallet.primary().address().to_string());
+        assert_eq!(wallet.get_funding_address().await.unwrap(), wallet.funding().address().to_string());
+        assert_ne!(wallet.get_address().await.unwrap(), wallet.get_funding_address().await.unwrap());
+        assert_ne!(wallet.get_public_key().await.unwrap(), wallet.get_funding_public_key().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_signs_funding_and_primary_inputs() {
+        let wallet = dual_wallet();
+        let input = |vout| TxIn {
+            previous_output: OutPoint::new("1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap(), vout),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ZERO,
+            witness: Witness::new(),
+        };
+        let tx = Transaction {
+            version: Version(2),
+            lock_time: LockTime::ZERO,
+            input: vec![input(0), input(1)],
+            output: vec![TxOut { value: Amount::from_sat(15000), script_pubkey: ScriptBuf::from_bytes(vec![0x51]) }],
+        };
+
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        psbt.inputs[0].witness_utxo = Some(TxOut { value: Amount::from_sat(10000), script_pubkey: wallet.funding().address().script_pubkey() });
+        psbt.inputs[1].witness_utxo = Some(TxOut { value: Amount::from_sat(10000), script_pubkey: wallet.primary().address().script_pubkey() });
+
+        let signed = wallet.sign_psbt(psbt.clone()).await.unwrap();
+        assert!(is_finalized(&signed));
+
+        // 不属于任一钱包的输入不能以部分签名的 PSBT 返回
+        psbt.inputs[1].witness_utxo = Some(TxOut { value: Amount::from_sat(10000), script_pubkey: ScriptBuf::from_bytes(vec![0x51]) });
+        assert!(matches!(wallet.sign_psbt(psbt.clone()).await, Err(Error::WalletError(_))));
+        psbt.inputs[0].witness_utxo = psbt.inputs[1].witness_utxo.clone();
+        assert!(matches!(wallet.sign_psbt(psbt).await, Err(Error::WalletError(_))));
+    }
+}
@@ -0,0 +1,484 @@
+use crate::errors::{Error, Result};
+use crate::types::atomicals::AtomicalInfo;
+use crate::api::{AtomicalsApi, ElectrumApi};
+use crate::wallet::{Utxo, UtxoLedger, WalletProvider};
+use async_trait::async_trait;
+use bip39::Mnemonic;
+use bitcoin::hashes::Hash;
+use bitcoin::bip32::{ChildNumber, DerivationPath, Fingerprint, Xpriv, Xpub};
+use bitcoin::key::{Keypair, TapTweak, XOnlyPublicKey};
+use bitcoin::psbt::{Input, Psbt};
+use bitcoin::secp256k1::{All, Message, Secp256k1};
+use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+use bitcoin::taproot::{self, TapLeafHash};
+use bitcoin::{Address, Amount, Network, PrivateKey, PublicKey, ScriptBuf, Transaction, TxOut, Witness};
+use std::rc::Rc;
+
+/// 基于 BIP39 助记词的本地软件钱包，按 BIP86 派生 taproot 密钥
+///
+/// 使用路径 `m/86'/coin'/account'/0/0` 的密钥签名；commit 输入走 key-path，
+/// 脚本中包含本钱包公钥的 reveal 输入走 script-path。UTXO、广播和费率通过 `AtomicalsApi` 查询。
+/// 也可以由单个 WIF 私钥构造，此时没有助记词和派生路径。
+pub struct HdWallet {
+    mnemonic: Option<Mnemonic>,
+    master: Option<Xpriv>,
+    network: Network,
+    path: Option<DerivationPath>,
+    keypair: Keypair,
+    api: Option<Rc<dyn AtomicalsApi>>,
+    ledger: UtxoLedger,
+    secp: Secp256k1<All>,
+}
+
+impl HdWallet {
+    /// 生成新的助记词，`word_count` 为 12、15、18、21 或 24
+    pub fn generate(word_count: usize, network: Network) -> Result<Self> {
+        let mnemonic = generate_mnemonic(word_count)?;
+        Self::from_parts(mnemonic, "", network, bip86_path(network, 0, 0)?)
+    }
+
+    /// 从助记词导入，`passphrase` 为 BIP39 附加密码（没有时传空字符串）
+    pub fn from_mnemonic(phrase: &str, passphrase: &str, network: Network) -> Result<Self> {
+        Self::from_mnemonic_with_account(phrase, passphrase, network, 0)
+    }
+
+    pub fn from_mnemonic_with_account(phrase: &str, passphrase: &str, network: Network, account: u32) -> Result<Self> {
+        Self::from_mnemonic_at_path(phrase, passphrase, network, bip86_path(network, account, 0)?)
+    }
+
+    /// 使用任意派生路径上的密钥
+    pub fn from_mnemonic_at_path(phrase: &str, passphrase: &str, network: Network, path: DerivationPath) -> Result<Self> {
+        let mnemonic = Mnemonic::parse(phrase)
+            .map_err(|e| Error::WalletError(format!("Invalid mnemonic: {}", e)))?;
+        Self::from_parts(mnemonic, passphrase, network, path)
+    }
+
+    /// 从 WIF 私钥导入，网络取自 WIF 前缀（测试网前缀按 `network` 区分 testnet/signet/regtest）
+    pub fn from_wif(wif: &str, network: Network) -> Result<Self> {
+        let key = PrivateKey::from_wif(wif)
+            .map_err(|e| Error::WalletError(format!("Invalid WIF: {}", e)))?;
+        if (key.network == Network::Bitcoin) != (network == Network::Bitcoin) {
+            return Err(Error::WalletError(format!("WIF is not for {}", network)));
+        }
+        let secp = Secp256k1::new();
+        Ok(Self {
+            mnemonic: None,
+            master: None,
+            network,
+            path: None,
+            keypair: Keypair::from_secret_key(&secp, &key.inner),
+            api: default_api(network),
+            ledger: UtxoLedger::new(),
+            secp,
+        })
+    }
+
+    fn from_parts(mnemonic: Mnemonic, passphrase: &str, network: Network, path: DerivationPath) -> Result<Self> {
+        let secp = Secp256k1::new();
+        let master = Xpriv::new_master(network, &mnemonic.to_seed(passphrase))
+            .map_err(|e| Error::WalletError(format!("Failed to derive master key: {}", e)))?;
+        let keypair = master.derive_priv(&secp, &path)
+            .map_err(|e| Error::WalletError(format!("Failed to derive key {}: {}", path, e)))?
+            .to_keypair(&secp);
+
+        Ok(Self {
+            mnemonic: Some(mnemonic),
+            master: Some(master),
+            network,
+            path: Some(path),
+            keypair,
+            api: default_api(network),
+            ledger: UtxoLedger::new(),
+            secp,
+        })
+    }
+
+    /// 使用指定的 API 查询 UTXO 和广播交易
+    pub fn with_api(mut self, api: impl AtomicalsApi + 'static) -> Self {
+        self.api = Some(Rc::new(api));
+        self
+    }
+
+    /// 与其他钱包实例共用 UTXO 预留账本，例如同一密钥的多个实例同时铸造
+    pub fn with_ledger(mut self, ledger: UtxoLedger) -> Self {
+        self.ledger = ledger;
+        self
+    }
+
+    /// WIF 导入的钱包没有助记词
+    pub fn mnemonic(&self) -> Option<String> {
+        self.mnemonic.as_ref().map(|m| m.to_string())
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// 签名密钥的派生路径
+    pub fn derivation_path(&self) -> Option<&DerivationPath> {
+        self.path.as_ref()
+    }
+
+    pub fn master_fingerprint(&self) -> Option<Fingerprint> {
+        self.master.as_ref().map(|master| master.fingerprint(&self.secp))
+    }
+
+    /// 派生路径上的扩展公钥，`path` 必须全部为硬化或普通索引的合法路径，例如账户级 `m/86'/0'/0'`
+    pub fn xpub_at(&self, path: &DerivationPath) -> Result<Xpub> {
+        let master = self.master.as_ref()
+            .ok_or_else(|| Error::WalletError("Wallet has no extended keys".into()))?;
+        let xpriv = master.derive_priv(&self.secp, path)
+            .map_err(|e| Error::WalletError(format!("Failed to derive key {}: {}", path, e)))?;
+        Ok(Xpub::from_priv(&self.secp, &xpriv))
+    }
+
+    /// taproot 内部公钥
+    pub fn x_only_public_key(&self) -> XOnlyPublicKey {
+        self.keypair.x_only_public_key().0
+    }
+
+    /// BIP86 key-path 地址
+    pub fn address(&self) -> Address {
+        Address::p2tr(&self.secp, self.x_only_public_key(), None, self.network)
+    }
+
+    fn api(&self) -> Result<&dyn AtomicalsApi> {
+        self.api.as_deref().ok_or_else(|| {
+            Error::NetworkError(format!("No Atomicals API configured for {}", self.network))
+        })
+    }
+
+    /// 签名 PSBT 中属于本钱包的 taproot 输入并完成这些输入
+    ///
+    /// 所有输入都需要 `witness_utxo`（taproot 签名哈希覆盖全部输入金额）。
+    pub fn sign_psbt_inputs(&self, psbt: &mut Psbt) -> Result<usize> {
+        let prevouts = psbt.inputs.iter().enumerate()
+            .map(|(i, input)| {
+                input.witness_utxo.clone()
+                    .ok_or_else(|| Error::PsbtError(format!("Input {} is missing witness_utxo", i)))
+            })
+            .collect::<Result<Vec<TxOut>>>()?;
+        let prevouts = Prevouts::All(&prevouts);
+        let xonly = self.x_only_public_key();
+        let own_script = self.address().script_pubkey();
+
+        let mut cache = SighashCache::new(&psbt.unsigned_tx);
+        let mut signed = 0;
+
+        for (i, input) in psbt.inputs.iter_mut().enumerate() {
+            if input.final_script_witness.is_some() {
+                continue;
+            }
+            let hash_ty = match input.sighash_type {
+                Some(ty) => ty.taproot_hash_ty()
+                    .map_err(|e| Error::PsbtError(format!("Input {}: {}", i, e)))?,
+                None => TapSighashType::Default,
+            };
+
+            // reveal 输入：脚本中包含本钱包公钥的叶子走 script-path
+            let leaf = input.tap_scripts.iter()
+                .find(|(_, (script, _))| script_has_key(script, &xonly))
+                .map(|(control_block, (script, version))| (control_block.clone(), script.clone(), *version));
+
+            let witness = if let Some((control_block, script, version)) = leaf {
+                let leaf_hash = TapLeafHash::from_script(&script, version);
+                let sighash = cache.taproot_script_spend_signature_hash(i, &prevouts, leaf_hash, hash_ty)
+                    .map_err(|e| Error::PsbtError(format!("Input {}: {}", i, e)))?;
+                let sig = self.secp.sign_schnorr_no_aux_rand(&Message::from_digest(sighash.to_byte_array()), &self.keypair);
+                let signature = taproot::Signature { sig, hash_ty };
+
+                let mut witness = Witness::new();
+                witness.push(signature.to_vec());
+                witness.push(script.as_bytes());
+                witness.push(control_block.serialize());
+                witness
+            } else if input.tap_internal_key == Some(xonly) || input.witness_utxo.as_ref().map(|o| &o.script_pubkey) == Some(&own_script) {
+                // commit 输入：key-path，输出密钥按 merkle root（没有时为 BIP86）调整
+                let sighash = cache.taproot_key_spend_signature_hash(i, &prevouts, hash_ty)
+                    .map_err(|e| Error::PsbtError(format!("Input {}: {}", i, e)))?;
+                let tweaked = self.keypair.tap_tweak(&self.secp, input.tap_merkle_root);
+                let sig = self.secp.sign_schnorr_no_aux_rand(&Message::from_digest(sighash.to_byte_array()), &tweaked.to_inner());
+                Witness::from_slice(&[taproot::Signature { sig, hash_ty }.to_vec()])
+            } else {
+                continue;
+            };
+
+            finalize_input(input, witness);
+            signed += 1;
+        }
+
+        Ok(signed)
+    }
+}
+
+fn default_api(network: Network) -> Option<Rc<dyn AtomicalsApi>> {
+    ElectrumApi::for_network(network).map(|api| Rc::new(api) as Rc<dyn AtomicalsApi>)
+}
+
+/// 生成新的 BIP39 助记词，`word_count` 为 12、15、18、21 或 24
+pub fn generate_mnemonic(word_count: usize) -> Result<Mnemonic> {
+    if ![12, 15, 18, 21, 24].contains(&word_count) {
+        return Err(Error::InvalidInput(format!("Unsupported mnemonic length: {}", word_count)));
+    }
+    let mut entropy = vec![0u8; word_count / 3 * 4];
+    getrandom::getrandom(&mut entropy)
+        .map_err(|e| Error::WalletError(format!("Failed to generate entropy: {}", e)))?;
+    Mnemonic::from_entropy(&entropy)
+        .map_err(|e| Error::WalletError(format!("Failed to create mnemonic: {}", e)))
+}
+
+/// BIP86 账户路径 `m/86'/coin'/account'`
+pub fn bip86_account_path(network: Network, account: u32) -> Result<DerivationPath> {
+    let path = bip86_path(network, account, 0)?;
+    Ok(DerivationPath::from(&path[..3]))
+}
+
+/// BIP86 主（接收）路径 `m/86'/coin'/account'/0/index`：主网 coin type 为 0，其他网络为 1
+pub fn bip86_path(network: Network, account: u32, index: u32) -> Result<DerivationPath> {
+    bip86_chain_path(network, account, 0, index)
+}
+
+/// atomicals-js 的资金密钥路径 `m/86'/coin'/account'/1/0`
+pub fn bip86_funding_path(network: Network, account: u32) -> Result<DerivationPath> {
+    bip86_chain_path(network, account, 1, 0)
+}
+
+fn bip86_chain_path(network: Network, account: u32, chain: u32, index: u32) -> Result<DerivationPath> {
+    let coin = if network == Network::Bitcoin { 0 } else { 1 };
+    let hardened = |n| ChildNumber::from_hardened_idx(n);
+    let normal = |n| ChildNumber::from_normal_idx(n);
+    let path = vec![hardened(86), hardened(coin), hardened(account), normal(chain), normal(index)]
+        .into_iter()
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| Error::WalletError(format!("Invalid derivation index: {}", e)))?;
+    Ok(DerivationPath::from(path))
+}
+
+// reveal 脚本以 `<x-only pubkey> OP_CHECKSIG` 开头
+fn script_has_key(script: &ScriptBuf, xonly: &XOnlyPublicKey) -> bool {
+    let key = xonly.serialize();
+    script.instructions().any(|ins| matches!(ins, Ok(bitcoin::script::Instruction::PushBytes(bytes)) if bytes.as_bytes() == key))
+}
+
+// 按 BIP174 完成输入：写入最终见证并清除签名过程中的字段
+fn finalize_input(input: &mut Input, witness: Witness) {
+    *input = Input {
+        witness_utxo: input.witness_utxo.take(),
+        non_witness_utxo: input.non_witness_utxo.take(),
+        final_script_witness: Some(witness),
+        unknown: std::mem::take(&mut input.unknown),
+        proprietary: std::mem::take(&mut input.proprietary),
+        ..Default::default()
+    };
+}
+
+#[async_trait(?Send)]
+impl WalletProvider for HdWallet {
+    async fn get_network(&self) -> Result<Network> {
+        Ok(self.network)
+    }
+
+    async fn get_public_key(&self) -> Result<PublicKey> {
+        Ok(PublicKey::new(self.keypair.public_key()))
+    }
+
+    async fn get_address(&self) -> Result<String> {
+        Ok(self.address().to_string())
+    }
+
+    async fn sign_transaction(&self, tx: Transaction, input_txouts: &[TxOut]) -> Result<Transaction> {
+        if tx.input.len() != input_txouts.len() {
+            return Err(Error::TransactionError(format!(
+                "Expected {} input txouts, got {}", tx.input.len(), input_txouts.len()
+            )));
+        }
+
+        let mut psbt = Psbt::from_unsigned_tx(tx)
+            .map_err(|e| Error::PsbtError(format!("Failed to create PSBT: {}", e)))?;
+        for (input, txout) in psbt.inputs.iter_mut().zip(input_txouts) {
+            input.witness_utxo = Some(txout.clone());
+        }
+        let signed = self.sign_psbt(psbt).await?;
+        signed.extract_tx()
+            .map_err(|e| Error::TransactionError(format!("Failed to extract signed tx: {}", e)))
+    }
+
+    async fn broadcast_transaction(&self, tx: Transaction) -> Result<String> {
+        self.api()?.broadcast(&tx).await
+    }
+
+    async fn sign_psbt(&self, mut psbt: Psbt) -> Result<Psbt> {
+        if self.sign_psbt_inputs(&mut psbt)? == 0 {
+            return Err(Error::WalletError("PSBT has no inputs this wallet can sign".into()));
+        }
+        Ok(psbt)
+    }
+
+    async fn get_utxos(&self) -> Result<Vec<Utxo>> {
+        self.api()?.list_utxos(&self.address().script_pubkey()).await
+    }
+
+    async fn get_balance(&self) -> Result<Amount> {
+        let utxos = self.get_utxos().await?;
+        Ok(utxos.iter().map(|utxo| utxo.txout.value).sum())
+    }
+
+    async fn get_network_fee_rate(&self) -> Result<f64> {
+        self.api()?.fee_rate().await
+    }
+
+    fn utxo_ledger(&self) -> Option<UtxoLedger> {
+        Some(self.ledger.clone())
+    }
+
+    async fn sign_atomicals_transactions(&self, commit_psbt: Psbt, reveal_psbt: Psbt) -> Result<(Transaction, Transaction)> {
+        let commit = self.sign_psbt(commit_psbt).await?.extract_tx()
+            .map_err(|e| Error::TransactionError(format!("Failed to extract commit tx: {}", e)))?;
+        let reveal = self.sign_psbt(reveal_psbt).await?.extract_tx()
+            .map_err(|e| Error::TransactionError(format!("Failed to extract reveal tx: {}", e)))?;
+        Ok((commit, reveal))
+    }
+
+    async fn get_atomical_by_id(&self, atomical_id: &str) -> Result<AtomicalInfo> {
+        self.api()?.get_state(atomical_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::secp256k1::schnorr;
+    use bitcoin::taproot::{LeafVersion, TaprootBuilder};
+    use bitcoin::{absolute::LockTime, transaction::Version, OutPoint, Sequence, TxIn};
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn spending_tx(prevout: &TxOut) -> (Transaction, TxOut) {
+        let tx = Transaction {
+            version: Version(2),
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new("1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap(), 0),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ZERO,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut { value: Amount::from_sat(9000), script_pubkey: ScriptBuf::from_bytes(vec![0x51]) }],
+        };
+        (tx, prevout.clone())
+    }
+
+    #[test]
+    fn test_bip86_test_vector() {
+        // BIP86 第一个接收地址
+        let wallet = HdWallet::from_mnemonic(MNEMONIC, "", Network::Bitcoin).unwrap();
+        assert_eq!(wallet.derivation_path().unwrap().to_string(), "m/86'/0'/0'/0/0");
+        assert_eq!(
+            wallet.x_only_public_key().to_string(),
+            "cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115"
+        );
+        assert_eq!(wallet.address().to_string(), "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr");
+
+        let testnet = HdWallet::from_mnemonic(MNEMONIC, "", Network::Testnet).unwrap();
+        assert_eq!(testnet.derivation_path().unwrap().to_string(), "m/86'/1'/0'/0/0");
+
+        // BIP86 账户扩展公钥
+        let xpub = wallet.xpub_at(&bip86_account_path(Network::Bitcoin, 0).unwrap()).unwrap();
+        assert_eq!(
+            xpub.to_string(),
+            "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ"
+        );
+    }
+
+    #[test]
+    fn test_generate_and_reimport() {
+        let wallet = HdWallet::generate(24, Network::Regtest).unwrap();
+        let phrase = wallet.mnemonic().unwrap();
+        assert_eq!(phrase.split_whitespace().count(), 24);
+
+        let imported = HdWallet::from_mnemonic(&phrase, "", Network::Regtest).unwrap();
+        assert_eq!(imported.address(), wallet.address());
+        assert!(HdWallet::generate(13, Network::Regtest).is_err());
+        assert!(HdWallet::from_mnemonic("abandon", "", Network::Regtest).is_err());
+    }
+
+    #[test]
+    fn test_import_wif() {
+        let wallet = HdWallet::from_mnemonic(MNEMONIC, "", Network::Regtest).unwrap();
+        let wif = PrivateKey::new(wallet.keypair.secret_key(), Network::Regtest).to_wif();
+
+        let imported = HdWallet::from_wif(&wif, Network::Regtest).unwrap();
+        assert_eq!(imported.address(), wallet.address());
+        assert!(imported.mnemonic().is_none());
+        assert!(imported.xpub_at(&bip86_account_path(Network::Regtest, 0).unwrap()).is_err());
+        assert!(HdWallet::from_wif(&wif, Network::Bitcoin).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sign_key_path_input() {
+        let wallet = HdWallet::from_mnemonic(MNEMONIC, "", Network::Regtest).unwrap();
+        let prevout = TxOut { value: Amount::from_sat(10000), script_pubkey: wallet.address().script_pubkey() };
+        let (tx, prevout) = spending_tx(&prevout);
+
+        let signed = wallet.sign_transaction(tx, std::slice::from_ref(&prevout)).await.unwrap();
+        let witness = &signed.input[0].witness;
+        assert_eq!(witness.len(), 1);
+
+        let sighash = SighashCache::new(&signed)
+            .taproot_key_spend_signature_hash(0, &Prevouts::All(&[prevout]), TapSighashType::Default)
+            .unwrap();
+        let output_key = wallet.x_only_public_key().tap_tweak(&wallet.secp, None).0.to_inner();
+        let sig = schnorr::Signature::from_slice(&witness[0]).unwrap();
+        wallet.secp
+            .verify_schnorr(&sig, &Message::from_digest(sighash.to_byte_array()), &output_key)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sign_script_path_reveal_input() {
+        let wallet = HdWallet::from_mnemonic(MNEMONIC, "", Network::Regtest).unwrap();
+        let xonly = wallet.x_only_public_key();
+        let script = crate::utils::script::append_mint_update_reveal_script("dmt", &xonly, b"payload").unwrap();
+
+        let spend_info = TaprootBuilder::new().add_leaf(0, script.clone()).unwrap().finalize(&wallet.secp, xonly).unwrap();
+        let control_block = spend_info.control_block(&(script.clone(), LeafVersion::TapScript)).unwrap();
+        let commit_output = TxOut {
+            value: Amount::from_sat(10000),
+            script_pubkey: ScriptBuf::new_p2tr(&wallet.secp, xonly, spend_info.merkle_root()),
+        };
+
+        let (tx, prevout) = spending_tx(&commit_output);
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        psbt.inputs[0].witness_utxo = Some(prevout.clone());
+        psbt.inputs[0].tap_internal_key = Some(xonly);
+        psbt.inputs[0].tap_merkle_root = spend_info.merkle_root();
+        psbt.inputs[0].tap_scripts.insert(control_block.clone(), (script.clone(), LeafVersion::TapScript));
+
+        let signed = wallet.sign_psbt(psbt).await.unwrap();
+        assert!(signed.inputs[0].tap_scripts.is_empty());
+        let reveal = signed.extract_tx().unwrap();
+        let witness = &reveal.input[0].witness;
+        assert_eq!(witness.len(), 3);
+        assert_eq!(witness[1], *script.as_bytes());
+        assert_eq!(witness[2], control_block.serialize());
+
+        let leaf_hash = TapLeafHash::from_script(&script, LeafVersion::TapScript);
+        let sighash = SighashCache::new(&reveal)
+            .taproot_script_spend_signature_hash(0, &Prevouts::All(&[prevout]), leaf_hash, TapSighashType::Default)
+            .unwrap();
+        let sig = schnorr::Signature::from_slice(&witness[0]).unwrap();
+        wallet.secp.verify_schnorr(&sig, &Message::from_digest(sighash.to_byte_array()), &xonly).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejects_foreign_psbt() {
+        let wallet = HdWallet::from_mnemonic(MNEMONIC, "", Network::Regtest).unwrap();
+        let other = HdWallet::generate(12, Network::Regtest).unwrap();
+        let prevout = TxOut { value: Amount::from_sat(10000), script_pubkey: other.address().script_pubkey() };
+        let (tx, prevout) = spending_tx(&prevout);
+
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        psbt.inputs[0].witness_utxo = Some(prevout);
+        assert!(matches!(wallet.sign_psbt(psbt).await, Err(Error::WalletError(_))));
+    }
+}
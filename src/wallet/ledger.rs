@@ -0,0 +1,297 @@
+use crate::errors::{Error, Result};
+use crate::wallet::Utxo;
+use bitcoin::{OutPoint, Script, Transaction};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+/// 预留和已花费记录的默认保留时间：30 分钟
+pub const DEFAULT_LEDGER_TTL_MS: u64 = 30 * 60 * 1000;
+
+#[derive(Default)]
+struct LedgerState {
+    next_id: u64,
+    /// 操作正在使用的输入 -> (预留编号, 过期时间)
+    reserved: BTreeMap<OutPoint, (u64, u64)>,
+    /// 已广播交易花费的输入 -> 过期时间，索引器可能还在返回它们
+    spent: BTreeMap<OutPoint, u64>,
+    /// 已广播交易的找零 -> (UTXO, 过期时间)，索引器可能还没有看到它们
+    pending: BTreeMap<OutPoint, (Utxo, u64)>,
+}
+
+impl LedgerState {
+    fn expire(&mut self, now: u64) {
+        self.reserved.retain(|_, (_, expires_at)| *expires_at > now);
+        self.spent.retain(|_, expires_at| *expires_at > now);
+        self.pending.retain(|_, (_, expires_at)| *expires_at > now);
+    }
+
+    fn is_locked(&self, outpoint: &OutPoint) -> bool {
+        self.reserved.contains_key(outpoint) || self.spent.contains_key(outpoint)
+    }
+}
+
+/// UTXO 预留账本
+///
+/// 选中的输入从选择起锁定，直到交易广播、操作失败或超时。广播后输入记为已花费，
+/// 找零记为未确认的可用 UTXO，后续操作可以在索引器更新之前直接花费。
+/// 克隆共享同一份状态，同一钱包上并发或批量的铸造应使用同一个账本。
+#[derive(Clone)]
+pub struct UtxoLedger {
+    state: Rc<RefCell<LedgerState>>,
+    ttl_ms: u64,
+}
+
+impl Default for UtxoLedger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UtxoLedger {
+    pub fn new() -> Self {
+        Self { state: Rc::new(RefCell::new(LedgerState::default())), ttl_ms: DEFAULT_LEDGER_TTL_MS }
+    }
+
+    /// 记录的保留时间，超时的预留自动释放，超时的找零不再提供
+    pub fn with_ttl_ms(mut self, ttl_ms: u64) -> Self {
+        self.ttl_ms = ttl_ms;
+        self
+    }
+
+    /// 去掉已预留和已花费的输入，并补上索引器还没返回的找零
+    pub fn spendable(&self, utxos: Vec<Utxo>) -> Vec<Utxo> {
+        self.spendable_at(utxos, now_ms())
+    }
+
+    fn spendable_at(&self, utxos: Vec<Utxo>, now: u64) -> Vec<Utxo> {
+        let mut state = self.state.borrow_mut();
+        state.expire(now);
+        // 索引器已经返回的找零以索引器为准
+        for utxo in &utxos {
+            state.pending.remove(&utxo.outpoint);
+        }
+
+        let mut spendable: Vec<Utxo> = utxos.into_iter()
+            .filter(|utxo| !state.is_locked(&utxo.outpoint))
+            .collect();
+        spendable.extend(state.pending.iter()
+            .filter(|(outpoint, _)| !state.is_locked(outpoint))
+            .map(|(_, (utxo, _))| utxo.clone()));
+        spendable
+    }
+
+    /// 锁定选中的输入；任一输入已被锁定时失败，不锁定任何输入
+    pub fn reserve(&self, utxos: &[Utxo]) -> Result<UtxoReservation> {
+        self.reserve_at(utxos, now_ms())
+    }
+
+    fn reserve_at(&self, utxos: &[Utxo], now: u64) -> Result<UtxoReservation> {
+        let mut state = self.state.borrow_mut();
+        state.expire(now);
+        if let Some(utxo) = utxos.iter().find(|utxo| state.is_locked(&utxo.outpoint)) {
+            return Err(Error::WalletError(format!("UTXO {} is already reserved or spent", utxo.outpoint)));
+        }
+
+        state.next_id += 1;
+        let id = state.next_id;
+        let expires_at = now.saturating_add(self.ttl_ms);
+        for utxo in utxos {
+            state.reserved.insert(utxo.outpoint, (id, expires_at));
+        }
+        Ok(UtxoReservation {
+            ledger: self.clone(),
+            id,
+            outpoints: utxos.iter().map(|utxo| utxo.outpoint).collect(),
+            completed: false,
+        })
+    }
+
+    pub fn is_reserved(&self, outpoint: &OutPoint) -> bool {
+        self.state.borrow().is_locked(outpoint)
+    }
+
+    /// 已登记、尚未过期的找零
+    pub fn pending(&self) -> Vec<Utxo> {
+        self.state.borrow().pending.values().map(|(utxo, _)| utxo.clone()).collect()
+    }
+
+    // 只释放本次预留的输入，超时后被其他操作重新预留的不受影响
+    fn release(&self, id: u64, outpoints: &[OutPoint]) {
+        let mut state = self.state.borrow_mut();
+        for outpoint in outpoints {
+            if state.reserved.get(outpoint).is_some_and(|(owner, _)| *owner == id) {
+                state.reserved.remove(outpoint);
+            }
+        }
+    }
+
+    fn record_broadcast(&self, id: u64, outpoints: &[OutPoint], tx: &Transaction, change_script: &Script, now: u64) {
+        self.release(id, outpoints);
+        let mut state = self.state.borrow_mut();
+        let expires_at = now.saturating_add(self.ttl_ms);
+        for input in &tx.input {
+            state.pending.remove(&input.previous_output);
+            state.spent.insert(input.previous_output, expires_at);
+        }
+
+        let txid = tx.txid();
+        for (vout, txout) in tx.output.iter().enumerate() {
+            if txout.script_pubkey.as_script() == change_script {
+                let outpoint = OutPoint::new(txid, vout as u32);
+                let utxo = Utxo { outpoint, txout: txout.clone(), height: None };
+                state.pending.insert(outpoint, (utxo, expires_at));
+            }
+        }
+    }
+}
+
+/// 一次操作预留的输入，未调用 `complete` 就被丢弃时（例如操作出错返回）自动释放
+pub struct UtxoReservation {
+    ledger: UtxoLedger,
+    id: u64,
+    outpoints: Vec<OutPoint>,
+    completed: bool,
+}
+
+impl UtxoReservation {
+    pub fn outpoints(&self) -> &[OutPoint] {
+        &self.outpoints
+    }
+
+    /// 交易已广播：输入记为已花费，支付到 `change_script` 的输出记为未确认的可用 UTXO
+    pub fn complete(mut self, tx: &Transaction, change_script: &Script) {
+        self.ledger.record_broadcast(self.id, &self.outpoints, tx, change_script, now_ms());
+        self.completed = true;
+    }
+}
+
+impl Drop for UtxoReservation {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.ledger.release(self.id, &self.outpoints);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::{absolute::LockTime, transaction::Version, Amount, ScriptBuf, Sequence, TxIn, TxOut, Txid, Witness};
+    use bitcoin::hashes::Hash;
+
+    fn script() -> ScriptBuf {
+        ScriptBuf::from_hex("5120d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d").unwrap()
+    }
+
+    fn utxo(n: u8, value: u64) -> Utxo {
+        Utxo {
+            outpoint: OutPoint::new(Txid::from_byte_array([n; 32]), 0),
+            txout: TxOut { value: Amount::from_sat(value), script_pubkey: script() },
+            height: Some(100),
+        }
+    }
+
+    fn spend(inputs: &[Utxo], outputs: Vec<TxOut>) -> Transaction {
+        Transaction {
+            version: Version(2),
+            lock_time: LockTime::ZERO,
+            input: inputs.iter().map(|utxo| TxIn {
+                previous_output: utxo.outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ZERO,
+                witness: Witness::new(),
+            }).collect(),
+            output: outputs,
+        }
+    }
+
+    #[test]
+    fn test_reservation_locks_until_dropped() {
+        let ledger = UtxoLedger::new();
+        let utxos = vec![utxo(1, 10_000), utxo(2, 20_000)];
+
+        let reservation = ledger.reserve(&utxos[..1]).unwrap();
+        assert!(ledger.is_reserved(&utxos[0].outpoint));
+        assert!(ledger.reserve(&utxos).is_err());
+        // 失败的预留不锁定其他输入
+        assert!(!ledger.is_reserved(&utxos[1].outpoint));
+
+        let spendable = ledger.spendable(utxos.clone());
+        assert_eq!(spendable.len(), 1);
+        assert_eq!(spendable[0].outpoint, utxos[1].outpoint);
+
+        drop(reservation);
+        assert_eq!(ledger.spendable(utxos).len(), 2);
+    }
+
+    #[test]
+    fn test_broadcast_tracks_change() {
+        let ledger = UtxoLedger::new();
+        let funding = vec![utxo(1, 10_000)];
+        let other = ScriptBuf::from_hex("0014751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+
+        let tx = spend(&funding, vec![
+            TxOut { value: Amount::from_sat(3_000), script_pubkey: other },
+            TxOut { value: Amount::from_sat(6_000), script_pubkey: script() },
+        ]);
+        ledger.reserve(&funding).unwrap().complete(&tx, &script());
+
+        // 索引器还在返回已花费的输入，也还没有看到找零
+        let spendable = ledger.spendable(funding);
+        assert_eq!(spendable.len(), 1);
+        assert_eq!(spendable[0].outpoint, OutPoint::new(tx.txid(), 1));
+        assert_eq!(spendable[0].height, None);
+
+        // 后续操作花费找零后不再提供
+        let change = vec![spendable[0].clone()];
+        let next = spend(&change, vec![TxOut { value: Amount::from_sat(5_000), script_pubkey: script() }]);
+        ledger.reserve(&change).unwrap().complete(&next, &script());
+        let spendable = ledger.spendable(vec![]);
+        assert_eq!(spendable.len(), 1);
+        assert_eq!(spendable[0].outpoint, OutPoint::new(next.txid(), 0));
+
+        // 索引器返回找零后以索引器的记录为准
+        let mut indexed = spendable[0].clone();
+        indexed.height = Some(101);
+        let spendable = ledger.spendable(vec![indexed]);
+        assert_eq!(spendable.len(), 1);
+        assert_eq!(spendable[0].height, Some(101));
+        assert!(ledger.pending().is_empty());
+    }
+
+    #[test]
+    fn test_records_expire() {
+        let ledger = UtxoLedger::new().with_ttl_ms(1_000);
+        let utxos = vec![utxo(1, 10_000)];
+
+        let stale = ledger.reserve_at(&utxos, 0).unwrap();
+        assert!(ledger.reserve_at(&utxos, 999).is_err());
+        assert_eq!(ledger.spendable_at(utxos.clone(), 1_000).len(), 1);
+
+        // 超时后被重新预留，旧的预留释放时不影响新的预留
+        let fresh = ledger.reserve_at(&utxos, 1_000).unwrap();
+        drop(stale);
+        assert!(ledger.is_reserved(&utxos[0].outpoint));
+        drop(fresh);
+
+        let tx = spend(&utxos, vec![TxOut { value: Amount::from_sat(9_000), script_pubkey: script() }]);
+        ledger.record_broadcast(0, &[], &tx, &script(), 2_000);
+        assert_eq!(ledger.spendable_at(utxos.clone(), 2_500).len(), 1);
+        assert!(ledger.spendable_at(vec![], 3_000).is_empty());
+        assert_eq!(ledger.spendable_at(utxos, 3_000).len(), 1);
+    }
+}
@@ -1,4 +1,9 @@
 // pub mod common;
+pub mod dual;
+pub mod hd;
+pub mod ledger;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod storage;
 pub mod web;
 
 use async_trait::async_trait;
@@ -8,6 +13,12 @@ use crate::types::AtomicalsTx;
 use crate::errors::{Result, Error};
 use serde::{Serialize, Deserialize};
 
+pub use dual::DualWallet;
+pub use hd::HdWallet;
+pub use ledger::{UtxoLedger, UtxoReservation};
+#[cfg(not(target_arch = "wasm32"))]
+pub use storage::{load_wallet, WalletFile};
+
 #[derive(Debug, Clone)]
 pub struct Utxo {
     pub outpoint: OutPoint,
@@ -24,11 +35,27 @@ pub struct AtomicalLocation {
     pub address: String,
 }
 
+/// 钱包接口
+///
+/// 与 atomicals-js 一样区分两种角色：`get_address`/`get_public_key` 是持有 atomicals 的主（primary）密钥，
+/// `get_funding_address`/`get_funding_public_key` 是支付手续费、接收找零的资金（funding）密钥。
+/// `get_utxos` 返回资金地址的 UTXO。只有一个密钥的钱包不需要实现资金方法，默认与主密钥相同。
 #[async_trait(?Send)]
 pub trait WalletProvider {
     async fn get_network(&self) -> Result<Network>;
     async fn get_public_key(&self) -> Result<PublicKey>;
     async fn get_address(&self) -> Result<String>;
+
+    /// 资金地址，默认与主地址相同
+    async fn get_funding_address(&self) -> Result<String> {
+        self.get_address().await
+    }
+
+    /// 资金公钥，commit 输入和 reveal 脚本都使用这个密钥签名
+    async fn get_funding_public_key(&self) -> Result<PublicKey> {
+        self.get_public_key().await
+    }
+
     async fn sign_transaction(&self, tx: Transaction, input_txouts: &[TxOut]) -> Result<Transaction>;
     async fn broadcast_transaction(&self, tx: Transaction) -> Result<String>;
     async fn sign_psbt(&self, psbt: Psbt) -> Result<Psbt>;
@@ -41,6 +68,12 @@ pub trait WalletProvider {
     async fn get_balance(&self) -> Result<Amount> {
         Err(Error::WalletError("get_balance not implemented".to_string()))
     }
+
+    /// 铸造操作用来预留输入、登记找零的账本；返回 None 时每次操作使用独立的账本，
+    /// 并发操作之间不做协调
+    fn utxo_ledger(&self) -> Option<UtxoLedger> {
+        None
+    }
     
     // 获取网络费率（satoshis/vbyte）
     async fn get_network_fee_rate(&self) -> Result<f64> {
@@ -0,0 +1,447 @@
+use crate::errors::{Error, Result};
+use crate::wallet::dual::DualWallet;
+use crate::wallet::hd::{bip86_account_path, bip86_funding_path, bip86_path, generate_mnemonic, HdWallet};
+use bitcoin::bip32::{DerivationPath, Xpub};
+use bitcoin::Network;
+use ring::aead;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+
+/// 钱包文件格式版本
+pub const WALLET_FILE_VERSION: u32 = 1;
+
+/// 持有 atomicals 的主密钥标签
+pub const PRIMARY_KEY_LABEL: &str = "primary";
+
+/// 支付手续费的资金密钥标签，WIF 钱包没有单独的资金密钥
+pub const FUNDING_KEY_LABEL: &str = "funding";
+
+const KDF_SCRYPT: &str = "scrypt";
+const CIPHER_CHACHA20_POLY1305: &str = "chacha20-poly1305";
+
+/// scrypt 参数，`N = 2^log_n`，内存占用约为 `128 * r * N` 字节；要求 `log_n <= 20`、`r <= 32`、`p <= 16`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl Default for KdfParams {
+    // N = 2^15, r = 8：约 32 MiB 内存
+    fn default() -> Self {
+        Self { log_n: 15, r: 8, p: 1 }
+    }
+}
+
+/// 钱包中一个已命名的密钥，地址以明文保存，查看钱包信息时无需口令
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletKey {
+    pub label: String,
+    /// WIF 导入的密钥没有派生路径
+    pub path: Option<DerivationPath>,
+    pub address: String,
+}
+
+/// 加密保存的秘密类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretKind {
+    Mnemonic,
+    Wif,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EncryptedSecret {
+    kdf: String,
+    kdf_params: KdfParams,
+    salt: String,
+    cipher: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+// 加密前的明文，序列化为 JSON 后整体加密
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum WalletSecret {
+    Mnemonic {
+        phrase: String,
+        #[serde(default)]
+        passphrase: String,
+    },
+    Wif {
+        wif: String,
+    },
+}
+
+/// 口令加密的钱包文件
+///
+/// 助记词（或 WIF）用 scrypt 从口令派生的密钥以 ChaCha20-Poly1305 加密；
+/// 网络、标签和各密钥的派生路径、地址以明文保存。网络作为附加认证数据参与加密，
+/// 修改文件中的网络会导致解锁失败。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletFile {
+    pub version: u32,
+    pub network: Network,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub kind: SecretKind,
+    pub keys: Vec<WalletKey>,
+    pub created_at: i64,
+    crypto: EncryptedSecret,
+}
+
+impl WalletFile {
+    /// 生成新的 12 词助记词并加密保存，返回钱包文件和需要用户备份的助记词
+    pub fn create(network: Network, passphrase: &str, kdf: KdfParams) -> Result<(Self, String)> {
+        let phrase = generate_mnemonic(12)?.to_string();
+        let file = Self::import_mnemonic(&phrase, "", network, passphrase, kdf)?;
+        Ok((file, phrase))
+    }
+
+    /// 导入助记词；`bip39_passphrase` 为 BIP39 附加密码，与助记词一同加密，`passphrase` 为文件口令
+    ///
+    /// 按 atomicals-js 的约定保存主密钥 `.../0/0` 和资金密钥 `.../1/0`。
+    pub fn import_mnemonic(phrase: &str, bip39_passphrase: &str, network: Network, passphrase: &str, kdf: KdfParams) -> Result<Self> {
+        let mut keys = Vec::new();
+        for (label, path) in [
+            (PRIMARY_KEY_LABEL, bip86_path(network, 0, 0)?),
+            (FUNDING_KEY_LABEL, bip86_funding_path(network, 0)?),
+        ] {
+            let wallet = HdWallet::from_mnemonic_at_path(phrase, bip39_passphrase, network, path.clone())?;
+            keys.push(WalletKey {
+                label: label.into(),
+                path: Some(path),
+                address: wallet.address().to_string(),
+            });
+        }
+        let secret = WalletSecret::Mnemonic {
+            phrase: phrase.split_whitespace().collect::<Vec<_>>().join(" "),
+            passphrase: bip39_passphrase.to_string(),
+        };
+        Self::new(network, SecretKind::Mnemonic, keys, &secret, passphrase, kdf)
+    }
+
+    /// 导入单个 WIF 私钥
+    pub fn import_wif(wif: &str, network: Network, passphrase: &str, kdf: KdfParams) -> Result<Self> {
+        let wallet = HdWallet::from_wif(wif, network)?;
+        let key = WalletKey {
+            label: PRIMARY_KEY_LABEL.into(),
+            path: None,
+            address: wallet.address().to_string(),
+        };
+        let secret = WalletSecret::Wif { wif: wif.trim().to_string() };
+        Self::new(network, SecretKind::Wif, vec![key], &secret, passphrase, kdf)
+    }
+
+    fn new(network: Network, kind: SecretKind, keys: Vec<WalletKey>, secret: &WalletSecret, passphrase: &str, kdf: KdfParams) -> Result<Self> {
+        let mut file = Self {
+            version: WALLET_FILE_VERSION,
+            network,
+            label: None,
+            kind,
+            keys,
+            created_at: chrono::Utc::now().timestamp(),
+            crypto: EncryptedSecret {
+                kdf: KDF_SCRYPT.into(),
+                kdf_params: kdf,
+                salt: String::new(),
+                cipher: CIPHER_CHACHA20_POLY1305.into(),
+                nonce: String::new(),
+                ciphertext: String::new(),
+            },
+        };
+        file.encrypt(secret, passphrase, kdf)?;
+        Ok(file)
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// 读取钱包文件；不需要口令，可直接查看网络、标签和地址
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes = std::fs::read(path.as_ref()).map_err(Error::IoError)?;
+        let file: Self = serde_json::from_slice(&bytes)?;
+        if file.version != WALLET_FILE_VERSION {
+            return Err(Error::WalletError(format!("Unsupported wallet file version: {}", file.version)));
+        }
+        Ok(file)
+    }
+
+    /// 保存钱包文件（Unix 下权限为 0600），先写临时文件再重命名
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(Error::IoError)?;
+        }
+
+        let tmp = path.with_extension("tmp");
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut out = options.open(&tmp).map_err(Error::IoError)?;
+        out.write_all(&serde_json::to_vec_pretty(self)?).map_err(Error::IoError)?;
+        out.sync_all().map_err(Error::IoError)?;
+        std::fs::rename(&tmp, path).map_err(Error::IoError)
+    }
+
+    pub fn key(&self, label: &str) -> Option<&WalletKey> {
+        self.keys.iter().find(|key| key.label == label)
+    }
+
+    /// 解锁主密钥
+    pub fn unlock(&self, passphrase: &str) -> Result<HdWallet> {
+        self.unlock_key(passphrase, PRIMARY_KEY_LABEL)
+    }
+
+    /// 解锁指定标签的密钥
+    pub fn unlock_key(&self, passphrase: &str, label: &str) -> Result<HdWallet> {
+        self.wallet_for(&self.decrypt(passphrase)?, label)
+    }
+
+    /// 解锁主密钥和资金密钥；没有资金密钥时两种角色都使用主密钥
+    pub fn unlock_dual(&self, passphrase: &str) -> Result<DualWallet<HdWallet, HdWallet>> {
+        let secret = self.decrypt(passphrase)?;
+        let funding_label = if self.key(FUNDING_KEY_LABEL).is_some() { FUNDING_KEY_LABEL } else { PRIMARY_KEY_LABEL };
+        Ok(DualWallet::new(
+            self.wallet_for(&secret, PRIMARY_KEY_LABEL)?,
+            self.wallet_for(&secret, funding_label)?,
+        ))
+    }
+
+    fn wallet_for(&self, secret: &WalletSecret, label: &str) -> Result<HdWallet> {
+        let key = self.key(label)
+            .ok_or_else(|| Error::WalletError(format!("Wallet has no key labelled {}", label)))?;
+        let wallet = match (secret, &key.path) {
+            (WalletSecret::Mnemonic { phrase, passphrase }, Some(path)) => {
+                HdWallet::from_mnemonic_at_path(phrase, passphrase, self.network, path.clone())?
+            }
+            (WalletSecret::Wif { wif }, None) => HdWallet::from_wif(wif, self.network)?,
+            _ => return Err(Error::WalletError(format!("Key {} does not match the wallet secret", label))),
+        };
+
+        if wallet.address().to_string() != key.address {
+            return Err(Error::WalletError(format!("Key {} does not match its stored address", label)));
+        }
+        Ok(wallet)
+    }
+
+    /// 用新口令重新加密，同时更换盐和 nonce
+    pub fn change_passphrase(&mut self, old: &str, new: &str) -> Result<()> {
+        let secret = self.decrypt(old)?;
+        let kdf = self.crypto.kdf_params;
+        self.encrypt(&secret, new, kdf)
+    }
+
+    /// 导出 BIP86 账户扩展公钥 `m/86'/coin'/account'`，WIF 钱包没有扩展公钥
+    pub fn export_xpub(&self, passphrase: &str, account: u32) -> Result<Xpub> {
+        if self.kind != SecretKind::Mnemonic {
+            return Err(Error::WalletError("WIF wallets have no extended public key".into()));
+        }
+        self.unlock(passphrase)?.xpub_at(&bip86_account_path(self.network, account)?)
+    }
+
+    fn aad(&self) -> Vec<u8> {
+        format!("atomicals-wallet:v{}:{}", self.version, self.network).into_bytes()
+    }
+
+    fn encrypt(&mut self, secret: &WalletSecret, passphrase: &str, kdf: KdfParams) -> Result<()> {
+        if passphrase.is_empty() {
+            return Err(Error::InvalidInput("Wallet passphrase must not be empty".into()));
+        }
+
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; aead::NONCE_LEN];
+        getrandom::getrandom(&mut salt)
+            .and_then(|_| getrandom::getrandom(&mut nonce))
+            .map_err(|e| Error::WalletError(format!("Failed to generate randomness: {}", e)))?;
+
+        let key = cipher_key(passphrase, &salt, &kdf)?;
+        let mut ciphertext = serde_json::to_vec(secret)?;
+        key.seal_in_place_append_tag(aead::Nonce::assume_unique_for_key(nonce), aead::Aad::from(self.aad()), &mut ciphertext)
+            .map_err(|_| Error::WalletError("Failed to encrypt wallet secret".into()))?;
+
+        self.crypto = EncryptedSecret {
+            kdf: KDF_SCRYPT.into(),
+            kdf_params: kdf,
+            salt: hex::encode(salt),
+            cipher: CIPHER_CHACHA20_POLY1305.into(),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        };
+        Ok(())
+    }
+
+    fn decrypt(&self, passphrase: &str) -> Result<WalletSecret> {
+        if self.crypto.kdf != KDF_SCRYPT || self.crypto.cipher != CIPHER_CHACHA20_POLY1305 {
+            return Err(Error::WalletError(format!(
+                "Unsupported wallet encryption: {}/{}", self.crypto.kdf, self.crypto.cipher
+            )));
+        }
+
+        let salt = hex::decode(&self.crypto.salt)?;
+        let nonce = aead::Nonce::try_assume_unique_for_key(&hex::decode(&self.crypto.nonce)?)
+            .map_err(|_| Error::WalletError("Invalid wallet nonce".into()))?;
+        let mut buffer = hex::decode(&self.crypto.ciphertext)?;
+
+        let key = cipher_key(passphrase, &salt, &self.crypto.kdf_params)?;
+        let plaintext = key.open_in_place(nonce, aead::Aad::from(self.aad()), &mut buffer)
+            .map_err(|_| Error::WalletError("Wrong passphrase or corrupted wallet file".into()))?;
+        Ok(serde_json::from_slice(plaintext)?)
+    }
+}
+
+/// 读取并解锁钱包文件，返回区分主密钥和资金密钥的 `WalletProvider`
+pub fn load_wallet(path: impl AsRef<Path>, passphrase: &str) -> Result<DualWallet<HdWallet, HdWallet>> {
+    WalletFile::load(path)?.unlock_dual(passphrase)
+}
+
+fn cipher_key(passphrase: &str, salt: &[u8], kdf: &KdfParams) -> Result<aead::LessSafeKey> {
+    let mut key = [0u8; 32];
+    scrypt(passphrase.as_bytes(), salt, kdf, &mut key)?;
+    let key = aead::UnboundKey::new(&aead::CHACHA20_POLY1305, &key)
+        .map_err(|_| Error::WalletError("Invalid encryption key".into()))?;
+    Ok(aead::LessSafeKey::new(key))
+}
+
+/// 钱包文件中允许的 scrypt 参数上限，防止篡改过的文件在解锁时耗尽内存或 CPU
+const MAX_LOG_N: u8 = 20;
+const MAX_R: u32 = 32;
+const MAX_P: u32 = 16;
+
+/// RFC 7914 scrypt
+fn scrypt(password: &[u8], salt: &[u8], params: &KdfParams, output: &mut [u8]) -> Result<()> {
+    if params.log_n > MAX_LOG_N || params.r > MAX_R || params.p > MAX_P {
+        return Err(Error::WalletError(format!(
+            "scrypt parameters exceed limits (log_n <= {}, r <= {}, p <= {}): {:?}",
+            MAX_LOG_N, MAX_R, MAX_P, params
+        )));
+    }
+    let scrypt_params = scrypt::Params::new(params.log_n, params.r, params.p, output.len())
+        .map_err(|e| Error::WalletError(format!("Invalid scrypt parameters {:?}: {}", params, e)))?;
+    scrypt::scrypt(password, salt, &scrypt_params, output)
+        .map_err(|e| Error::WalletError(format!("scrypt failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::WalletProvider;
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    // 测试使用低成本参数
+    const FAST: KdfParams = KdfParams { log_n: 4, r: 8, p: 1 };
+
+    #[test]
+    fn test_scrypt_rfc7914_vectors() {
+        let mut out = [0u8; 64];
+        scrypt(b"", b"", &KdfParams { log_n: 4, r: 1, p: 1 }, &mut out).unwrap();
+        assert_eq!(
+            hex::encode(out),
+            "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906"
+        );
+
+        scrypt(b"password", b"NaCl", &KdfParams { log_n: 10, r: 8, p: 16 }, &mut out).unwrap();
+        assert_eq!(
+            hex::encode(out),
+            "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b3731622eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_save_load_unlock() {
+        let dir = std::env::temp_dir().join(format!("atomicals-wallet-test-{}", std::process::id()));
+        let path = dir.join("wallet.json");
+
+        let (file, phrase) = WalletFile::create(Network::Testnet, "correct horse", FAST).unwrap();
+        file.clone().with_label("test").save(&path).unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains(&phrase));
+
+        let loaded = WalletFile::load(&path).unwrap();
+        assert_eq!(loaded.label.as_deref(), Some("test"));
+        assert_eq!(loaded.network, Network::Testnet);
+        assert_eq!(loaded.keys, file.keys);
+
+        let wallet = load_wallet(&path, "correct horse").unwrap();
+        assert_eq!(wallet.primary().mnemonic().as_deref(), Some(phrase.as_str()));
+        assert_eq!(wallet.get_address().await.unwrap(), loaded.key(PRIMARY_KEY_LABEL).unwrap().address);
+        assert_eq!(wallet.get_funding_address().await.unwrap(), loaded.key(FUNDING_KEY_LABEL).unwrap().address);
+        assert!(matches!(loaded.unlock("wrong"), Err(Error::WalletError(_))));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_change_passphrase() {
+        let mut file = WalletFile::import_mnemonic(MNEMONIC, "", Network::Bitcoin, "old", FAST).unwrap();
+        let ciphertext = file.crypto.ciphertext.clone();
+
+        file.change_passphrase("old", "new").unwrap();
+        assert_ne!(file.crypto.ciphertext, ciphertext);
+        assert!(file.unlock("old").is_err());
+        assert_eq!(file.unlock("new").unwrap().address().to_string(), file.keys[0].address);
+        assert!(file.change_passphrase("old", "other").is_err());
+        assert!(file.change_passphrase("new", "").is_err());
+    }
+
+    #[test]
+    fn test_export_xpub() {
+        let file = WalletFile::import_mnemonic(MNEMONIC, "", Network::Bitcoin, "pass", FAST).unwrap();
+        assert_eq!(file.keys[0].address, "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr");
+        assert_eq!(file.keys[1].path.as_ref().unwrap().to_string(), "m/86'/0'/0'/1/0");
+        assert_eq!(
+            file.export_xpub("pass", 0).unwrap().to_string(),
+            "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ"
+        );
+    }
+
+    #[test]
+    fn test_import_wif() {
+        let secret = bitcoin::secp256k1::SecretKey::new(&mut rand::thread_rng());
+        let wif = bitcoin::PrivateKey::new(secret, Network::Regtest).to_wif();
+        let expected = HdWallet::from_wif(&wif, Network::Regtest).unwrap().address();
+
+        let file = WalletFile::import_wif(&wif, Network::Regtest, "pass", FAST).unwrap();
+        assert_eq!(file.kind, SecretKind::Wif);
+        assert_eq!(file.keys[0].path, None);
+        assert_eq!(file.keys[0].address, expected.to_string());
+        assert_eq!(file.unlock("pass").unwrap().address(), expected);
+        assert!(file.export_xpub("pass", 0).is_err());
+
+        // 没有资金密钥时两种角色共用一个密钥
+        let dual = file.unlock_dual("pass").unwrap();
+        assert_eq!(dual.funding().address(), expected);
+    }
+
+    #[test]
+    fn test_tampered_network_fails_to_unlock() {
+        let mut file = WalletFile::import_mnemonic(MNEMONIC, "", Network::Testnet, "pass", FAST).unwrap();
+        file.network = Network::Signet;
+        assert!(matches!(file.unlock("pass"), Err(Error::WalletError(_))));
+    }
+
+    #[test]
+    fn test_oversized_kdf_params_rejected() {
+        let file = WalletFile::import_mnemonic(MNEMONIC, "", Network::Testnet, "pass", FAST).unwrap();
+        for kdf_params in [
+            KdfParams { log_n: 40, ..FAST },
+            KdfParams { r: 1 << 20, ..FAST },
+            KdfParams { p: u32::MAX, ..FAST },
+        ] {
+            let mut tampered = file.clone();
+            tampered.crypto.kdf_params = kdf_params;
+            assert!(matches!(tampered.unlock("pass"), Err(Error::WalletError(_))));
+        }
+
+        // 创建时同样拒绝超出上限的参数
+        assert!(WalletFile::import_mnemonic(MNEMONIC, "", Network::Testnet, "pass", KdfParams { log_n: 21, ..FAST }).is_err());
+    }
+}
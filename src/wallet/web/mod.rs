@@ -1,3 +1,4 @@
+pub mod network_api;
 pub mod unisat;
 pub mod wizz;
 
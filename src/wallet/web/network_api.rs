@@ -0,0 +1,107 @@
+use crate::api::{AtomicalsApi, ElectrumApi};
+use crate::errors::{Error, Result};
+use crate::types::AtomicalsNetwork;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// 按钱包扩展所在的网络选择 Atomicals 索引器
+///
+/// 通过 `configure` 指定的索引器只服务于对应的网络，钱包切换到其他网络时返回错误，
+/// 避免用错误网络的索引器过滤 UTXO 或估算费率。未指定时按钱包的网络使用公共
+/// ElectrumX 代理，第一次使用时创建并缓存。
+#[derive(Default)]
+pub(crate) struct NetworkApi {
+    configured: Option<(AtomicalsNetwork, Rc<dyn AtomicalsApi>)>,
+    public: RefCell<Option<(AtomicalsNetwork, Rc<dyn AtomicalsApi>)>>,
+}
+
+impl NetworkApi {
+    /// 将索引器绑定到 `network`
+    pub(crate) fn configure(&mut self, network: AtomicalsNetwork, api: Rc<dyn AtomicalsApi>) {
+        self.configured = Some((network, api));
+    }
+
+    /// 钱包处于 `network` 时使用的索引器
+    pub(crate) fn for_network(&self, network: AtomicalsNetwork) -> Result<Rc<dyn AtomicalsApi>> {
+        if let Some((configured, api)) = &self.configured {
+            if *configured != network {
+                return Err(Error::NetworkError(format!(
+                    "Atomicals API is configured for {} but the wallet is on {}", configured, network
+                )));
+            }
+            return Ok(api.clone());
+        }
+
+        let mut public = self.public.borrow_mut();
+        if let Some((cached, api)) = public.as_ref() {
+            if *cached == network {
+                return Ok(api.clone());
+            }
+        }
+        let api: Rc<dyn AtomicalsApi> = Rc::new(ElectrumApi::for_network(network).ok_or_else(|| {
+            Error::NetworkError(format!("No public Atomicals API for {}, call setNetwork with an API URL", network))
+        })?);
+        *public = Some((network, api.clone()));
+        Ok(api)
+    }
+}
+
+/// 钱包报告的网络与配置的网络地址编码相同时使用配置的网络
+///
+/// 钱包扩展报告的 `testnet` 不区分 testnet3 和 testnet4，两者地址编码相同。
+pub(crate) fn resolve_network(reported: AtomicalsNetwork, configured: AtomicalsNetwork) -> AtomicalsNetwork {
+    if reported.bitcoin_network() == configured.bitcoin_network() {
+        configured
+    } else {
+        reported
+    }
+}
+
+// 指定了代理地址时使用该地址，否则使用网络的公共代理；费率来源随网络
+pub(crate) fn network_api(network: AtomicalsNetwork, api_url: Option<String>) -> Result<ElectrumApi> {
+    match api_url {
+        Some(url) => Ok(match network.mempool_url() {
+            Some(mempool_url) => ElectrumApi::new(url).with_mempool_url(mempool_url),
+            None => ElectrumApi::new(url),
+        }),
+        None => ElectrumApi::for_network(network)
+            .ok_or_else(|| Error::NetworkError(format!("No public Atomicals API for {}, an API URL is required", network))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::MockAtomicalsApi;
+
+    #[test]
+    fn test_configured_api_rejects_other_network() {
+        let mut apis = NetworkApi::default();
+        let mock: Rc<dyn AtomicalsApi> = Rc::new(MockAtomicalsApi::new());
+        apis.configure(AtomicalsNetwork::Testnet4, mock.clone());
+
+        assert!(Rc::ptr_eq(&apis.for_network(AtomicalsNetwork::Testnet4).unwrap(), &mock));
+        assert!(matches!(apis.for_network(AtomicalsNetwork::Mainnet), Err(Error::NetworkError(_))));
+    }
+
+    #[test]
+    fn test_public_api_follows_wallet_network() {
+        let apis = NetworkApi::default();
+        let mainnet = apis.for_network(AtomicalsNetwork::Mainnet).unwrap();
+        assert!(Rc::ptr_eq(&mainnet, &apis.for_network(AtomicalsNetwork::Mainnet).unwrap()));
+
+        let testnet4 = apis.for_network(AtomicalsNetwork::Testnet4).unwrap();
+        assert!(!Rc::ptr_eq(&mainnet, &testnet4));
+
+        // 没有公共代理的网络必须先配置地址
+        assert!(matches!(apis.for_network(AtomicalsNetwork::Regtest), Err(Error::NetworkError(_))));
+    }
+
+    #[test]
+    fn test_resolve_reported_network() {
+        // 扩展报告 testnet，配置为 testnet4 时使用 testnet4 的索引器
+        assert_eq!(resolve_network(AtomicalsNetwork::Testnet, AtomicalsNetwork::Testnet4), AtomicalsNetwork::Testnet4);
+        assert_eq!(resolve_network(AtomicalsNetwork::Mainnet, AtomicalsNetwork::Testnet4), AtomicalsNetwork::Mainnet);
+        assert_eq!(resolve_network(AtomicalsNetwork::Mainnet, AtomicalsNetwork::Mainnet), AtomicalsNetwork::Mainnet);
+    }
+}
@@ -1,22 +1,45 @@
 use crate::errors::{Error, Result};
-use crate::wallet::WalletProvider;
-use crate::types::atomicals::{AtomicalInfo, LocationInfo, AtomicalState};
+use crate::api::{AtomicalsApi, ElectrumApi};
+use crate::wallet::web::network_api::{network_api, resolve_network, NetworkApi};
+use crate::wallet::{Utxo, UtxoLedger, WalletProvider};
+use crate::types::atomicals::AtomicalInfo;
+use crate::types::AtomicalsNetwork;
 use async_trait::async_trait;
-use bitcoin::{Transaction, TxOut, Network, PublicKey};
+use bitcoin::{Amount, OutPoint, Transaction, TxOut, Network, PublicKey, ScriptBuf, Txid};
 use bitcoin::psbt::Psbt;
 use wasm_bindgen::prelude::*;
 use js_sys::{Function, Object, Promise, Reflect, Array};
+use wasm_bindgen_futures::JsFuture;
+use serde::{Deserialize, Serialize};
 use serde_wasm_bindgen::{to_value, from_value};
-use serde::{Deserialize};
-use serde_json::Value;
+use std::rc::Rc;
 use std::str::FromStr;
-use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
-use reqwest;
 
+#[cfg(target_arch = "wasm32")]
+macro_rules! log {
+    ($($t:tt)*) => (web_sys::console::log_1(&format!($($t)*).into()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+macro_rules! log {
+    ($($t:tt)*) => (log::info!($($t)*))
+}
+
+/// `getBitcoinUtxos` 每页数量
+const UTXO_PAGE_SIZE: u32 = 100;
+
+/// Unisat 钱包扩展
+///
+/// 签名、广播和 UTXO 列表走注入的 `window.unisat`；Unisat 不索引 atomicals，
+/// 因此 UTXO 要再经过 Atomicals 索引器过滤，atomical 信息和费率也来自索引器。
+/// 索引器按钱包所在的网络选择，配置的索引器与钱包网络不一致时拒绝查询。
 #[wasm_bindgen]
-#[derive(Debug)]
 pub struct UnisatProvider {
     wallet: Object,
+    /// `setApiUrl` 绑定的网络，旧版本扩展只报告 `testnet` 时也以它区分 testnet3 和 testnet4
+    network: AtomicalsNetwork,
+    apis: NetworkApi,
+    ledger: UtxoLedger,
 }
 
 #[wasm_bindgen]
@@ -27,130 +50,392 @@ impl UnisatProvider {
             .ok_or_else(|| JsValue::from_str("Window not available"))?;
         let unisat = Reflect::get(&window, &"unisat".into())
             .map_err(|_| JsValue::from_str("Unisat wallet not found"))?;
-        
+        if unisat.is_undefined() {
+            return Err(JsValue::from_str("Unisat wallet not found"));
+        }
+
         Ok(UnisatProvider {
             wallet: unisat.unchecked_into(),
+            network: AtomicalsNetwork::Testnet4,
+            apis: NetworkApi::default(),
+            ledger: UtxoLedger::new(),
+        })
+    }
+
+    /// 更换当前网络（默认 testnet4，可用 `setNetwork` 切换）的 ElectrumX 代理地址，
+    /// `mempool_url` 为空时通过 ElectrumX 估算费率
+    #[wasm_bindgen(js_name = setApiUrl)]
+    pub fn set_api_url(&mut self, url: String, mempool_url: Option<String>) {
+        let api = match mempool_url {
+            Some(mempool_url) => ElectrumApi::new(url).with_mempool_url(mempool_url),
+            None => ElectrumApi::new(url),
+        };
+        self.apis.configure(self.network, Rc::new(api));
+    }
+
+    /// 使用网络对应的索引器；`api_url` 为空时使用该网络的公共 ElectrumX 代理
+    #[wasm_bindgen(js_name = setNetwork)]
+    pub fn set_network(&mut self, network: AtomicalsNetwork, api_url: Option<String>) -> std::result::Result<(), JsValue> {
+        let api = network_api(network, api_url).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.apis.configure(network, Rc::new(api));
+        self.network = network;
+        Ok(())
+    }
+}
+
+impl UnisatProvider {
+    /// 在 `network` 上使用其他索引器，例如自建服务或测试用的模拟实现
+    pub fn with_api(mut self, network: AtomicalsNetwork, api: impl AtomicalsApi + 'static) -> Self {
+        self.apis.configure(network, Rc::new(api));
+        self.network = network;
+        self
+    }
+
+    /// 钱包扩展当前网络对应的索引器
+    async fn api(&self) -> Result<Rc<dyn AtomicalsApi>> {
+        self.apis.for_network(self.atomicals_network().await?)
+    }
+
+    async fn atomicals_network(&self) -> Result<AtomicalsNetwork> {
+        // 新版本通过 getChain 区分 testnet 和 testnet4，旧版本只有 getNetwork
+        match self.call_async("getChain", &[]).await {
+            Ok(chain) => from_value::<UnisatChain>(chain)?.chain.parse(),
+            Err(_) => {
+                let name = from_value::<String>(self.call_async("getNetwork", &[]).await?)?;
+                Ok(resolve_network(name.parse()?, self.network))
+            }
+        }
+    }
+}
+
+/// `getBitcoinUtxos` 返回的 UTXO
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UnisatUtxo {
+    txid: String,
+    vout: u32,
+    satoshis: u64,
+    script_pk: String,
+}
+
+impl UnisatUtxo {
+    fn into_utxo(self) -> Result<Utxo> {
+        let txid = Txid::from_str(&self.txid)
+            .map_err(|e| Error::TransactionError(format!("Invalid txid: {}", e)))?;
+        let script_pubkey = ScriptBuf::from_hex(&self.script_pk)
+            .map_err(|e| Error::ScriptError(format!("Invalid scriptPk: {}", e)))?;
+        Ok(Utxo {
+            outpoint: OutPoint::new(txid, self.vout),
+            txout: TxOut { value: Amount::from_sat(self.satoshis), script_pubkey },
+            height: None,
         })
     }
 }
 
+/// `getChain` 的结果，`enum` 形如 `BITCOIN_TESTNET4`
+#[derive(Debug, Deserialize)]
+struct UnisatChain {
+    #[serde(rename = "enum")]
+    chain: String,
+}
+
+/// `signPsbt` 的选项
+///
+/// 指定 `toSignInputs` 后 Unisat 只签列出的输入：本地址的 key path 输入按地址签名，
+/// 带有 tap 脚本的输入（例如花费 commit 输出的 reveal 输入）用未调整的公钥按 script path 签名。
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SignPsbtOptions {
+    auto_finalized: bool,
+    to_sign_inputs: Vec<ToSignInput>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToSignInput {
+    index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    public_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    disable_tweak_signer: Option<bool>,
+}
+
+impl SignPsbtOptions {
+    fn new(psbt: &Psbt, address: &str, script_pubkey: &ScriptBuf, public_key: &str) -> Self {
+        let to_sign_inputs = psbt.inputs.iter().enumerate()
+            .filter_map(|(index, input)| {
+                if !input.tap_scripts.is_empty() {
+                    Some(ToSignInput {
+                        index,
+                        address: None,
+                        public_key: Some(public_key.to_string()),
+                        disable_tweak_signer: Some(true),
+                    })
+                } else if input.witness_utxo.as_ref().is_some_and(|txout| &txout.script_pubkey == script_pubkey) {
+                    Some(ToSignInput { index, address: Some(address.to_string()), public_key: None, disable_tweak_signer: None })
+                } else {
+                    None
+                }
+            })
+            .collect();
+        Self { auto_finalized: true, to_sign_inputs }
+    }
+}
+
 #[async_trait(?Send)]
 impl WalletProvider for UnisatProvider {
     async fn get_network(&self) -> Result<Network> {
-        let network = self.call_wallet_method("getNetwork", &[])?;
-        let network_str: String = from_value(network)?;
-        
-        match network_str.as_str() {
-            "mainnet" => Ok(Network::Bitcoin),
-            "testnet" => Ok(Network::Testnet),
-            _ => Err(Error::NetworkError(format!("Unsupported network: {}", network_str))),
-        }
+        Ok(self.atomicals_network().await?.bitcoin_network())
     }
 
     async fn get_public_key(&self) -> Result<PublicKey> {
-        let pubkey = self.call_wallet_method("getPublicKey", &[])?;
-        let pubkey_str: String = from_value(pubkey)?;
-        
+        let pubkey_str: String = from_value(self.call_async("getPublicKey", &[]).await?)?;
         PublicKey::from_str(&pubkey_str)
             .map_err(|e| Error::WalletError(format!("Invalid public key: {}", e)))
     }
 
     async fn get_address(&self) -> Result<String> {
-        let address = self.call_wallet_method("getAddress", &[])?;
-        from_value(address)
-            .map_err(|e| Error::WalletError(format!("Failed to get address: {}", e)))
+        let mut accounts: Vec<String> = from_value(self.call_async("getAccounts", &[]).await?)?;
+        if accounts.is_empty() {
+            accounts = from_value(self.call_async("requestAccounts", &[]).await?)?;
+        }
+        accounts.into_iter().next()
+            .ok_or_else(|| Error::WalletError("No accounts available".to_string()))
     }
 
-    async fn sign_transaction(&self, tx: Transaction, outputs: &[TxOut]) -> Result<Transaction> {
-        let tx_hex = bitcoin::consensus::encode::serialize_hex(&tx);
-        
-        let args = vec![
-            to_value(&tx_hex)?,
-            to_value(&outputs)?,
-        ];
-        
-        let result = self.call_wallet_method("signTransaction", &args)?;
-        
-        let signed_tx_hex: String = from_value(result)?;
-        let tx_bytes = hex::decode(signed_tx_hex)?;
-        let signed_tx = bitcoin::consensus::encode::deserialize(&tx_bytes)?;
-        
-        Ok(signed_tx)
+    /// 按给定输出构造 PSBT 交给钱包签名
+    async fn sign_transaction(&self, tx: Transaction, input_txouts: &[TxOut]) -> Result<Transaction> {
+        if input_txouts.len() != tx.input.len() {
+            return Err(Error::TransactionError("Missing previous outputs for signing".into()));
+        }
+        let mut psbt = Psbt::from_unsigned_tx(tx)
+            .map_err(|e| Error::PsbtError(format!("Failed to create PSBT: {}", e)))?;
+        for (input, txout) in psbt.inputs.iter_mut().zip(input_txouts) {
+            input.witness_utxo = Some(txout.clone());
+        }
+        self.sign_psbt(psbt).await?.extract_tx()
+            .map_err(|e| Error::TransactionError(format!("Failed to extract signed tx: {}", e)))
     }
 
     async fn broadcast_transaction(&self, tx: Transaction) -> Result<String> {
         let tx_hex = bitcoin::consensus::encode::serialize_hex(&tx);
-        let result = self.call_wallet_method("broadcastTransaction", &[to_value(&tx_hex)?])?;
-        
+        let request = Object::new();
+        Reflect::set(&request, &"rawtx".into(), &tx_hex.into())
+            .map_err(|e| Error::WalletError(format!("Failed to build pushTx request: {:?}", e)))?;
+
+        let result = self.call_async("pushTx", &[request.into()]).await?;
         from_value(result)
             .map_err(|e| Error::WalletError(format!("Failed to broadcast transaction: {}", e)))
     }
 
     async fn sign_psbt(&self, psbt: Psbt) -> Result<Psbt> {
-        let psbt_bytes = psbt.serialize();
-        let psbt_base64 = BASE64.encode(psbt_bytes);
-        
-        let result = self.call_wallet_method("signPsbt", &[to_value(&psbt_base64)?])?;
-        
-        let signed_psbt_str: String = from_value(result)?;
-        let signed_psbt_bytes = BASE64.decode(signed_psbt_str.as_bytes())
-            .map_err(|e| Error::WalletError(format!("Failed to decode base64 PSBT: {}", e)))?;
-        
-        Psbt::deserialize(&signed_psbt_bytes)
-            .map_err(|e| Error::WalletError(format!("Failed to parse signed PSBT: {}", e)))
-    }
+        let address = self.get_address().await?;
+        let script_pubkey = self.atomicals_network().await?.parse_address(&address)?.script_pubkey();
+        let public_key: String = from_value(self.call_async("getPublicKey", &[]).await?)?;
+        let options = SignPsbtOptions::new(&psbt, &address, &script_pubkey, &public_key);
 
-    async fn get_atomical_by_id(&self, atomical_id: &str) -> Result<AtomicalInfo> {
-        // 使用 UniSat 的 API 获取 Atomical 信息
-        let url = format!(
-            "https://testnet.unisat.io/api/v1/atomical/{}",
-            atomical_id
-        );
-
-        let response = reqwest::get(&url).await
-            .map_err(|e| Error::NetworkError(format!("Failed to fetch atomical info: {}", e)))?;
-            
-        let unisat_response: UnisatResponse = response.json().await
-            .map_err(|e| Error::DeserializationError(format!("Failed to deserialize response: {}", e)))?;
-
-        if unisat_response.code != 0 {
-            return Err(Error::AtomicalNotFound(format!("Atomical {} not found: {}", atomical_id, unisat_response.msg)));
-        }
+        let result = self.call_async("signPsbt", &[to_value(&psbt.serialize_hex())?, to_value(&options)?]).await?;
+        let signed_psbt_hex: String = from_value(result)
+            .map_err(|e| Error::WalletError(format!("Failed to parse signed PSBT result: {}", e)))?;
+        let signed_psbt_bytes = hex::decode(&signed_psbt_hex)
+            .map_err(|e| Error::WalletError(format!("Failed to decode hex PSBT: {}", e)))?;
 
-        // 将 UniSat 的响应格式转换为我们的 AtomicalInfo 格式
-        let mut atomical_info: AtomicalInfo = serde_json::from_value(unisat_response.data)
-            .map_err(|e| Error::DeserializationError(format!("Failed to convert response format: {}", e)))?;
+        let signed_psbt = Psbt::deserialize(&signed_psbt_bytes)
+            .map_err(|e| Error::WalletError(format!("Failed to parse signed PSBT: {}", e)))?;
 
-        // 确保 location 字段格式正确
-        if let Some(location) = atomical_info.location_info.first_mut() {
-            if !location.location.contains(':') {
-                location.location = format!("{}:{}", location.txid, location.index);
-            }
+        // 钱包跳过的输入不会报错，广播前在这里拦下
+        if signed_psbt.inputs.iter().any(|input| input.final_script_sig.is_none() && input.final_script_witness.is_none()) {
+            return Err(Error::WalletError("PSBT signing incomplete".into()));
         }
+        Ok(signed_psbt)
+    }
+
+    async fn get_utxos(&self) -> Result<Vec<Utxo>> {
+        let address = self.get_address().await?;
+        let network = self.atomicals_network().await?;
+        spendable_utxos(&self.apis, network, &address, self.wallet_utxos().await).await
+    }
+
+    /// 可用于付费的余额，不含带有 atomicals 的 UTXO
+    async fn get_balance(&self) -> Result<Amount> {
+        Ok(self.get_utxos().await?.iter().map(|utxo| utxo.txout.value).sum())
+    }
+
+    fn utxo_ledger(&self) -> Option<UtxoLedger> {
+        Some(self.ledger.clone())
+    }
 
-        Ok(atomical_info)
+    async fn get_network_fee_rate(&self) -> Result<f64> {
+        self.api().await?.fee_rate().await
+    }
+
+    async fn get_atomical_by_id(&self, atomical_id: &str) -> Result<AtomicalInfo> {
+        self.api().await?.get_state(atomical_id).await
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct UnisatResponse {
-    code: i32,
-    msg: String,
-    data: Value,
+/// 钱包的 UTXO 中去掉 `network` 上的索引器记录为带有 atomicals 的部分；
+/// 钱包不支持 `getBitcoinUtxos` 时直接使用索引器的列表
+async fn spendable_utxos(
+    apis: &NetworkApi,
+    network: AtomicalsNetwork,
+    address: &str,
+    wallet_utxos: Result<Vec<Utxo>>,
+) -> Result<Vec<Utxo>> {
+    let api = apis.for_network(network)?;
+    let script_pubkey = network.parse_address(address)?.script_pubkey();
+
+    let utxos = match wallet_utxos {
+        Ok(utxos) => utxos,
+        Err(e) => {
+            log!("getBitcoinUtxos unavailable, using indexer UTXOs: {}", e);
+            return api.list_utxos(&script_pubkey).await;
+        }
+    };
+    // 只用本地址的 UTXO 付费，其他地址的输出无法用资金密钥签名
+    let utxos = utxos.into_iter().filter(|utxo| utxo.txout.script_pubkey == script_pubkey).collect();
+    api.exclude_atomicals(&script_pubkey, utxos).await
 }
 
 impl UnisatProvider {
+    // 按页读取 getBitcoinUtxos
+    async fn wallet_utxos(&self) -> Result<Vec<Utxo>> {
+        let mut utxos = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let page: Vec<UnisatUtxo> = from_value(
+                self.call_async("getBitcoinUtxos", &[cursor.into(), UTXO_PAGE_SIZE.into()]).await?,
+            )?;
+            let count = page.len() as u32;
+            for item in page {
+                utxos.push(item.into_utxo()?);
+            }
+            if count < UTXO_PAGE_SIZE {
+                return Ok(utxos);
+            }
+            cursor += count;
+        }
+    }
+
     fn call_wallet_method(&self, method: &str, args: &[JsValue]) -> Result<JsValue> {
         let method_fn = Reflect::get(&self.wallet, &method.into())
             .map_err(|_| Error::WalletError(format!("Method {} not found", method)))?;
-        
+        if !method_fn.is_function() {
+            return Err(Error::WalletError(format!("Method {} not found", method)));
+        }
+
         let method_fn: Function = method_fn.unchecked_into();
         let args_array = Array::new();
         for arg in args {
             args_array.push(arg);
         }
-        
+
         method_fn.apply(&self.wallet, &args_array)
             .map_err(|e| Error::WalletError(format!("Failed to call {}: {:?}", method, e)))
     }
+
+    // Unisat 的方法都返回 Promise
+    async fn call_async(&self, method: &str, args: &[JsValue]) -> Result<JsValue> {
+        let result = self.call_wallet_method(method, args)?;
+        if !result.has_type::<Promise>() {
+            return Ok(result);
+        }
+        JsFuture::from(result.unchecked_into::<Promise>()).await
+            .map_err(|e| Error::WalletError(format!("{} failed: {:?}", method, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{scripthash, MockAtomicalsApi};
+    use bitcoin::hashes::Hash;
+    use bitcoin::taproot::{LeafVersion, TaprootBuilder};
+    use bitcoin::{Address, XOnlyPublicKey};
+
+    const SCRIPT_PK: &str = "5120d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d";
+
+    fn wallet_utxo(outpoint: OutPoint, script_pubkey: &ScriptBuf) -> Utxo {
+        Utxo { outpoint, txout: TxOut { value: Amount::from_sat(5000), script_pubkey: script_pubkey.clone() }, height: None }
+    }
+
+    #[tokio::test]
+    async fn test_spendable_utxos_follow_wallet_network() {
+        let script_pubkey = ScriptBuf::from_hex(SCRIPT_PK).unwrap();
+        let api = MockAtomicalsApi::new();
+        let funding = api.fund(&script_pubkey, Amount::from_sat(5000));
+        api.add_realm("example", &script_pubkey, Amount::from_sat(1000));
+        let listing = api.list_scripthash(&scripthash(&script_pubkey)).await.unwrap();
+        let realm = listing.utxos.iter().find(|utxo| utxo.has_atomicals()).unwrap();
+        let realm = OutPoint::new(Txid::from_str(&realm.txid).unwrap(), realm.vout);
+        let wallet_utxos = || Ok(vec![wallet_utxo(funding, &script_pubkey), wallet_utxo(realm, &script_pubkey)]);
+
+        let mut apis = NetworkApi::default();
+        apis.configure(AtomicalsNetwork::Testnet4, Rc::new(api));
+
+        // 钱包与索引器在同一网络：去掉带有 atomicals 的 UTXO
+        let testnet = Address::from_script(&script_pubkey, Network::Testnet).unwrap().to_string();
+        let utxos = spendable_utxos(&apis, AtomicalsNetwork::Testnet4, &testnet, wallet_utxos()).await.unwrap();
+        assert_eq!(utxos.iter().map(|utxo| utxo.outpoint).collect::<Vec<_>>(), vec![funding]);
+
+        // 钱包切换到主网：不能用 testnet4 的索引器过滤主网 UTXO
+        let mainnet = Address::from_script(&script_pubkey, Network::Bitcoin).unwrap().to_string();
+        let result = spendable_utxos(&apis, AtomicalsNetwork::Mainnet, &mainnet, wallet_utxos()).await;
+        assert!(matches!(result, Err(Error::NetworkError(_))));
+    }
+
+    #[test]
+    fn test_sign_options_include_script_path_inputs() {
+        let script_pubkey = ScriptBuf::from_hex(SCRIPT_PK).unwrap();
+        let address = Address::from_script(&script_pubkey, Network::Testnet).unwrap().to_string();
+        let input = |vout| bitcoin::TxIn { previous_output: OutPoint::new(Txid::all_zeros(), vout), ..Default::default() };
+        let tx = Transaction {
+            version: bitcoin::transaction::Version(2),
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![input(0), input(1), input(2)],
+            output: vec![],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        // reveal 输入花费 commit 的脚本路径输出，地址不是钱包地址
+        let leaf_script = ScriptBuf::from_bytes(vec![0x51]);
+        let internal_key = XOnlyPublicKey::from_slice(&script_pubkey.as_bytes()[2..]).unwrap();
+        let control_block = TaprootBuilder::new().add_leaf(0, leaf_script.clone()).unwrap()
+            .finalize(&bitcoin::secp256k1::Secp256k1::new(), internal_key).unwrap()
+            .control_block(&(leaf_script.clone(), LeafVersion::TapScript)).unwrap();
+        psbt.inputs[0].tap_scripts.insert(control_block, (leaf_script, LeafVersion::TapScript));
+        psbt.inputs[1].witness_utxo = Some(TxOut { value: Amount::from_sat(1000), script_pubkey: script_pubkey.clone() });
+        psbt.inputs[2].witness_utxo = Some(TxOut { value: Amount::from_sat(1000), script_pubkey: ScriptBuf::from_bytes(vec![0x51]) });
+
+        let options = SignPsbtOptions::new(&psbt, &address, &script_pubkey, "02aa");
+        assert_eq!(serde_json::to_value(&options).unwrap(), serde_json::json!({
+            "autoFinalized": true,
+            "toSignInputs": [
+                { "index": 0, "publicKey": "02aa", "disableTweakSigner": true },
+                { "index": 1, "address": address },
+            ],
+        }));
+    }
+
+    #[test]
+    fn test_parse_unisat_utxo() {
+        let item: UnisatUtxo = serde_json::from_value(serde_json::json!({
+            "txid": "1111111111111111111111111111111111111111111111111111111111111111",
+            "vout": 1,
+            "satoshis": 5000,
+            "scriptPk": "5120d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d",
+            "addressType": 2,
+            "inscriptions": [],
+        })).unwrap();
+        let utxo = item.into_utxo().unwrap();
+        assert_eq!(utxo.outpoint.vout, 1);
+        assert_eq!(utxo.txout.value, Amount::from_sat(5000));
+        assert!(utxo.txout.script_pubkey.is_p2tr());
+
+        let chain: UnisatChain = serde_json::from_value(serde_json::json!({
+            "enum": "BITCOIN_TESTNET4", "name": "Bitcoin Testnet4", "network": "testnet",
+        })).unwrap();
+        assert_eq!(chain.chain.parse::<AtomicalsNetwork>().unwrap(), AtomicalsNetwork::Testnet4);
+    }
 }
@@ -1,79 +1,30 @@
 use crate::errors::{Error, Result};
-use crate::wallet::{WalletProvider, Utxo};
-use crate::types::atomicals::{AtomicalInfo, AtomicalResponse, AtomicalResponseData};
+use crate::wallet::{WalletProvider, Utxo, UtxoLedger};
+use crate::api::{AtomicalsApi, ElectrumApi};
+use crate::wallet::web::network_api::{network_api, resolve_network, NetworkApi};
+use crate::types::atomicals::AtomicalInfo;
+use crate::types::AtomicalsNetwork;
 use async_trait::async_trait;
-use bitcoin::{Transaction, TxOut, Network, PublicKey, Amount, OutPoint, Psbt, Address, Txid};
+use bitcoin::{Transaction, TxOut, Network, PublicKey, Amount, Psbt};
 use wasm_bindgen::prelude::*;
 use js_sys::{Function, Object, Promise, Reflect, Array};
 use serde_wasm_bindgen::{to_value, from_value};
+use std::rc::Rc;
 use std::str::FromStr;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use web_sys::{window, console};
 use wasm_bindgen_futures::JsFuture;
-use serde::{Deserialize, Serialize};
-use reqwest;
-use serde_json::Value;
-use bitcoin::hashes::{sha256, Hash};
 use hex;
 
 
-#[derive(Debug, Deserialize)]
-struct UtxoResponse {
-    success: bool,
-    response: ResponseData,
-    #[serde(default)]
-    cache: bool,
-}
-
-#[derive(Debug, Deserialize)]
-struct ResponseData {
-    atomicals: Value,
-    global: GlobalInfo,
-    utxos: Vec<UtxoItem>,
-}
-
-#[derive(Debug, Deserialize)]
-struct GlobalInfo {
-    atomical_count: u32,
-    height: u32,
-    network: String,
-    server_time: String,
-    #[serde(flatten)]
-    other: Value,
-}
-
-#[derive(Debug, Deserialize)]
-struct UtxoItem {
-    height: u32,
-    txid: String,
-    value: u64,
-    vout: u32,
-    index: u32,
-    #[serde(default)]
-    atomicals: Value,
-}
-
-#[derive(Debug, Deserialize)]
-struct MempoolBlock {
-    #[serde(rename = "blockSize")]
-    block_size: f64,
-    #[serde(rename = "blockVSize")]
-    block_vsize: f64,
-    #[serde(rename = "nTx")]
-    n_tx: u32,
-    #[serde(rename = "totalFees")]
-    total_fees: f64,
-    #[serde(rename = "medianFee")]
-    median_fee: f64,
-    #[serde(rename = "feeRange")]
-    fee_range: Vec<f64>,
-}
-
 #[wasm_bindgen]
-#[derive(Debug)]
 pub struct WizzProvider {
     wallet: Object,
     account: Option<String>,
+    /// 钱包扩展不报告网络时使用
+    network: AtomicalsNetwork,
+    apis: NetworkApi,
+    ledger: UtxoLedger,
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -97,13 +48,48 @@ impl WizzProvider {
         Ok(WizzProvider {
             wallet: wizz.unchecked_into(),
             account: None,
+            network: AtomicalsNetwork::Testnet4,
+            apis: NetworkApi::default(),
+            ledger: UtxoLedger::new(),
         })
     }
+
+    /// 切换网络；`api_url` 为空时使用该网络的公共 ElectrumX 代理，费率来源随网络切换。
+    /// 钱包扩展处于其他网络时，查询 UTXO 和费率会返回错误。
+    #[wasm_bindgen(js_name = setNetwork)]
+    pub fn set_network(&mut self, network: AtomicalsNetwork, api_url: Option<String>) -> std::result::Result<(), JsValue> {
+        let api = network_api(network, api_url).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.apis.configure(network, Rc::new(api));
+        self.network = network;
+        Ok(())
+    }
+
+    /// 更换当前网络（默认 testnet4，可用 `setNetwork` 切换）的 ElectrumX 代理地址，
+    /// `mempool_url` 为空时通过 ElectrumX 估算费率
+    #[wasm_bindgen(js_name = setApiUrl)]
+    pub fn set_api_url(&mut self, url: String, mempool_url: Option<String>) {
+        let api = match mempool_url {
+            Some(mempool_url) => ElectrumApi::new(url).with_mempool_url(mempool_url),
+            None => ElectrumApi::new(url),
+        };
+        self.apis.configure(self.network, Rc::new(api));
+    }
 }
 
-#[async_trait(?Send)]
-impl WalletProvider for WizzProvider {
-    async fn get_network(&self) -> Result<Network> {
+impl WizzProvider {
+    /// 在 `network` 上使用其他后端，例如 `EsploraApi::with_atomicals` 组合的 Esplora 和 Atomicals 索引器
+    pub fn with_api(mut self, network: AtomicalsNetwork, api: impl AtomicalsApi + 'static) -> Self {
+        self.apis.configure(network, Rc::new(api));
+        self.network = network;
+        self
+    }
+
+    /// 钱包扩展当前网络对应的索引器
+    async fn api(&self) -> Result<Rc<dyn AtomicalsApi>> {
+        self.apis.for_network(self.atomicals_network().await?)
+    }
+
+    async fn atomicals_network(&self) -> Result<AtomicalsNetwork> {
         // 检查 accounts 是否存在
         let accounts = self.call_wallet_method("accounts", &[])?;
         let accounts_array: Array = accounts.unchecked_into();
@@ -112,8 +98,24 @@ impl WalletProvider for WizzProvider {
             self.call_wallet_method("requestAccounts", &[])?;
         }
 
-        // 默认使用 testnet
-        Ok(Network::Testnet)
+        // 以钱包扩展报告的网络为准，取不到时使用配置的网络
+        let reported = match self.call_wallet_method("getNetwork", &[]) {
+            Ok(result) if result.has_type::<Promise>() => JsFuture::from(result.unchecked_into::<Promise>()).await.ok(),
+            Ok(result) => Some(result),
+            Err(_) => None,
+        };
+        match reported.and_then(|value| value.as_string()) {
+            // 扩展报告的 testnet 不区分 testnet3 和 testnet4，地址编码相同时以配置的网络为准
+            Some(name) => Ok(resolve_network(name.parse()?, self.network)),
+            None => Ok(self.network),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl WalletProvider for WizzProvider {
+    async fn get_network(&self) -> Result<Network> {
+        Ok(self.atomicals_network().await?.bitcoin_network())
     }
 
     async fn get_public_key(&self) -> Result<PublicKey> {
@@ -207,88 +209,10 @@ impl WalletProvider for WizzProvider {
         let address = self.get_address().await?;
         log!("Got address: {}", address);
         
-        // 获取 scripthash
-        let addr = Address::from_str(&address)
-            .map_err(|e| Error::AddressError(format!("Invalid address: {}", e)))?
-            .require_network(Network::Testnet)
-            .map_err(|e| Error::AddressError(format!("Invalid network: {}", e)))?;
-            
-        let script_pubkey = addr.script_pubkey();
-        
-        // 计算 scripthash
-        let script_bytes = script_pubkey.as_bytes();
-        let hash = sha256::Hash::hash(script_bytes);
-        
-        // 反转字节序并转换为十六进制字符串
-        let scripthash = hash.to_byte_array()
-            .iter()
-            .rev()
-            .map(|b| format!("{:02x}", b))
-            .collect::<String>();
-        
-        log!("Calculated scripthash: {}", scripthash);
-        
-        // 构建 API URL
-        let api_url = format!(
-            "https://eptestnet4.wizz.cash/proxy/blockchain.atomicals.listscripthash?params=[\"{}\",true]",
-            scripthash
-        );
-        
-        log!("Fetching UTXOs from API: {}", api_url);
-        
-        // 发起 HTTP 请求
-        let response = reqwest::get(&api_url).await
-            .map_err(|e| Error::NetworkError(format!("Failed to fetch UTXOs: {}", e)))?;
-            
-        let response_text = response.text().await
-            .map_err(|e| Error::NetworkError(format!("Failed to get response text: {}", e)))?;
-            
-        log!("API Response: {}", response_text);
-        
-        let utxo_response: UtxoResponse = serde_json::from_str(&response_text)
-            .map_err(|e| Error::DeserializationError(format!("Failed to parse UTXO response: {}", e)))?;
-            
-        if !utxo_response.success {
-            return Err(Error::NetworkError("UTXO API request failed".into()));
-        }
-        
-        let total_utxos = utxo_response.response.utxos.len();
-        
-        // 过滤出 atomicals 为空的 UTXO
-        let filtered_utxos: Vec<UtxoItem> = utxo_response.response.utxos.into_iter()
-            .filter(|utxo| utxo.atomicals.as_object().map_or(true, |obj| obj.is_empty()))
-            .collect();
-        
-        let available_utxos = filtered_utxos.len();
-        log!("Found {} total UTXOs, {} available after filtering", 
-            total_utxos,
-            available_utxos
-        );
-        
-        // 转换为 Utxo 结构
-        let utxos = filtered_utxos.into_iter()
-            .map(|item| {
-                let outpoint = OutPoint::new(
-                    bitcoin::Txid::from_str(&item.txid)
-                        .map_err(|e| Error::TransactionError(format!("Invalid txid: {}", e)))?,
-                    item.vout,
-                );
-                
-                let txout = TxOut {
-                    value: Amount::from_sat(item.value),
-                    script_pubkey: script_pubkey.clone(),
-                };
-                
-                Ok(Utxo {
-                    outpoint,
-                    txout,
-                    height: Some(item.height),
-                })
-            })
-            .collect::<Result<Vec<_>>>()?;
-            
-        log!("Successfully parsed {} available UTXOs", utxos.len());
-        Ok(utxos)
+        let network = self.atomicals_network().await?;
+        let addr = network.parse_address(&address)?;
+
+        self.apis.for_network(network)?.list_utxos(&addr.script_pubkey()).await
     }
 
     async fn get_balance(&self) -> Result<Amount> {
@@ -304,32 +228,13 @@ impl WalletProvider for WizzProvider {
         Ok(Amount::from_sat(balance))
     }
 
+    fn utxo_ledger(&self) -> Option<UtxoLedger> {
+        Some(self.ledger.clone())
+    }
+
     async fn get_network_fee_rate(&self) -> Result<f64> {
-        log!("Getting fee rate from mempool.space API");
-        
-        let api_url = "https://mempool.space/testnet4/api/v1/fees/mempool-blocks";
-        
-        // 发起 HTTP 请求
-        let response = reqwest::get(api_url).await
-            .map_err(|e| Error::NetworkError(format!("Failed to fetch fee rate: {}", e)))?;
-            
-        let mempool_blocks: Vec<MempoolBlock> = response.json().await
-            .map_err(|e| Error::DeserializationError(format!("Failed to parse fee rate response: {}", e)))?;
-            
-        // 获取第一个区块的费率
-        let fee_rate = mempool_blocks.first()
-            .map(|block| {
-                if block.median_fee == 0.0 {
-                    1.0
-                } else {
-                    block.median_fee
-                }
-            })
-            .unwrap_or(1.0);
-            
-        log!("Got fee rate: {} sat/vB", fee_rate);
-        
-        Ok(fee_rate)
+        log!("Getting fee rate from API");
+        self.api().await?.fee_rate().await
     }
 
     async fn sign_transaction(&self, tx: Transaction, outputs: &[TxOut]) -> Result<Transaction> {
@@ -398,34 +303,7 @@ impl WalletProvider for WizzProvider {
     }
 
     async fn get_atomical_by_id(&self, atomical_id: &str) -> Result<AtomicalInfo> {
-        let url = format!(
-            "https://eptestnet4.wizz.cash/proxy/blockchain.atomicals.get_state?params=[\"{}\"]&_={}",
-            atomical_id,
-            js_sys::Date::now() as u64
-        );
-
-        let response = reqwest::get(&url).await
-            .map_err(|e| Error::NetworkError(format!("Failed to fetch atomical info: {}", e)))?;
-            
-        let atomical_response: AtomicalResponse = response.json().await
-            .map_err(|e| Error::DeserializationError(format!("Failed to deserialize response: {}", e)))?;
-
-        if !atomical_response.success {
-            return Err(Error::AtomicalNotFound(format!("Atomical {} not found", atomical_id)));
-        }
-
-        let mut result = atomical_response.response.result;
-        
-        // 确保 location 字段格式正确
-        for location in result.location_info.iter_mut() {
-            if !location.location.contains(':') {
-                // 使用 txid 和 index 构建正确的 location 格式
-                location.location = format!("{}:{}", location.txid, location.index);
-                log!("Fixed location format: {} -> {}", location.txid, location.location);
-            }
-        }
-
-        Ok(result)
+        self.api().await?.get_state(atomical_id).await
     }
 }
 
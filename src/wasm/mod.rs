@@ -1,13 +1,18 @@
 use wasm_bindgen::prelude::*;
-use crate::types::{Arc20Config, MintConfig, RealmConfig, subrealm::{SubrealmConfig, SubrealmClaimType}};
-use crate::operations::{mint_ft, mining::MiningOptions, mint_realm, mint_subrealm};
+use crate::types::{Arc20Config, AtomicalsNetwork, RealmConfig, subrealm::{SubrealmConfig, SubrealmClaimType}};
+use crate::operations::{mining::MiningOptions, mint_ft_with_backend, mint_realm_with_backend, mint_subrealm_with_backend, DefaultMiningBackend, MiningSession};
 use crate::wallet::web::WizzProvider;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use serde_json::Value;
 
 #[wasm_bindgen(js_name = "AtomicalsWasm")]
 pub struct Atomicals {
     wallet: WizzProvider,
+    // 多次铸造共用同一个挖矿后端，worker 只在第一次挖矿时创建
+    backend: DefaultMiningBackend,
+    // 下一次铸造使用的挖矿会话，未设置时每次铸造新建
+    next_session: RefCell<Option<MiningSession>>,
 }
 
 #[wasm_bindgen(js_class = "AtomicalsWasm")]
@@ -15,10 +20,28 @@ impl Atomicals {
     #[wasm_bindgen(constructor)]
     pub fn try_new() -> std::result::Result<Atomicals, JsValue> {
         let wallet = WizzProvider::try_new()?;
-        Ok(Atomicals { wallet })
+        Ok(Atomicals { wallet, backend: DefaultMiningBackend::default(), next_session: RefCell::new(None) })
+    }
+
+    /// 切换网络：mainnet、testnet4 可以使用公共 API，其他网络需要提供 ElectrumX 代理地址
+    #[wasm_bindgen(js_name = setNetwork)]
+    pub fn set_network(&mut self, network: AtomicalsNetwork, api_url: Option<String>) -> std::result::Result<(), JsValue> {
+        self.wallet.set_network(network, api_url)
+    }
+
+    /// 下一次铸造在 `session` 中挖矿，JS 保留的会话可以取消挖矿或读取进度；只对下一次铸造生效
+    #[wasm_bindgen(js_name = setMiningSession)]
+    pub fn set_mining_session(&self, session: &MiningSession) {
+        *self.next_session.borrow_mut() = Some(session.clone());
+    }
+
+    // 取出 setMiningSession 设置的会话
+    fn take_session(&self) -> MiningSession {
+        self.next_session.borrow_mut().take().unwrap_or_default()
     }
 
     #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
     pub async fn mint_ft(
         &self,
         tick: String,
@@ -27,6 +50,7 @@ impl Atomicals {
         bitwork_r: Option<String>,
         num_workers: Option<u32>,
         batch_size: Option<u32>,
+        receive_address: Option<String>,
     ) -> std::result::Result<JsValue, JsValue> {
         let config = Arc20Config {
             tick,
@@ -34,18 +58,20 @@ impl Atomicals {
             mint_bitworkc: bitwork_c,
             mint_bitworkr: bitwork_r,
             meta: HashMap::new(),
+            receive_address,
         };
 
         let mining_options = if num_workers.is_some() || batch_size.is_some() {
             Some(MiningOptions {
                 num_workers: num_workers.unwrap_or(4),
                 batch_size: batch_size.unwrap_or(1000),
+                ..MiningOptions::new()
             })
         } else {
             None
         };
 
-        let result = mint_ft::mint_ft(&self.wallet, config, mining_options).await
+        let result = mint_ft_with_backend(&self.wallet, config, mining_options, &self.backend, self.take_session()).await
             .map_err(|e| JsValue::from_str(&e.to_string()))?;
             
         serde_wasm_bindgen::to_value(&result)
@@ -53,6 +79,7 @@ impl Atomicals {
     }
 
     #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
     pub async fn mint_realm(
         &self,
         name: String,
@@ -64,6 +91,7 @@ impl Atomicals {
         parent_owner: Option<String>,
         num_workers: Option<u32>,
         batch_size: Option<u32>,
+        receive_address: Option<String>,
     ) -> std::result::Result<JsValue, JsValue> {
         let config = RealmConfig {
             name,
@@ -73,18 +101,20 @@ impl Atomicals {
             parent,
             parent_owner,
             sats_output,
+            receive_address,
         };
 
         let mining_options = if num_workers.is_some() || batch_size.is_some() {
             Some(MiningOptions {
                 num_workers: num_workers.unwrap_or(4),
                 batch_size: batch_size.unwrap_or(1000),
+                ..MiningOptions::new()
             })
         } else {
             None
         };
 
-        let result = mint_realm(&self.wallet, config, mining_options)
+        let result = mint_realm_with_backend(&self.wallet, config, mining_options, &self.backend, self.take_session())
             .await
             .map_err(|e| JsValue::from_str(&e.to_string()))?;
             
@@ -93,6 +123,7 @@ impl Atomicals {
     }
     
     #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
     pub async fn mint_subrealm(
         &self,
         name: String,
@@ -107,6 +138,7 @@ impl Atomicals {
         init: Option<String>,
         num_workers: Option<u32>,
         batch_size: Option<u32>,
+        receive_address: Option<String>,
     ) -> std::result::Result<JsValue, JsValue> {
         // 解析 claim_type
         let claim_type = match claim_type.to_lowercase().as_str() {
@@ -157,6 +189,7 @@ impl Atomicals {
             sats_output,
             fee_rate: None, // 使用网络费率
             rule_outputs: None, // 规则输出在验证规则时设置
+            receive_address,
         };
 
         // 创建挖矿选项
@@ -164,13 +197,13 @@ impl Atomicals {
             Some(MiningOptions {
                 num_workers: num_workers.unwrap_or(4),
                 batch_size: batch_size.unwrap_or(1000),
+                ..MiningOptions::new()
             })
         } else {
             None
         };
 
-        // 调用 mint_subrealm 函数
-        let result = mint_subrealm(&self.wallet, config, mining_options)
+        let result = mint_subrealm_with_backend(&self.wallet, config, mining_options, &self.backend, self.take_session())
             .await
             .map_err(|e| JsValue::from_str(&e.to_string()))?;
             
@@ -179,3 +212,19 @@ impl Atomicals {
             .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
     }
 }
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen(js_class = "AtomicalsWasm")]
+impl Atomicals {
+    /// 使用 JS 创建的 worker 池挖矿，池的大小由 JS 通过 `resize` 控制；传入 `undefined` 恢复自动管理
+    #[wasm_bindgen(js_name = setWorkerPool)]
+    pub fn set_worker_pool(&self, pool: Option<crate::operations::WorkerPool>) {
+        self.backend.set_pool(pool);
+    }
+
+    /// 当前使用的 worker 池，可以在铸造之间调用 `resize` 或 `terminate`
+    #[wasm_bindgen(js_name = workerPool)]
+    pub fn worker_pool(&self) -> Option<crate::operations::WorkerPool> {
+        self.backend.current_pool()
+    }
+}
@@ -5,8 +5,6 @@
     let initializationPromise = null;
     const MAX_RETRIES = 3;
     const RETRY_DELAY = 1000; // 1 second
-    const MAX_SEQUENCE = 0xffffffff;
-    const BATCH_SIZE = 100000; // 增加批次大小
 
     function log(message, ...args) {
         console.log('[Worker]', message, ...args);
@@ -26,18 +24,19 @@
         log(`Loading WASM module (attempt ${attempt}/${MAX_RETRIES})...`);
         
         try {
-            // 使用动态 import 加载模块
-            log('Loading atomicals_rs.js...');
-            const module = await import('./atomicals_rs.js');
-            log('Successfully loaded atomicals_rs.js');
+            // 使用动态 import 加载模块；Blob worker 由库在脚本前写入模块地址
+            const moduleUrl = self.ATOMICALS_MODULE_URL || './atomicals_rs.js';
+            log(`Loading ${moduleUrl}...`);
+            const module = await import(moduleUrl);
+            log(`Successfully loaded ${moduleUrl}`);
 
             // 初始化 WASM 模块
             await module.default();
             log('WASM module initialized successfully');
 
-            // 验证必要的函数是否存在
-            if (typeof module.mine_nonce_range !== 'function') {
-                throw new Error('mine_nonce_range function not found in WASM module');
+            // 验证必要的导出是否存在
+            if (typeof module.WorkerTaskRunner !== 'function') {
+                throw new Error('WorkerTaskRunner not found in WASM module');
             }
 
             return module;
@@ -75,7 +74,46 @@
     // 启动初始化
     initializationPromise = initializeWorker();
 
-    // 处理挖矿消息
+    // 当前正在执行的任务，收到 stop 或新任务时替换
+    let currentRunner = null;
+
+    function postError(runner, err) {
+        const message = err && err.message ? err.message : String(err);
+        error('Mining task failed:', message);
+        try {
+            const job = runner ? runner.job() : 0;
+            const worker = runner ? runner.worker() : null;
+            self.postMessage(wasmModule.worker_error_message(job, worker, message));
+        } catch (encodeErr) {
+            // 模块未加载时无法按协议编码，主线程会把它当作格式错误处理
+            self.postMessage({ type: 'error', message });
+        }
+    }
+
+    // 每批处理完让出事件循环，以便及时收到 stop 消息
+    function runBatch(runner) {
+        if (runner !== currentRunner) {
+            return;
+        }
+
+        try {
+            const response = runner.next_batch();
+            self.postMessage(response);
+
+            if (response.type === 'progress') {
+                setTimeout(() => runBatch(runner), 0);
+            } else {
+                log(`Worker ${runner.worker()} finished: ${response.type}`);
+                currentRunner = null;
+                runner.free();
+            }
+        } catch (err) {
+            currentRunner = null;
+            postError(runner, err);
+        }
+    }
+
+    // 处理主线程消息，消息格式由 Rust 端的 WorkerRequest 定义
     self.onmessage = async function(e) {
         try {
             // 等待初始化完成
@@ -85,129 +123,22 @@
                 throw new Error('Worker not initialized');
             }
 
-            const { tx_wrapper, start_nonce, end_nonce, bitwork } = e.data;
-            
-            // 如果收到停止信号，立即停止
-            if (e.data.type === 'stop') {
-                log('Received stop signal, terminating worker');
-                return;
+            // 格式或协议版本不符时抛出错误
+            const runner = wasmModule.WorkerTaskRunner.from_message(e.data);
+            if (currentRunner) {
+                currentRunner.free();
+                currentRunner = null;
             }
-            
-            log('Received mining task:', { 
-                start_nonce, 
-                end_nonce: end_nonce || MAX_SEQUENCE,
-                bitwork 
-            });
-            log('Transaction hex:', tx_wrapper.hex.substring(0, 50) + '...');
-
-            // 创建 WasmTransaction 实例
-            const wasmTx = new wasmModule.WasmTransaction(tx_wrapper.hex);
-            log('Created WasmTransaction instance');
-            
-            let currentNonce = start_nonce;
-            const targetEnd = end_nonce || MAX_SEQUENCE;
-
-            function mineNextBatch() {
-                const batchEnd = Math.min(currentNonce + BATCH_SIZE, targetEnd);
-                log(`Mining batch: nonce range ${currentNonce}-${batchEnd}, target prefix: ${bitwork}`);
-                
-                try {
-                    // 调用 WASM 模块的挖矿函数
-                    log(`Calling mine_nonce_range with params:`, {
-                        currentNonce,
-                        batchEnd,
-                        bitwork,
-                        total_range: targetEnd - start_nonce
-                    });
-
-                    const result = wasmModule.mine_nonce_range(
-                        wasmTx,
-                        currentNonce,
-                        batchEnd,
-                        bitwork
-                    );
-
-                    log(`Batch result: ${result} (type: ${typeof result})`);
-
-                    if (result !== undefined && result !== null) {
-                        // 获取修改后的交易hex
-                        const modifiedTxHex = wasmTx.to_hex();
-                        self.postMessage({
-                            type: 'success',
-                            success: true,
-                            nonce: result,
-                            tx: { hex: modifiedTxHex },  // 返回修改后的交易
-                            finished: true
-                        });
-                        return;  // 找到有效 nonce 后立即返回
-                    } else {
-                        log(`No valid nonce found in range ${currentNonce}-${batchEnd} for prefix ${bitwork}`);
-                        currentNonce = batchEnd;
-                        
-                        if (currentNonce < targetEnd) {
-                            const progress = Math.floor((currentNonce - start_nonce) / (targetEnd - start_nonce) * 100);
-                            log(`Mining progress: ${progress}%, scheduling next batch starting at ${currentNonce}`);
-                            self.postMessage({
-                                type: 'progress',
-                                success: false,
-                                nonce: null,
-                                tx: null,
-                                progress: {
-                                    current: currentNonce,
-                                    total: targetEnd,
-                                    percent: progress
-                                }
-                            });
-                            setTimeout(mineNextBatch, 0);
-                        } else {
-                            log(`Completed search through entire nonce range (${start_nonce} to ${targetEnd}) without finding a solution`);
-                            self.postMessage({
-                                type: 'exhausted',
-                                success: false,
-                                nonce: null,
-                                tx: null,
-                                finished: true,
-                                exhausted: true
-                            });
-                        }
-                    }
-                } catch (err) {
-                    error(`Error in batch ${currentNonce}-${batchEnd}:`, err);
-                    error('Error details:', {
-                        message: err.message,
-                        stack: err.stack,
-                        currentNonce,
-                        batchEnd,
-                        bitwork
-                    });
-                    self.postMessage({
-                        type: 'error',
-                        success: false,
-                        nonce: null,
-                        tx: null,
-                        error: err.message
-                    });
-                }
+            if (!runner) {
+                log('Received stop signal');
+                return;
             }
 
-            // 开始挖矿
-            log('Starting mining process with configuration:', {
-                start_nonce,
-                end_nonce: targetEnd,
-                bitwork,
-                batch_size: BATCH_SIZE,
-                max_sequence: MAX_SEQUENCE
-            });
-            mineNextBatch();
+            log(`Received mining task for worker ${runner.worker()}`);
+            currentRunner = runner;
+            runBatch(runner);
         } catch (err) {
-            error('Error processing mining task:', err);
-            self.postMessage({
-                type: 'error',
-                success: false,
-                nonce: null,
-                tx: null,
-                error: err.message
-            });
+            postError(null, err);
         }
     };
 })();